@@ -0,0 +1,95 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use portal_market::{AlertMonitor, FredService};
+use portal_types::{
+    AcknowledgeAlertsRequest, Alert, ApiError, EarningsSource, Role, SaveAlertRulesRequest,
+};
+
+use crate::auth::{require_role, Authenticator};
+use crate::earnings::EarningsStore;
+use crate::error::HttpError;
+
+/// Checks the rules now, with the current earnings source.
+pub async fn check(
+    monitor: &AlertMonitor,
+    fred: &FredService,
+    earnings: &EarningsStore,
+) -> std::io::Result<Vec<Alert>> {
+    let source = earnings.source();
+    let source = source
+        .as_ref()
+        .map(|table| table as &(dyn EarningsSource + Sync));
+    let raised = monitor.check(fred, source, Utc::now()).await?;
+    for alert in &raised {
+        log::info!("Alert raised: {}", alert.message);
+    }
+    Ok(raised)
+}
+
+pub async fn list_alerts(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    monitor: web::Data<AlertMonitor>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    Ok(HttpResponse::Ok().json(monitor.store.lock().unwrap().alerts()))
+}
+
+pub async fn acknowledge(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    monitor: web::Data<AlertMonitor>,
+    request: web::Json<AcknowledgeAlertsRequest>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let mut store = monitor.store.lock().unwrap();
+    store.acknowledge(&request.ids).map_err(|e| {
+        log::error!("Could not write alerts: {}", e);
+        ApiError::internal("Could not save the alerts")
+    })?;
+    Ok(HttpResponse::Ok().json(store.alerts()))
+}
+
+pub async fn list_rules(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    monitor: web::Data<AlertMonitor>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    Ok(HttpResponse::Ok().json(monitor.store.lock().unwrap().rules()))
+}
+
+pub async fn save_rules(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    monitor: web::Data<AlertMonitor>,
+    request: web::Json<SaveAlertRulesRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Analyst)?;
+
+    let request = request.into_inner();
+    let mut store = monitor.store.lock().unwrap();
+    let rules = store.set_rules(request.rules)?;
+    log::info!("{} saved {} alert rules", actor.username(), rules.len());
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+pub async fn check_now(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    monitor: web::Data<AlertMonitor>,
+    fred: web::Data<FredService>,
+    earnings: web::Data<EarningsStore>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::Analyst)?;
+
+    check(&monitor, &fred, &earnings).await.map_err(|e| {
+        log::error!("Could not write alerts: {}", e);
+        ApiError::internal("Could not save the alerts")
+    })?;
+    Ok(HttpResponse::Ok().json(monitor.store.lock().unwrap().alerts()))
+}
@@ -0,0 +1,20 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use portal_types::{ApiError, AuditQuery, Role};
+
+use crate::auth::{require_role, Authenticator};
+use crate::error::HttpError;
+
+pub async fn query_audit_log(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    query: web::Query<AuditQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::Admin)?;
+
+    let entries = auth.audit_log.query(&query).map_err(|e| {
+        log::error!("Could not read audit log: {}", e);
+        ApiError::internal("Could not read audit log")
+    })?;
+    Ok(HttpResponse::Ok().json(entries))
+}
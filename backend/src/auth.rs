@@ -0,0 +1,448 @@
+use actix_session::Session;
+use actix_web::{
+    dev::Payload, web, FromRequest, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use chrono::Utc;
+use portal_auth::{CurrentUser, LoginStep, SessionRecord};
+use portal_types::{ApiError, LoginRequest, LoginResponse, Role, TwoFactorLoginRequest, UserInfo};
+use std::future::{ready, Ready};
+
+use crate::config::Config;
+use crate::error::HttpError;
+
+pub use portal_auth::Authenticator;
+
+const SESSION_TOKEN_KEY: &str = "session_token";
+
+fn session_token(session: &Session) -> Option<String> {
+    session.get::<String>(SESSION_TOKEN_KEY).ok().flatten()
+}
+
+/// Resolves the session for a request that counts as user activity and checks
+/// that the account holds at least `required`.
+pub fn require_role(
+    session: &Session,
+    auth: &Authenticator,
+    required: Role,
+) -> Result<CurrentUser, HttpError> {
+    let token = session_token(session).ok_or_else(ApiError::session_expired)?;
+    Ok(auth.require_role(&token, required)?)
+}
+
+pub async fn login(
+    req: HttpRequest,
+    session: Session,
+    auth: web::Data<Authenticator>,
+    config: web::Data<Config>,
+    request: web::Json<LoginRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let client = client_address(&req, &config);
+
+    // Password hashing would stall the worker, so it goes to the blocking pool
+    let step = {
+        let auth = auth.clone();
+        let request = request.into_inner();
+        web::block(move || auth.login(&request.username, &request.password, &client, Utc::now()))
+            .await??
+    };
+    match step {
+        LoginStep::Session(record) => start_session(&session, &auth, record),
+        LoginStep::TwoFactor(challenge) => Ok(HttpResponse::Ok().json(LoginResponse {
+            two_factor_token: Some(challenge),
+            ..LoginResponse::new("Enter your authentication code")
+        })),
+    }
+}
+
+pub async fn login_two_factor(
+    req: HttpRequest,
+    session: Session,
+    auth: web::Data<Authenticator>,
+    config: web::Data<Config>,
+    request: web::Json<TwoFactorLoginRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let client = client_address(&req, &config);
+
+    let record = auth.login_two_factor(
+        &request.two_factor_token,
+        &request.code,
+        &client,
+        Utc::now(),
+    )?;
+    start_session(&session, &auth, record)
+}
+
+// The session token goes in a fresh cookie so a pre-login cookie is never reused
+fn start_session(
+    session: &Session,
+    auth: &Authenticator,
+    record: SessionRecord,
+) -> Result<HttpResponse, HttpError> {
+    session.renew();
+    if session.insert(SESSION_TOKEN_KEY, &record.token).is_err() {
+        auth.sessions.lock().unwrap().revoke(&record.token);
+        return Err(ApiError::internal("Could not create session").into());
+    }
+
+    Ok(HttpResponse::Ok().json(LoginResponse::new("Login successful")))
+}
+
+// Forwarded headers are only trusted when running behind a known proxy
+pub(crate) fn client_address(req: &HttpRequest, config: &Config) -> String {
+    if config.trust_proxy {
+        if let Some(addr) = req.connection_info().realip_remote_addr() {
+            return addr.to_string();
+        }
+    }
+
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The client address of a request, for handlers that only need it for
+/// their audit entries.
+pub struct ClientAddress(pub String);
+
+impl FromRequest for ClientAddress {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = req
+            .app_data::<web::Data<Config>>()
+            .expect("the config is registered as app data");
+        ready(Ok(Self(client_address(req, config))))
+    }
+}
+
+pub async fn logout(
+    req: HttpRequest,
+    session: Session,
+    auth: web::Data<Authenticator>,
+    config: web::Data<Config>,
+) -> impl Responder {
+    if let Some(token) = session_token(&session) {
+        auth.logout(&token, &client_address(&req, &config));
+    }
+    session.purge();
+
+    HttpResponse::Ok().json(LoginResponse::new("Logged out successfully"))
+}
+
+// Polled by the dashboard, so it must not extend the idle timeout itself
+pub async fn check_auth(session: Session, auth: web::Data<Authenticator>) -> impl Responder {
+    let user = session_token(&session).and_then(|token| auth.session_user(&token, false));
+    let user_info = match user {
+        Some(user) => auth.user_info(&user),
+        None => {
+            session.purge();
+            UserInfo::logged_out()
+        }
+    };
+
+    HttpResponse::Ok().json(user_info)
+}
+
+pub async fn refresh_session(session: Session, auth: web::Data<Authenticator>) -> impl Responder {
+    match require_role(&session, &auth, Role::ReadOnly) {
+        Ok(user) => HttpResponse::Ok().json(auth.user_info(&user)),
+        Err(e) => {
+            session.purge();
+            e.error_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_session::{storage::CookieSessionStore, SessionMiddleware};
+    use actix_web::{
+        cookie::{Cookie, Key},
+        dev::{Service, ServiceResponse},
+        http::{header, StatusCode},
+        test, App,
+    };
+    use portal_auth::{totp, AuditLog, SessionLimits, UserStore};
+    use portal_market::{AlertMonitor, AlertStore};
+    use portal_types::{AuditEvent, AuditOutcome, AuditQuery, ErrorCode};
+    use serde_json::json;
+
+    // A store with one account per role, each with the password "<name>-password"
+    fn authenticator(dir: &tempfile::TempDir, limits: SessionLimits) -> Authenticator {
+        let mut users = UserStore::open(dir.path().join("users.json")).unwrap();
+        for (username, role) in [
+            ("admin", Role::Admin),
+            ("analyst", Role::Analyst),
+            ("reader", Role::ReadOnly),
+        ] {
+            users
+                .create_user(username, &format!("{}-password", username), role)
+                .unwrap();
+        }
+        Authenticator::new(users, limits, audit_log(dir))
+    }
+
+    async fn service(
+        dir: &tempfile::TempDir,
+        auth: Authenticator,
+    ) -> impl Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>
+    {
+        let alerts = AlertMonitor::new(AlertStore::open(dir.path().join("alerts.json")).unwrap());
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(auth))
+                .app_data(web::Data::new(alerts))
+                .app_data(web::Data::new(Config::from_env()))
+                .wrap(
+                    SessionMiddleware::builder(CookieSessionStore::default(), Key::generate())
+                        .cookie_secure(false)
+                        .build(),
+                )
+                .configure(crate::api_routes),
+        )
+        .await
+    }
+
+    async fn login(
+        app: &impl Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>,
+        username: &str,
+        password: &str,
+    ) -> ServiceResponse {
+        let request = test::TestRequest::post()
+            .uri("/api/login")
+            .set_json(json!({ "username": username, "password": password }))
+            .to_request();
+        test::call_service(app, request).await
+    }
+
+    fn session_cookie(response: &ServiceResponse) -> Cookie<'static> {
+        response
+            .response()
+            .cookies()
+            .next()
+            .expect("a session cookie")
+            .into_owned()
+    }
+
+    fn audit_log(dir: &tempfile::TempDir) -> AuditLog {
+        AuditLog::new(dir.path().join("audit.jsonl"))
+    }
+
+    async fn error(response: ServiceResponse) -> (StatusCode, ErrorCode) {
+        let status = response.status();
+        let body: ApiError = test::read_body_json(response).await;
+        (status, body.code)
+    }
+
+    #[actix_web::test]
+    async fn routes_require_their_role() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = service(&dir, authenticator(&dir, SessionLimits::default())).await;
+
+        let mut cookies = Vec::new();
+        for username in ["reader", "analyst", "admin"] {
+            let response = login(&app, username, &format!("{}-password", username)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            cookies.push(session_cookie(&response));
+        }
+
+        // ReadOnly, Analyst and Admin routes in turn, and the lowest role each admits
+        let routes: [(fn() -> test::TestRequest, usize); 3] = [
+            (|| test::TestRequest::get().uri("/api/alerts"), 0),
+            (
+                || {
+                    test::TestRequest::post()
+                        .uri("/api/alerts/rules")
+                        .set_json(json!({ "rules": [] }))
+                },
+                1,
+            ),
+            (|| test::TestRequest::get().uri("/api/users"), 2),
+        ];
+        for (route, lowest) in routes {
+            for (rank, cookie) in cookies.iter().enumerate() {
+                let request = route().cookie(cookie.clone()).to_request();
+                let response = test::call_service(&app, request).await;
+                if rank >= lowest {
+                    assert_eq!(response.status(), StatusCode::OK);
+                } else {
+                    let (status, code) = error(response).await;
+                    assert_eq!(
+                        (status, code),
+                        (StatusCode::FORBIDDEN, ErrorCode::Forbidden)
+                    );
+                }
+            }
+        }
+
+        // Reader is denied the Analyst and Admin routes, analyst the Admin one
+        let denied = audit_log(&dir)
+            .query(&AuditQuery::default())
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.event == AuditEvent::AccessDenied)
+            .count();
+        assert_eq!(denied, 3);
+    }
+
+    #[actix_web::test]
+    async fn missing_and_expired_sessions_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = service(&dir, authenticator(&dir, SessionLimits::default())).await;
+
+        let request = test::TestRequest::get().uri("/api/alerts").to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(
+            error(response).await,
+            (StatusCode::UNAUTHORIZED, ErrorCode::SessionExpired)
+        );
+
+        // A cookie for a session that has since ended
+        let cookie = session_cookie(&login(&app, "reader", "reader-password").await);
+        let request = test::TestRequest::post()
+            .uri("/api/logout")
+            .cookie(cookie.clone())
+            .to_request();
+        test::call_service(&app, request).await;
+        let request = test::TestRequest::get()
+            .uri("/api/alerts")
+            .cookie(cookie)
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(
+            error(response).await,
+            (StatusCode::UNAUTHORIZED, ErrorCode::SessionExpired)
+        );
+
+        // Sessions that outlive the idle timeout
+        let dir = tempfile::tempdir().unwrap();
+        let limits = SessionLimits {
+            idle_timeout: chrono::Duration::zero(),
+            ..SessionLimits::default()
+        };
+        let app = service(&dir, authenticator(&dir, limits)).await;
+        let cookie = session_cookie(&login(&app, "reader", "reader-password").await);
+        let request = test::TestRequest::get()
+            .uri("/api/alerts")
+            .cookie(cookie)
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(
+            error(response).await,
+            (StatusCode::UNAUTHORIZED, ErrorCode::SessionExpired)
+        );
+    }
+
+    #[actix_web::test]
+    async fn failed_logins_lock_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = service(&dir, authenticator(&dir, SessionLimits::default())).await;
+
+        for _ in 0..3 {
+            let response = login(&app, "analyst", "wrong-password").await;
+            assert_eq!(
+                error(response).await,
+                (StatusCode::UNAUTHORIZED, ErrorCode::InvalidCredentials)
+            );
+        }
+        let response = login(&app, "analyst", "wrong-password").await;
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        assert_eq!(
+            error(response).await,
+            (StatusCode::TOO_MANY_REQUESTS, ErrorCode::LockedOut)
+        );
+
+        // The right password is refused too until the wait is over
+        let response = login(&app, "analyst", "analyst-password").await;
+        assert_eq!(
+            error(response).await,
+            (StatusCode::TOO_MANY_REQUESTS, ErrorCode::LockedOut)
+        );
+
+        let outcomes: Vec<_> = audit_log(&dir)
+            .query(&AuditQuery::default())
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.event, entry.outcome))
+            .collect();
+        assert_eq!(outcomes.len(), 5);
+        assert_eq!(outcomes[0], (AuditEvent::Login, AuditOutcome::Throttled));
+        assert!(outcomes[1..]
+            .iter()
+            .all(|outcome| *outcome == (AuditEvent::Login, AuditOutcome::Failure)));
+    }
+
+    #[actix_web::test]
+    async fn two_factor_logins_need_a_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let auth = authenticator(&dir, SessionLimits::default());
+        let now = Utc::now();
+        let secret = {
+            let mut users = auth.users.lock().unwrap();
+            let secret = users.begin_totp_enrollment("analyst").unwrap();
+            let earlier = now - chrono::Duration::seconds(totp::STEP_SECONDS * 2);
+            users
+                .confirm_totp("analyst", &code(&secret, earlier), earlier)
+                .unwrap();
+            secret
+        };
+        let app = service(&dir, auth).await;
+
+        // The password alone gives a challenge, not a session
+        let response = login(&app, "analyst", "analyst-password").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.response().cookies().count(), 0);
+        let body: LoginResponse = test::read_body_json(response).await;
+        let challenge = body.two_factor_token.unwrap();
+
+        let second_factor = |code: String| {
+            test::TestRequest::post()
+                .uri("/api/login/two-factor")
+                .set_json(json!({ "two_factor_token": challenge, "code": code }))
+                .to_request()
+        };
+        let response = test::call_service(&app, second_factor("000000x".to_string())).await;
+        assert_eq!(
+            error(response).await,
+            (StatusCode::UNAUTHORIZED, ErrorCode::InvalidTwoFactorCode)
+        );
+
+        let response = test::call_service(&app, second_factor(code(&secret, now))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let request = test::TestRequest::get()
+            .uri("/api/alerts")
+            .cookie(session_cookie(&response))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, request).await.status(),
+            StatusCode::OK
+        );
+
+        let outcomes: Vec<_> = audit_log(&dir)
+            .query(&AuditQuery::default())
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.event, entry.outcome, entry.detail))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (AuditEvent::Login, AuditOutcome::Success, None),
+                (
+                    AuditEvent::Login,
+                    AuditOutcome::Failure,
+                    Some("invalid authentication code".to_string())
+                ),
+            ]
+        );
+    }
+
+    fn code(secret: &str, at: chrono::DateTime<Utc>) -> String {
+        let key = data_encoding::BASE32_NOPAD
+            .decode(secret.as_bytes())
+            .unwrap();
+        format!("{:06}", totp::code_at(&key, totp::step_at(at)))
+    }
+}
@@ -0,0 +1,98 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use portal_types::backtest::backtest;
+use portal_types::{ApiError, BacktestRequest, ErrorCode, ReturnsSummary, ReturnsTable, Role};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::auth::{require_role, Authenticator};
+use crate::error::HttpError;
+
+/// Monthly returns per ticker for the backtester, from a CSV file that is
+/// re-read whenever it changes.
+pub struct ReturnsState {
+    path: PathBuf,
+    cached: Mutex<Option<(SystemTime, Option<ReturnsTable>)>>,
+}
+
+impl ReturnsState {
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn table(&self) -> Result<ReturnsTable, ApiError> {
+        let unavailable = || ApiError::new(ErrorCode::NotFound, "No monthly returns are available");
+        let modified = fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .map_err(|e| {
+                log::error!("Could not read {}: {}", self.path.display(), e);
+                unavailable()
+            })?;
+
+        let mut cached = self.cached.lock().unwrap();
+        if cached.as_ref().map(|(at, _)| *at) != Some(modified) {
+            let table = fs::read_to_string(&self.path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| ReturnsTable::parse_csv(&text))
+                .inspect_err(|e| log::error!("Ignoring {}: {}", self.path.display(), e))
+                .ok();
+            *cached = Some((modified, table));
+        }
+        cached
+            .as_ref()
+            .and_then(|(_, table)| table.clone())
+            .ok_or_else(unavailable)
+    }
+}
+
+pub async fn get_returns(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    returns: web::Data<ReturnsState>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let table = returns.table()?;
+    Ok(HttpResponse::Ok().json(ReturnsSummary::from(&table)))
+}
+
+pub async fn run(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    returns: web::Data<ReturnsState>,
+    request: web::Json<BacktestRequest>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let table = returns.table()?;
+    let result = backtest(&table, &request).map_err(ApiError::validation)?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_returns_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("returns.csv");
+        let state = ReturnsState::open(&path);
+        assert_eq!(state.table().unwrap_err().code, ErrorCode::NotFound);
+
+        fs::write(&path, "# comment\nmonth,inflation,VTI\n2024-01,0.3,1.5\n").unwrap();
+        let summary = ReturnsSummary::from(&state.table().unwrap());
+        assert_eq!(summary.tickers, ["VTI"]);
+        assert_eq!(summary.first_month, summary.last_month);
+
+        // An unreadable file is reported the same way as a missing one
+        let other = dir.path().join("broken.csv");
+        fs::write(&other, "VTI\n1").unwrap();
+        assert!(ReturnsState::open(&other).table().is_err());
+    }
+}
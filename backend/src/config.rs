@@ -0,0 +1,159 @@
+use actix_web::cookie::Key;
+use portal_auth::SessionLimits;
+use portal_types::EarningsBasis;
+use std::path::PathBuf;
+
+// Server settings, overridable through the environment
+#[derive(Clone)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub dist_dir: PathBuf,
+    pub allowed_origins: Vec<String>,
+    pub cookie_secure: bool,
+    pub trust_proxy: bool,
+    pub session_key: Key,
+    pub session_limits: SessionLimits,
+    pub users_file: PathBuf,
+    pub audit_log_file: PathBuf,
+    pub fred_api_key: Option<String>,
+    pub fred_api_url: String,
+    pub fred_cache_file: PathBuf,
+    pub earnings_file: PathBuf,
+    pub earnings_import_file: PathBuf,
+    pub earnings_basis: EarningsBasis,
+    pub alerts_file: PathBuf,
+    /// Minutes between alert checks; 0 only checks on request
+    pub alert_check_minutes: u64,
+    pub funds_dir: PathBuf,
+    pub fund_versions_file: PathBuf,
+    pub returns_file: PathBuf,
+    pub bootstrap_username: String,
+    pub bootstrap_password: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+        let port = std::env::var("PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(8080);
+        let dist_dir = std::env::var("DIST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("dist"));
+        let allowed_origins = std::env::var("ALLOWED_ORIGINS")
+            .unwrap_or_else(|_| "http://127.0.0.1:8000,http://localhost:8000".to_string())
+            .split(',')
+            .map(|origin| origin.trim().to_string())
+            .filter(|origin| !origin.is_empty())
+            .collect();
+        let cookie_secure = std::env::var("COOKIE_SECURE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let users_file = std::env::var("USERS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/users.json"));
+        let audit_log_file = std::env::var("AUDIT_LOG_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/audit.jsonl"));
+
+        // Without a key the FRED proxy only serves what is already cached
+        let fred_api_key = std::env::var("FRED_API_KEY").ok();
+        let fred_api_url = std::env::var("FRED_API_URL")
+            .unwrap_or_else(|_| "https://api.stlouisfed.org/fred".to_string());
+        let fred_cache_file = std::env::var("FRED_CACHE_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/fred-cache.json"));
+
+        // The maintained file is checked in; imports are local to this server
+        let earnings_file = std::env::var("EARNINGS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("MS-Portal-Website/data/sp500-earnings.csv"));
+        let earnings_import_file = std::env::var("EARNINGS_IMPORT_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/earnings-import.json"));
+        let earnings_basis = std::env::var("EARNINGS_BASIS")
+            .ok()
+            .and_then(|v| EarningsBasis::from_label(&v))
+            .unwrap_or_default();
+
+        let alerts_file = std::env::var("ALERTS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/alerts.json"));
+        let alert_check_minutes = std::env::var("ALERT_CHECK_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+
+        let funds_dir = std::env::var("FUNDS_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("MS-Portal-Website/data/funds"));
+        let fund_versions_file = std::env::var("FUND_VERSIONS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/fund-versions.json"));
+        let returns_file = std::env::var("RETURNS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("MS-Portal-Website/data/monthly-returns.csv"));
+
+        // Behind Render's load balancer every peer address is the proxy's
+        let trust_proxy = std::env::var("TRUST_PROXY")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        // Only used to seed the first account when the user store is empty
+        let bootstrap_username =
+            std::env::var("LOGIN_USERNAME").unwrap_or_else(|_| "admin".to_string());
+        let bootstrap_password = std::env::var("LOGIN_PASSWORD").ok();
+
+        // Sessions do not survive a restart unless a fixed key is configured
+        let session_key = match std::env::var("SESSION_KEY") {
+            Ok(secret) if secret.len() >= 64 => Key::from(secret.as_bytes()),
+            _ => {
+                log::warn!("SESSION_KEY not set (or shorter than 64 bytes), using a random key");
+                Key::generate()
+            }
+        };
+
+        let defaults = SessionLimits::default();
+        let session_limits = SessionLimits {
+            idle_timeout: std::env::var("SESSION_IDLE_MINUTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(chrono::Duration::minutes)
+                .unwrap_or(defaults.idle_timeout),
+            absolute_timeout: std::env::var("SESSION_MAX_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(chrono::Duration::hours)
+                .unwrap_or(defaults.absolute_timeout),
+        };
+
+        Self {
+            host,
+            port,
+            dist_dir,
+            allowed_origins,
+            cookie_secure,
+            trust_proxy,
+            session_key,
+            session_limits,
+            users_file,
+            audit_log_file,
+            fred_api_key,
+            fred_api_url,
+            fred_cache_file,
+            earnings_file,
+            earnings_import_file,
+            earnings_basis,
+            alerts_file,
+            alert_check_minutes,
+            funds_dir,
+            fund_versions_file,
+            returns_file,
+            bootstrap_username,
+            bootstrap_password,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use portal_types::{ApiError, ImportEarningsRequest, Role};
+
+use crate::auth::{require_role, Authenticator};
+use crate::error::HttpError;
+
+pub use portal_market::EarningsStore;
+
+pub async fn get_status(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    earnings: web::Data<EarningsStore>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    Ok(HttpResponse::Ok().json(earnings.status()))
+}
+
+pub async fn import(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    earnings: web::Data<EarningsStore>,
+    request: web::Json<ImportEarningsRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Analyst)?;
+
+    earnings.import(&request, actor.username(), Utc::now())?;
+    log::info!(
+        "{} imported earnings from {}",
+        actor.username(),
+        request.file_name
+    );
+    Ok(HttpResponse::Ok().json(earnings.status()))
+}
+
+pub async fn clear_import(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    earnings: web::Data<EarningsStore>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Analyst)?;
+
+    earnings.clear_import().map_err(|e| {
+        log::error!("Could not remove earnings import: {}", e);
+        ApiError::internal("Could not remove the imported file")
+    })?;
+    log::info!("{} removed the earnings import", actor.username());
+    Ok(HttpResponse::Ok().json(earnings.status()))
+}
@@ -0,0 +1,58 @@
+use actix_web::{
+    error::BlockingError,
+    http::{header, StatusCode},
+    HttpResponse, ResponseError,
+};
+use portal_auth::{store_error, ThrottleDenial, UserStoreError};
+use portal_types::ApiError;
+use std::fmt;
+
+/// An `ApiError` sent as the response body with its matching status code.
+#[derive(Debug)]
+pub struct HttpError(pub ApiError);
+
+impl From<ApiError> for HttpError {
+    fn from(error: ApiError) -> Self {
+        Self(error)
+    }
+}
+
+// Password hashing runs on the blocking pool; losing the thread is our fault
+impl From<BlockingError> for HttpError {
+    fn from(error: BlockingError) -> Self {
+        log::error!("Blocking task failed: {}", error);
+        Self(ApiError::internal("Could not complete the request"))
+    }
+}
+
+impl From<ThrottleDenial> for HttpError {
+    fn from(denial: ThrottleDenial) -> Self {
+        Self(denial.into())
+    }
+}
+
+impl From<UserStoreError> for HttpError {
+    fn from(error: UserStoreError) -> Self {
+        Self(store_error(error))
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl ResponseError for HttpError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.0.code.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let Some(secs) = self.0.retry_after_secs {
+            response.insert_header((header::RETRY_AFTER, secs));
+        }
+        response.json(&self.0)
+    }
+}
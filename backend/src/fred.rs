@@ -0,0 +1,20 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use portal_market::FredService;
+use portal_types::{FredSeriesQuery, Role};
+
+use crate::auth::{require_role, Authenticator};
+use crate::error::HttpError;
+
+pub async fn get_series(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    fred: web::Data<FredService>,
+    query: web::Query<FredSeriesQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let response = fred.series(&query, Utc::now()).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
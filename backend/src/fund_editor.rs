@@ -0,0 +1,765 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use portal_fs::write_json_atomic;
+use portal_types::fund_editor::diff;
+use portal_types::{
+    ApiError, AuditEvent, DeletedFund, ErrorCode, FundChange, FundDefinition, FundDiff,
+    FundDiffQuery, FundVersion, FundVersionRequest, FundVersions, Role, SaveFundRequest,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::auth::{require_role, Authenticator, ClientAddress};
+use crate::error::HttpError;
+use crate::funds::{no_fund, FundLibrary};
+use crate::users::admin_entry;
+
+/// Every saved version of every fund definition, kept in one JSON file.
+/// The lock is held for the whole of an edit, so edits to the fund files
+/// and to the history happen one at a time.
+pub struct FundHistory {
+    path: PathBuf,
+    funds: Mutex<BTreeMap<u32, Vec<FundVersion>>>,
+}
+
+impl FundHistory {
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let funds = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            path,
+            funds: Mutex::new(funds),
+        })
+    }
+
+    pub fn versions(&self, library: &FundLibrary, number: u32) -> Result<FundVersions, ApiError> {
+        let history = self.funds.lock().unwrap();
+        let versions = state(&history, library, number);
+        if versions.current.is_none() && versions.versions.is_empty() {
+            return Err(no_fund(number));
+        }
+        Ok(versions)
+    }
+
+    /// Funds whose latest version is a deletion, newest number first.
+    pub fn deleted(&self, library: &FundLibrary) -> Vec<DeletedFund> {
+        let history = self.funds.lock().unwrap();
+        history
+            .iter()
+            .rev()
+            .filter(|(number, _)| library.get(**number).is_none())
+            .filter_map(|(number, versions)| {
+                let latest = versions.last()?;
+                (latest.change == FundChange::Deleted).then(|| DeletedFund {
+                    number: *number,
+                    name: latest.definition.name.clone(),
+                    deleted_by: latest.saved_by.clone(),
+                    deleted_at: latest.saved_at,
+                })
+            })
+            .collect()
+    }
+
+    pub fn diff(&self, number: u32, query: &FundDiffQuery) -> Result<FundDiff, ApiError> {
+        let history = self.funds.lock().unwrap();
+        let versions = history.get(&number).map(Vec::as_slice).unwrap_or_default();
+        let find = |version: u32| {
+            versions
+                .iter()
+                .find(|saved| saved.version == version)
+                .ok_or_else(|| no_version(number, version))
+        };
+        Ok(FundDiff {
+            number,
+            from: query.from,
+            to: query.to,
+            changes: diff(&find(query.from)?.definition, &find(query.to)?.definition),
+        })
+    }
+
+    /// Saves a new fund under the next free number.
+    pub fn create(
+        &self,
+        library: &FundLibrary,
+        definition: FundDefinition,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<FundVersions, ApiError> {
+        let definition = checked(definition)?;
+        let mut history = self.funds.lock().unwrap();
+
+        let number = library
+            .last_number()
+            .max(history.keys().next_back().copied().unwrap_or(0))
+            + 1;
+        self.commit(
+            &mut history,
+            number,
+            vec![(FundChange::Created, definition.clone())],
+            username,
+            now,
+            || write(library, number, &definition),
+        )?;
+        Ok(state(&history, library, number))
+    }
+
+    /// Saves `request` over the fund, unless someone else has saved it
+    /// since `request.base_version`. Saving an unchanged definition adds
+    /// no version.
+    pub fn update(
+        &self,
+        library: &FundLibrary,
+        number: u32,
+        request: SaveFundRequest,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<FundVersions, ApiError> {
+        let definition = checked(request.definition)?;
+        let mut history = self.funds.lock().unwrap();
+
+        let current = library
+            .get(number)
+            .ok_or_else(|| no_fund(number))?
+            .definition();
+        let versions = history.get(&number).map(Vec::as_slice).unwrap_or_default();
+        check_base(versions, request.base_version)?;
+        if definition == current {
+            return Ok(state(&history, library, number));
+        }
+
+        let mut changes = from_file(versions, &current);
+        changes.push((FundChange::Updated, definition.clone()));
+        self.commit(&mut history, number, changes, username, now, || {
+            write(library, number, &definition)
+        })?;
+        Ok(state(&history, library, number))
+    }
+
+    /// Deletes the fund, unless someone else has saved it since
+    /// `base_version`.
+    pub fn delete(
+        &self,
+        library: &FundLibrary,
+        number: u32,
+        base_version: Option<u32>,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<FundVersions, ApiError> {
+        let mut history = self.funds.lock().unwrap();
+
+        let current = library
+            .get(number)
+            .ok_or_else(|| no_fund(number))?
+            .definition();
+        let versions = history.get(&number).map(Vec::as_slice).unwrap_or_default();
+        check_base(versions, base_version)?;
+        let mut changes = from_file(versions, &current);
+        changes.push((FundChange::Deleted, current));
+        self.commit(&mut history, number, changes, username, now, || {
+            library.remove(number).map_err(|e| {
+                log::error!("Could not delete fund {}: {}", number, e);
+                ApiError::internal("Could not delete the fund")
+            })
+        })?;
+        Ok(state(&history, library, number))
+    }
+
+    /// Saves an earlier version's definition as the newest version,
+    /// bringing the fund back if it was deleted, unless someone else has
+    /// saved it since `base_version`. The definition is not validated
+    /// again: it may be a hand-written one read from the file.
+    pub fn restore(
+        &self,
+        library: &FundLibrary,
+        number: u32,
+        version: u32,
+        base_version: Option<u32>,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<FundVersions, ApiError> {
+        let mut history = self.funds.lock().unwrap();
+
+        let versions = history.get(&number).map(Vec::as_slice).unwrap_or_default();
+        check_base(versions, base_version)?;
+        let definition = versions
+            .iter()
+            .find(|saved| saved.version == version)
+            .ok_or_else(|| no_version(number, version))?
+            .definition
+            .clone();
+        let mut changes = match library.get(number) {
+            Some(current) => from_file(versions, &current.definition()),
+            None => Vec::new(),
+        };
+        changes.push((FundChange::Restored { version }, definition.clone()));
+        self.commit(&mut history, number, changes, username, now, || {
+            write(library, number, &definition)
+        })?;
+        Ok(state(&history, library, number))
+    }
+
+    // Numbers the changes after the fund's latest version and writes the
+    // history out before `change_file` touches the fund's file. If either
+    // write fails the history is put back as it was, so no file is changed
+    // without a version recording it.
+    fn commit(
+        &self,
+        history: &mut BTreeMap<u32, Vec<FundVersion>>,
+        number: u32,
+        changes: Vec<(FundChange, FundDefinition)>,
+        username: &str,
+        now: DateTime<Utc>,
+        change_file: impl FnOnce() -> Result<(), ApiError>,
+    ) -> Result<(), ApiError> {
+        let mut updated = history.clone();
+        let versions = updated.entry(number).or_default();
+        for (change, definition) in changes {
+            let version = versions.last().map_or(1, |latest| latest.version + 1);
+            versions.push(FundVersion {
+                version,
+                change,
+                saved_by: username.to_string(),
+                saved_at: now,
+                definition,
+            });
+        }
+
+        self.save(&updated).map_err(|e| {
+            log::error!("Could not write fund history: {}", e);
+            ApiError::internal("Could not save the fund")
+        })?;
+        if let Err(error) = change_file() {
+            if let Err(e) = self.save(history) {
+                log::error!("Could not roll back fund history: {}", e);
+            }
+            return Err(error);
+        }
+        *history = updated;
+        Ok(())
+    }
+
+    fn save(&self, history: &BTreeMap<u32, Vec<FundVersion>>) -> io::Result<()> {
+        write_json_atomic(&self.path, history)
+    }
+}
+
+fn state(
+    history: &BTreeMap<u32, Vec<FundVersion>>,
+    library: &FundLibrary,
+    number: u32,
+) -> FundVersions {
+    FundVersions {
+        number,
+        current: library.get(number).map(|fund| fund.definition()),
+        versions: history.get(&number).cloned().unwrap_or_default(),
+    }
+}
+
+/// The definition tidied up, or every reason it cannot be saved.
+fn checked(definition: FundDefinition) -> Result<FundDefinition, ApiError> {
+    let definition = definition.normalized();
+    let errors = definition.validate();
+    if errors.is_empty() {
+        Ok(definition)
+    } else {
+        Err(ApiError::validation(errors.join("; ")))
+    }
+}
+
+/// Refuses a change made from an older view of the fund than its latest
+/// version.
+fn check_base(versions: &[FundVersion], base_version: Option<u32>) -> Result<(), ApiError> {
+    match versions
+        .last()
+        .filter(|latest| Some(latest.version) != base_version)
+    {
+        Some(latest) => Err(ApiError::new(
+            ErrorCode::Conflict,
+            format!(
+                "{} saved version {} at {}; reload the fund to see their changes",
+                latest.saved_by,
+                latest.version,
+                latest.saved_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The file as it stands, when no version records it: the fund has never
+/// been edited here, or its file was changed by hand since.
+fn from_file(
+    versions: &[FundVersion],
+    current: &FundDefinition,
+) -> Vec<(FundChange, FundDefinition)> {
+    if versions
+        .last()
+        .is_some_and(|latest| latest.definition == *current)
+    {
+        Vec::new()
+    } else {
+        vec![(FundChange::FromFile, current.clone())]
+    }
+}
+
+fn write(library: &FundLibrary, number: u32, definition: &FundDefinition) -> Result<(), ApiError> {
+    library.write(number, definition).map(|_| ()).map_err(|e| {
+        log::error!("Could not write fund {}: {}", number, e);
+        ApiError::internal("Could not save the fund")
+    })
+}
+
+fn no_version(number: u32, version: u32) -> ApiError {
+    ApiError::new(
+        ErrorCode::NotFound,
+        format!("Fund {} has no version {}", number, version),
+    )
+}
+
+pub async fn create_fund(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    funds: web::Data<FundLibrary>,
+    history: web::Data<FundHistory>,
+    request: web::Json<SaveFundRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let request = request.into_inner();
+    let versions = history.create(&funds, request.definition, actor.username(), Utc::now())?;
+    log::info!("{} created fund {}", actor.username(), versions.number);
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::FundCreated,
+        fund_detail(&versions),
+    ));
+    Ok(HttpResponse::Created().json(versions))
+}
+
+pub async fn update_fund(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    funds: web::Data<FundLibrary>,
+    history: web::Data<FundHistory>,
+    number: web::Path<u32>,
+    request: web::Json<SaveFundRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let versions = history.update(
+        &funds,
+        *number,
+        request.into_inner(),
+        actor.username(),
+        Utc::now(),
+    )?;
+    log::info!("{} saved fund {}", actor.username(), versions.number);
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::FundUpdated,
+        fund_detail(&versions),
+    ));
+    Ok(HttpResponse::Ok().json(versions))
+}
+
+pub async fn delete_fund(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    funds: web::Data<FundLibrary>,
+    history: web::Data<FundHistory>,
+    number: web::Path<u32>,
+    request: web::Json<FundVersionRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let versions = history.delete(
+        &funds,
+        *number,
+        request.base_version,
+        actor.username(),
+        Utc::now(),
+    )?;
+    log::info!("{} deleted fund {}", actor.username(), versions.number);
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::FundDeleted,
+        format!("Fund {}", versions.number),
+    ));
+    Ok(HttpResponse::Ok().json(versions))
+}
+
+pub async fn deleted_funds(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+    history: web::Data<FundHistory>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::Admin)?;
+
+    Ok(HttpResponse::Ok().json(history.deleted(&funds)))
+}
+
+pub async fn list_versions(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+    history: web::Data<FundHistory>,
+    number: web::Path<u32>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::Admin)?;
+
+    Ok(HttpResponse::Ok().json(history.versions(&funds, *number)?))
+}
+
+pub async fn diff_versions(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    history: web::Data<FundHistory>,
+    number: web::Path<u32>,
+    query: web::Query<FundDiffQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::Admin)?;
+
+    Ok(HttpResponse::Ok().json(history.diff(*number, &query)?))
+}
+
+pub async fn restore_version(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    funds: web::Data<FundLibrary>,
+    history: web::Data<FundHistory>,
+    path: web::Path<(u32, u32)>,
+    request: web::Json<FundVersionRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let (number, version) = path.into_inner();
+    let versions = history.restore(
+        &funds,
+        number,
+        version,
+        request.base_version,
+        actor.username(),
+        Utc::now(),
+    )?;
+    log::info!(
+        "{} restored fund {} to version {}",
+        actor.username(),
+        number,
+        version
+    );
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::FundRestored,
+        format!("{} to version {}", fund_detail(&versions), version),
+    ));
+    Ok(HttpResponse::Ok().json(versions))
+}
+
+// The fund's number and, while it has a file, its name
+fn fund_detail(versions: &FundVersions) -> String {
+    match &versions.current {
+        Some(definition) => format!("Fund {} ({})", versions.number, definition.name),
+        None => format!("Fund {}", versions.number),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use portal_types::{Holding, Strategy};
+
+    // An older-style file: `name`, top-level holdings and text allocations
+    const FUND: &str = r##"{
+  "name": "ALL WEATHER FUND 11",
+  "holdings": [
+    { "ticker": "VTI", "name": "Vanguard Total Stock Market ETF", "allocation": "60.00%" },
+    { "ticker": "TLT", "name": "iShares 20+ Year Treasury Bond ETF", "allocation": "40.00%" }
+  ],
+  "monthlyReturns": [ { "year": "2024", "jan": "1.00%", "feb": "-0.50%" } ]
+}"##;
+
+    fn definition(name: &str, holdings: &[(&str, f64)]) -> FundDefinition {
+        FundDefinition {
+            name: name.to_string(),
+            starting_balance: Some(10_000.0),
+            holdings: holdings
+                .iter()
+                .map(|(ticker, allocation)| Holding {
+                    ticker: ticker.to_string(),
+                    name: format!("{} ETF", ticker),
+                    allocation: *allocation,
+                    color: Some("#4285f4".to_string()),
+                })
+                .collect(),
+            strategy: Strategy {
+                overview: "Stocks and bonds".to_string(),
+                ..Strategy::default()
+            },
+        }
+    }
+
+    fn save(definition: FundDefinition, base_version: Option<u32>) -> SaveFundRequest {
+        SaveFundRequest {
+            definition,
+            base_version,
+        }
+    }
+
+    fn open(dir: &tempfile::TempDir) -> (FundLibrary, FundHistory) {
+        (
+            FundLibrary::open(dir.path().join("funds")),
+            FundHistory::open(dir.path().join("data").join("fund-versions.json")).unwrap(),
+        )
+    }
+
+    #[test]
+    fn edits_keep_the_rest_of_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("funds")).unwrap();
+        fs::write(dir.path().join("funds").join("fund-11.json"), FUND).unwrap();
+        let (library, history) = open(&dir);
+
+        let edited = definition(" ALL WEATHER FUND 11 ", &[("vti", 55.0), ("GLD", 45.0)]);
+        let versions = history
+            .update(&library, 11, save(edited, None), "admin", Utc::now())
+            .unwrap();
+
+        // The hand-written file is kept as the first version
+        assert_eq!(versions.versions.len(), 2);
+        assert_eq!(versions.versions[0].change, FundChange::FromFile);
+        assert_eq!(versions.versions[0].definition.holdings[0].allocation, 60.0);
+        assert_eq!(versions.versions[1].change, FundChange::Updated);
+        assert_eq!(versions.versions[1].saved_by, "admin");
+        assert_eq!(versions.latest_version(), Some(2));
+
+        let fund = library.get(11).unwrap();
+        assert_eq!(fund.name, "ALL WEATHER FUND 11");
+        assert_eq!(fund.holdings[0].ticker, "VTI");
+        assert_eq!(fund.monthly_returns[0].months[1], Some(-0.5));
+
+        let file: serde_json::Value = serde_json::from_slice(
+            &fs::read(dir.path().join("funds").join("fund-11.json")).unwrap(),
+        )
+        .unwrap();
+        let keys: Vec<&str> = file
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "name",
+                "holdings",
+                "monthlyReturns",
+                "startingBalance",
+                "strategy"
+            ]
+        );
+        assert_eq!(file["holdings"][1]["allocation"], serde_json::json!(45));
+
+        // The history survives a restart
+        let (library, history) = open(&dir);
+        assert_eq!(history.versions(&library, 11).unwrap().versions.len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_and_conflicting_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let (library, history) = open(&dir);
+
+        let error = history
+            .create(
+                &library,
+                definition("", &[("VTI", 90.0)]),
+                "admin",
+                Utc::now(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.message,
+            "The fund needs a name; Allocations add up to 90.00%, not 100%"
+        );
+
+        let created = history
+            .create(
+                &library,
+                definition("Fund", &[("VTI", 100.0)]),
+                "admin",
+                Utc::now(),
+            )
+            .unwrap();
+        assert_eq!(created.number, 1);
+        assert_eq!(created.latest_version(), Some(1));
+
+        let mut renamed = definition("Renamed", &[("VTI", 100.0)]);
+        history
+            .update(
+                &library,
+                1,
+                save(renamed.clone(), Some(1)),
+                "alice",
+                Utc::now(),
+            )
+            .unwrap();
+        renamed.name = "Renamed again".to_string();
+        let error = history
+            .update(&library, 1, save(renamed, Some(1)), "bob", Utc::now())
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Conflict);
+        assert!(error.message.starts_with("alice saved version 2"));
+
+        // Deleting or restoring from the same stale view is refused too
+        let error = history
+            .delete(&library, 1, Some(1), "bob", Utc::now())
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Conflict);
+        let error = history
+            .restore(&library, 1, 1, Some(1), "bob", Utc::now())
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Conflict);
+        assert_eq!(library.get(1).unwrap().name, "Renamed");
+        assert_eq!(history.versions(&library, 1).unwrap().versions.len(), 2);
+    }
+
+    #[test]
+    fn deleted_funds_can_be_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let (library, history) = open(&dir);
+
+        history
+            .create(
+                &library,
+                definition("Fund", &[("VTI", 100.0)]),
+                "admin",
+                Utc::now(),
+            )
+            .unwrap();
+        history
+            .update(
+                &library,
+                1,
+                save(definition("Fund", &[("VTI", 50.0), ("TLT", 50.0)]), Some(1)),
+                "admin",
+                Utc::now(),
+            )
+            .unwrap();
+        let deleted = history
+            .delete(&library, 1, Some(2), "admin", Utc::now())
+            .unwrap();
+        assert!(deleted.current.is_none());
+        assert!(library.get(1).is_none());
+        assert_eq!(history.deleted(&library)[0].name, "Fund");
+
+        // A new fund does not take the deleted fund's number
+        let other = history
+            .create(
+                &library,
+                definition("Other", &[("GLD", 100.0)]),
+                "admin",
+                Utc::now(),
+            )
+            .unwrap();
+        assert_eq!(other.number, 2);
+
+        let changes = history
+            .diff(1, &FundDiffQuery { from: 1, to: 2 })
+            .unwrap()
+            .changes;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "VTI allocation");
+
+        let restored = history
+            .restore(&library, 1, 1, Some(3), "admin", Utc::now())
+            .unwrap();
+        assert_eq!(
+            restored.versions.last().unwrap().change,
+            FundChange::Restored { version: 1 }
+        );
+        assert_eq!(library.get(1).unwrap().holdings.len(), 1);
+        assert!(history.deleted(&library).is_empty());
+        assert_eq!(
+            history
+                .restore(&library, 1, 9, Some(4), "admin", Utc::now())
+                .unwrap_err()
+                .code,
+            ErrorCode::NotFound
+        );
+    }
+
+    #[test]
+    fn failed_saves_leave_the_file_and_history_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let (library, history) = open(&dir);
+        history
+            .create(
+                &library,
+                definition("Fund", &[("VTI", 100.0)]),
+                "admin",
+                Utc::now(),
+            )
+            .unwrap();
+        let fund_file = dir.path().join("funds").join("fund-1.json");
+        let written = fs::read(&fund_file).unwrap();
+
+        // The history cannot be written: the fund file is not touched
+        let blocker = dir.path().join("data").join("fund-versions.json.tmp");
+        fs::create_dir(&blocker).unwrap();
+        let renamed = definition("Renamed", &[("VTI", 100.0)]);
+        assert!(history
+            .update(
+                &library,
+                1,
+                save(renamed.clone(), Some(1)),
+                "admin",
+                Utc::now()
+            )
+            .is_err());
+        assert_eq!(fs::read(&fund_file).unwrap(), written);
+        fs::remove_dir(&blocker).unwrap();
+
+        // The fund file cannot be written: the new version is taken back out
+        let blocker = dir.path().join("funds").join("fund-1.json.tmp");
+        fs::create_dir(&blocker).unwrap();
+        assert!(history
+            .update(
+                &library,
+                1,
+                save(renamed.clone(), Some(1)),
+                "admin",
+                Utc::now()
+            )
+            .is_err());
+        fs::remove_dir(&blocker).unwrap();
+        let (library, history) = open(&dir);
+        assert_eq!(
+            history.versions(&library, 1).unwrap().latest_version(),
+            Some(1)
+        );
+
+        // So the retry is an ordinary second version
+        let versions = history
+            .update(&library, 1, save(renamed, Some(1)), "admin", Utc::now())
+            .unwrap();
+        assert_eq!(versions.versions.len(), 2);
+        assert_eq!(versions.versions[1].change, FundChange::Updated);
+    }
+}
@@ -0,0 +1,481 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use portal_fs::write_json_atomic;
+use portal_market::FredService;
+use portal_types::analytics::risk_table;
+use portal_types::comparison::{compare, GROWTH_START};
+use portal_types::leverage::{monthly_rates, simulate};
+use portal_types::{
+    ApiError, ComparisonInput, ErrorCode, FredSeries, FredSeriesQuery, Fund, FundComparisonQuery,
+    FundDefinition, FundFile, FundResponse, FundSummary, LeverageQuery, LeverageResponse,
+    ReturnSeries, RiskMetricsQuery, RiskMetricsResponse, Role,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::auth::{require_role, Authenticator};
+use crate::backtest::ReturnsState;
+use crate::error::HttpError;
+
+/// The All Weather funds, read from `fund-<number>.json` files when the
+/// server starts. Files that cannot be read are logged and left out.
+pub struct FundLibrary {
+    dir: PathBuf,
+    /// Replaced fund by fund as the definitions are edited
+    funds: RwLock<BTreeMap<u32, Fund>>,
+}
+
+impl FundLibrary {
+    pub fn open(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Could not read funds from {}: {}", dir.display(), e);
+                return Self {
+                    dir,
+                    funds: RwLock::new(BTreeMap::new()),
+                };
+            }
+        };
+
+        let mut funds = BTreeMap::new();
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let Some(number) = fund_number(&path) else {
+                continue;
+            };
+            match load(number, &path) {
+                Ok(fund) => {
+                    for issue in fund.validate() {
+                        log::warn!("{}: {}", path.display(), issue);
+                    }
+                    funds.insert(number, fund);
+                }
+                Err(e) => log::warn!("Skipping {}: {}", path.display(), e),
+            }
+        }
+
+        Self {
+            dir,
+            funds: RwLock::new(funds),
+        }
+    }
+
+    pub fn list(&self) -> Vec<FundSummary> {
+        self.funds
+            .read()
+            .unwrap()
+            .values()
+            .map(Fund::summary)
+            .collect()
+    }
+
+    pub fn get(&self, number: u32) -> Option<Fund> {
+        self.funds.read().unwrap().get(&number).cloned()
+    }
+
+    /// The highest fund number with a file, including unreadable and
+    /// deleted ones, so a new fund never takes over an old file.
+    pub fn last_number(&self) -> u32 {
+        let on_disk = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                fund_number(&path).or_else(|| fund_number(&path.with_extension("")))
+            })
+            .max();
+        let loaded = self.funds.read().unwrap().keys().next_back().copied();
+        on_disk.max(loaded).unwrap_or(0)
+    }
+
+    /// Writes `definition` into the fund's file, leaving the returns, the
+    /// tables and anything else in it as they are. A deleted fund's file
+    /// is brought back, and a new fund gets a file of its own.
+    pub fn write(&self, number: u32, definition: &FundDefinition) -> Result<Fund, String> {
+        let path = self.path(number);
+        let deleted = self.deleted_path(number);
+        let mut file = match [&path, &deleted].into_iter().find(|path| path.exists()) {
+            Some(source) => {
+                let bytes = fs::read(source).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&bytes).map_err(|e| e.to_string())?
+            }
+            None => json!({ "fundNumber": number }),
+        };
+        apply(&mut file, definition)?;
+
+        let write = || -> io::Result<()> {
+            write_json_atomic(&path, &file)?;
+            match fs::remove_file(&deleted) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        };
+        write().map_err(|e| e.to_string())?;
+
+        let fund = load(number, &path)?;
+        self.funds.write().unwrap().insert(number, fund.clone());
+        Ok(fund)
+    }
+
+    /// Takes the fund out of the library. Its file is kept as
+    /// `fund-<number>.json.deleted` until the fund is restored.
+    pub fn remove(&self, number: u32) -> io::Result<()> {
+        fs::rename(self.path(number), self.deleted_path(number))?;
+        self.funds.write().unwrap().remove(&number);
+        Ok(())
+    }
+
+    fn path(&self, number: u32) -> PathBuf {
+        self.dir.join(format!("fund-{}.json", number))
+    }
+
+    fn deleted_path(&self, number: u32) -> PathBuf {
+        self.dir.join(format!("fund-{}.json.deleted", number))
+    }
+}
+
+/// The number in `fund-<number>.json`.
+fn fund_number(path: &Path) -> Option<u32> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("fund-")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
+
+/// Sets the edited fields in a fund file. Older files keep their `name` key
+/// and top-level holdings.
+fn apply(file: &mut Value, definition: &FundDefinition) -> Result<(), String> {
+    let file = file
+        .as_object_mut()
+        .ok_or("The fund file is not a JSON object")?;
+
+    let name_key = if file.contains_key("name") && !file.contains_key("fundName") {
+        "name"
+    } else {
+        "fundName"
+    };
+    file.insert(name_key.to_string(), json!(definition.name));
+    match definition.starting_balance {
+        Some(balance) => {
+            file.insert("startingBalance".to_string(), number(balance));
+        }
+        None => {
+            file.shift_remove("startingBalance");
+        }
+    }
+
+    let holdings = definition
+        .holdings
+        .iter()
+        .map(|holding| {
+            let mut value = json!({
+                "ticker": holding.ticker,
+                "name": holding.name,
+                "allocation": number(holding.allocation),
+            });
+            if let Some(color) = &holding.color {
+                value["color"] = json!(color);
+            }
+            value
+        })
+        .collect();
+    if file.contains_key("holdings") && !file.contains_key("portfolio") {
+        file.insert("holdings".to_string(), Value::Array(holdings));
+    } else {
+        file.entry("portfolio")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .ok_or("The portfolio is not a JSON object")?
+            .insert("holdings".to_string(), Value::Array(holdings));
+    }
+
+    let strategy = file
+        .entry("strategy")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("The strategy is not a JSON object")?;
+    strategy.insert("assetsList".to_string(), json!(definition.strategy.assets));
+    strategy.insert("overview".to_string(), json!(definition.strategy.overview));
+    strategy.insert(
+        "whyWorks".to_string(),
+        json!(definition.strategy.why_it_works),
+    );
+    strategy.insert(
+        "bestSuited".to_string(),
+        json!(definition.strategy.best_suited),
+    );
+    strategy.insert(
+        "effectiveDuring".to_string(),
+        json!(definition.strategy.effective_during),
+    );
+    Ok(())
+}
+
+/// Whole numbers are written the way the files write them, 10 not 10.0.
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+fn load(number: u32, path: &Path) -> Result<Fund, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let file: FundFile = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+    Fund::from_file(number, file)
+}
+
+pub async fn list_funds(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    Ok(HttpResponse::Ok().json(funds.list()))
+}
+
+pub async fn get_fund(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+    number: web::Path<u32>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let fund = find(&funds, *number)?;
+    Ok(HttpResponse::Ok().json(FundResponse {
+        issues: fund.validate(),
+        fund,
+    }))
+}
+
+/// The fund's risk table recomputed from its monthly returns, on its own or
+/// against a ticker from the backtest returns.
+pub async fn fund_metrics(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+    returns: web::Data<ReturnsState>,
+    number: web::Path<u32>,
+    query: web::Query<RiskMetricsQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let fund = find(&funds, *number)?;
+    check_risk_free(query.risk_free)?;
+    // Only a benchmark needs the returns dataset
+    let (benchmark, benchmark_series) = match query.benchmark.as_deref().map(str::trim) {
+        Some(ticker) if !ticker.is_empty() => {
+            let (label, series) = benchmark_returns(&returns, ticker)?;
+            (Some(label), Some(series))
+        }
+        _ => (None, None),
+    };
+
+    let series = ReturnSeries::from_years(&fund.monthly_returns);
+    let metrics = risk_table(&series, benchmark_series.as_ref(), query.risk_free)
+        .map_err(ApiError::validation)?;
+    let covered = match &benchmark_series {
+        Some(benchmark_series) => series.overlap(benchmark_series).0,
+        None => series,
+    };
+    Ok(HttpResponse::Ok().json(RiskMetricsResponse {
+        benchmark,
+        risk_free: query.risk_free,
+        first_month: covered.first_month(),
+        last_month: covered.last_month(),
+        metrics,
+    }))
+}
+
+/// Two to five funds, and optionally a benchmark ticker, over the months
+/// they all have.
+pub async fn compare_funds(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+    returns: web::Data<ReturnsState>,
+    query: web::Query<FundComparisonQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    check_risk_free(query.risk_free)?;
+    let inputs = query
+        .fund_numbers()
+        .map_err(ApiError::validation)?
+        .into_iter()
+        .map(|number| {
+            let fund = find(&funds, number)?;
+            Ok(ComparisonInput {
+                label: fund.name.clone(),
+                fund: Some(number),
+                returns: ReturnSeries::from_years(&fund.monthly_returns),
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    let benchmark = match query.benchmark.as_deref().map(str::trim) {
+        Some(ticker) if !ticker.is_empty() => {
+            let (label, returns) = benchmark_returns(&returns, ticker)?;
+            Some(ComparisonInput {
+                label,
+                fund: None,
+                returns,
+            })
+        }
+        _ => None,
+    };
+
+    let comparison = compare(inputs, benchmark, query.risk_free).map_err(ApiError::validation)?;
+    Ok(HttpResponse::Ok().json(comparison))
+}
+
+/// The fund run at leverage, borrowing at the fed funds rate from the FRED
+/// cache.
+pub async fn fund_leverage(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    funds: web::Data<FundLibrary>,
+    fred: web::Data<FredService>,
+    number: web::Path<u32>,
+    query: web::Query<LeverageQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    let fund = find(&funds, *number)?;
+    let series = ReturnSeries::from_years(&fund.monthly_returns);
+    let fed_funds = fred
+        .series(
+            &FredSeriesQuery {
+                series_id: FredSeries::FedFunds.id().to_string(),
+                observation_start: series.first_month(),
+                observation_end: None,
+            },
+            Utc::now(),
+        )
+        .await?;
+
+    let result = simulate(
+        &series,
+        &monthly_rates(&fed_funds.observations),
+        &query,
+        fund.starting_balance.unwrap_or(GROWTH_START),
+    )
+    .map_err(ApiError::validation)?;
+    Ok(HttpResponse::Ok().json(LeverageResponse {
+        result,
+        stale: fed_funds.stale,
+    }))
+}
+
+pub(crate) fn find(funds: &FundLibrary, number: u32) -> Result<Fund, ApiError> {
+    funds.get(number).ok_or_else(|| no_fund(number))
+}
+
+pub(crate) fn no_fund(number: u32) -> ApiError {
+    ApiError::new(ErrorCode::NotFound, format!("There is no fund {}", number))
+}
+
+fn check_risk_free(risk_free: f64) -> Result<(), ApiError> {
+    if risk_free.is_finite() {
+        Ok(())
+    } else {
+        Err(ApiError::validation("The risk-free rate must be a number"))
+    }
+}
+
+/// A ticker's monthly returns from the backtest returns, with the ticker
+/// as written there.
+fn benchmark_returns(
+    returns: &ReturnsState,
+    ticker: &str,
+) -> Result<(String, ReturnSeries), ApiError> {
+    let ticker = ticker.trim().to_ascii_uppercase();
+    let table = returns.table()?;
+    let series = ReturnSeries::new(
+        table
+            .rows()
+            .iter()
+            .filter_map(|row| Some((row.month, *row.returns.get(&ticker)?)))
+            .collect(),
+    );
+    if series.months().is_empty() {
+        return Err(ApiError::validation(format!(
+            "There are no returns for {}",
+            ticker
+        )));
+    }
+    Ok((ticker, series))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUND: &str = r##"{
+        "fundNumber": 3,
+        "fundName": "ALL WEATHER FUND 3",
+        "startingBalance": 10000,
+        "portfolio": { "holdings": [
+            { "ticker": "VTI", "name": "Vanguard Total Stock Market ETF", "allocation": 60, "color": "#4285f4" },
+            { "ticker": "TLT", "name": "iShares 20+ Year Treasury Bond ETF", "allocation": 40, "color": "#34a853" }
+        ] },
+        "performance": [ { "metric": "Start Balance", "fund": "$10,000", "benchmark": "$10,000" } ],
+        "monthlyReturns": [ { "year": "2024", "jan": "1.00%", "feb": "-0.50%", "total": "0.50%" } ],
+        "drawdowns": [],
+        "strategy": { "assetsList": [], "overview": "", "whyWorks": [], "bestSuited": [], "effectiveDuring": [] }
+    }"##;
+
+    #[test]
+    fn loads_fund_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("fund-3.json"), FUND).unwrap();
+        fs::write(dir.path().join("fund-4.json"), "{ not json").unwrap();
+        fs::write(dir.path().join("fund-5.json"), FUND).unwrap();
+        fs::write(dir.path().join("notes.json"), FUND).unwrap();
+
+        // fund-4 is unreadable and fund-5 holds fund 3
+        let library = FundLibrary::open(dir.path());
+        let list = library.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].number, 3);
+        assert_eq!(list[0].first_year, Some(2024));
+        assert!(list[0].issues.is_empty(), "{:?}", list[0].issues);
+
+        let fund = library.get(3).unwrap();
+        assert_eq!(fund.holdings[1].allocation, 40.0);
+        assert_eq!(fund.performance[0].fund.value, Some(10_000.0));
+        assert!(library.get(4).is_none());
+    }
+
+    #[test]
+    fn missing_directory_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(FundLibrary::open(dir.path().join("missing"))
+            .list()
+            .is_empty());
+    }
+
+    // Every fund in the repository must at least parse
+    #[test]
+    fn reads_the_repository_funds() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../MS-Portal-Website/data/funds");
+        let library = FundLibrary::open(&dir);
+        let numbers: Vec<u32> = library.list().iter().map(|fund| fund.number).collect();
+        assert_eq!(numbers, (7..=15).collect::<Vec<_>>());
+
+        let fund = library.get(9).unwrap();
+        assert_eq!(fund.holdings.len(), 15);
+        assert_eq!(fund.monthly_returns.len(), 17);
+        assert_eq!(fund.drawdowns[0].depth, -26.95);
+    }
+}
@@ -0,0 +1,282 @@
+use actix_cors::Cors;
+use actix_files::{Files, NamedFile};
+use actix_session::{config::PersistentSession, storage::CookieSessionStore, SessionMiddleware};
+use actix_web::{
+    cookie::time::Duration,
+    dev::{fn_service, ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Logger,
+    web, App, HttpResponse, HttpServer, Responder,
+};
+use portal_auth::{AuditLog, UserStore};
+use portal_market::{AlertMonitor, AlertStore, FredCache, FredClient, FredService};
+use portal_types::{ApiError, HealthResponse};
+
+mod alerts;
+mod audit;
+mod auth;
+mod backtest;
+mod config;
+mod earnings;
+mod error;
+mod fred;
+mod fund_editor;
+mod funds;
+mod risk_premiums;
+mod two_factor;
+mod users;
+
+use auth::Authenticator;
+use backtest::ReturnsState;
+use config::Config;
+use earnings::EarningsStore;
+use error::HttpError;
+use fund_editor::FundHistory;
+use funds::FundLibrary;
+
+async fn health() -> impl Responder {
+    HttpResponse::Ok().json(HealthResponse {
+        status: "healthy".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+fn api_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api")
+            .route("/login", web::post().to(auth::login))
+            .route("/login/two-factor", web::post().to(auth::login_two_factor))
+            .route("/logout", web::post().to(auth::logout))
+            .route("/auth", web::get().to(auth::check_auth))
+            .route("/session/refresh", web::post().to(auth::refresh_session))
+            .route("/health", web::get().to(health))
+            .route("/users", web::get().to(users::list_users))
+            .route("/users", web::post().to(users::create_user))
+            .route("/users/disabled", web::post().to(users::set_user_disabled))
+            .route("/users/role", web::post().to(users::set_user_role))
+            .route("/users/password", web::post().to(users::reset_password))
+            .route(
+                "/two-factor/enroll",
+                web::post().to(two_factor::begin_enrollment),
+            )
+            .route("/two-factor/confirm", web::post().to(two_factor::confirm))
+            .route("/two-factor/disable", web::post().to(two_factor::disable))
+            .route("/audit", web::get().to(audit::query_audit_log))
+            .route("/fred/series", web::get().to(fred::get_series))
+            .route(
+                "/risk-premiums",
+                web::get().to(risk_premiums::get_risk_premiums),
+            )
+            .route(
+                "/risk-premiums/history",
+                web::get().to(risk_premiums::get_history),
+            )
+            .route("/earnings", web::get().to(earnings::get_status))
+            .route("/earnings/import", web::post().to(earnings::import))
+            .route(
+                "/earnings/import/clear",
+                web::post().to(earnings::clear_import),
+            )
+            .route("/alerts", web::get().to(alerts::list_alerts))
+            .route("/alerts/acknowledge", web::post().to(alerts::acknowledge))
+            .route("/alerts/rules", web::get().to(alerts::list_rules))
+            .route("/alerts/rules", web::post().to(alerts::save_rules))
+            .route("/alerts/check", web::post().to(alerts::check_now))
+            .route("/funds", web::get().to(funds::list_funds))
+            .route("/funds", web::post().to(fund_editor::create_fund))
+            // Before /funds/{number}, which would take these as numbers
+            .route("/funds/compare", web::get().to(funds::compare_funds))
+            .route("/funds/deleted", web::get().to(fund_editor::deleted_funds))
+            .route("/funds/{number}", web::get().to(funds::get_fund))
+            .route("/funds/{number}", web::post().to(fund_editor::update_fund))
+            .route(
+                "/funds/{number}/delete",
+                web::post().to(fund_editor::delete_fund),
+            )
+            .route(
+                "/funds/{number}/versions",
+                web::get().to(fund_editor::list_versions),
+            )
+            .route(
+                "/funds/{number}/versions/diff",
+                web::get().to(fund_editor::diff_versions),
+            )
+            .route(
+                "/funds/{number}/versions/{version}/restore",
+                web::post().to(fund_editor::restore_version),
+            )
+            .route(
+                "/funds/{number}/metrics",
+                web::get().to(funds::fund_metrics),
+            )
+            .route(
+                "/funds/{number}/leverage",
+                web::get().to(funds::fund_leverage),
+            )
+            .route("/backtest", web::post().to(backtest::run))
+            .route("/backtest/returns", web::get().to(backtest::get_returns)),
+    );
+}
+
+// Malformed bodies and query strings get the same error shape as everything else
+fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .error_handler(|err, _req| HttpError(ApiError::validation(err.to_string())).into())
+}
+
+fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|err, _req| HttpError(ApiError::validation(err.to_string())).into())
+}
+
+fn path_config() -> web::PathConfig {
+    web::PathConfig::default()
+        .error_handler(|err, _req| HttpError(ApiError::validation(err.to_string())).into())
+}
+
+fn build_cors(config: &Config) -> Cors {
+    config
+        .allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(vec!["GET", "POST"])
+        .allowed_headers(vec![header::CONTENT_TYPE, header::ACCEPT])
+        .supports_credentials()
+        .max_age(3600)
+}
+
+fn open_user_store(config: &Config) -> std::io::Result<UserStore> {
+    let mut store = UserStore::open(&config.users_file).map_err(std::io::Error::other)?;
+
+    let generated = store
+        .bootstrap(
+            &config.bootstrap_username,
+            config.bootstrap_password.clone(),
+        )
+        .map_err(std::io::Error::other)?;
+    if let Some(password) = generated {
+        log::warn!(
+            "Created initial user '{}' with generated password: {}",
+            config.bootstrap_username,
+            password
+        );
+        log::warn!("Change it after logging in, or set LOGIN_PASSWORD before first start");
+    }
+
+    Ok(store)
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+
+    let config = Config::from_env();
+    let bind_address = (config.host.clone(), config.port);
+    let auth_state = web::Data::new(Authenticator::new(
+        open_user_store(&config)?,
+        config.session_limits,
+        AuditLog::new(&config.audit_log_file),
+    ));
+    let fred_state = web::Data::new(FredService::new(
+        FredClient::new(&config.fred_api_url, config.fred_api_key.clone()),
+        FredCache::open(&config.fred_cache_file),
+    ));
+    let earnings_state = web::Data::new(EarningsStore::open(
+        &config.earnings_file,
+        &config.earnings_import_file,
+        config.earnings_basis,
+    ));
+    let alert_monitor = web::Data::new(AlertMonitor::new(AlertStore::open(&config.alerts_file)?));
+    let fund_library = web::Data::new(FundLibrary::open(&config.funds_dir));
+    let fund_history = web::Data::new(FundHistory::open(&config.fund_versions_file)?);
+    let returns_state = web::Data::new(ReturnsState::open(&config.returns_file));
+    let config_data = web::Data::new(config.clone());
+
+    log::info!(
+        "MS Portal backend listening on http://{}:{}",
+        config.host,
+        config.port
+    );
+    log::info!("Serving frontend from {}", config.dist_dir.display());
+    log::info!("User store at {}", config.users_file.display());
+    log::info!("Audit log at {}", config.audit_log_file.display());
+    log::info!("FRED cache at {}", config.fred_cache_file.display());
+    log::info!(
+        "S&P 500 earnings from {} ({})",
+        config.earnings_file.display(),
+        config.earnings_basis.label()
+    );
+    if config.fred_api_key.is_none() {
+        log::warn!("FRED_API_KEY not set, FRED data will only be served from the cache");
+    }
+    log::info!("Alerts at {}", config.alerts_file.display());
+    log::info!("Funds from {}", config.funds_dir.display());
+    log::info!("Fund versions at {}", config.fund_versions_file.display());
+    log::info!("Monthly returns from {}", config.returns_file.display());
+
+    if config.alert_check_minutes > 0 {
+        let monitor = alert_monitor.clone();
+        let fred = fred_state.clone();
+        let earnings = earnings_state.clone();
+        let period = std::time::Duration::from_secs(config.alert_check_minutes * 60);
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = alerts::check(&monitor, &fred, &earnings).await {
+                    log::error!("Could not write alerts: {}", e);
+                }
+            }
+        });
+    }
+
+    HttpServer::new(move || {
+        let index_file = config.dist_dir.join("index.html");
+
+        App::new()
+            .app_data(auth_state.clone())
+            .app_data(fred_state.clone())
+            .app_data(earnings_state.clone())
+            .app_data(alert_monitor.clone())
+            .app_data(fund_library.clone())
+            .app_data(fund_history.clone())
+            .app_data(returns_state.clone())
+            .app_data(config_data.clone())
+            .app_data(json_config())
+            .app_data(query_config())
+            .app_data(path_config())
+            .wrap(
+                SessionMiddleware::builder(
+                    CookieSessionStore::default(),
+                    config.session_key.clone(),
+                )
+                .cookie_name("ms_portal_session".to_string())
+                .cookie_secure(config.cookie_secure)
+                .cookie_http_only(true)
+                .session_lifecycle(PersistentSession::default().session_ttl(Duration::seconds(
+                    config.session_limits.absolute_timeout.num_seconds(),
+                )))
+                .build(),
+            )
+            .wrap(build_cors(&config))
+            .wrap(Logger::default())
+            .configure(api_routes)
+            // Client-side routes such as /dashboard fall back to the SPA entry point
+            .service(
+                Files::new("/", &config.dist_dir)
+                    .index_file("index.html")
+                    .default_handler(fn_service(move |req: ServiceRequest| {
+                        let index_file = index_file.clone();
+                        async move {
+                            let (req, _) = req.into_parts();
+                            let file = NamedFile::open_async(index_file).await?;
+                            let res = file.into_response(&req);
+                            Ok(ServiceResponse::new(req, res))
+                        }
+                    })),
+            )
+    })
+    .bind(bind_address)?
+    .run()
+    .await
+}
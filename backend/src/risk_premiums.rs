@@ -0,0 +1,95 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use portal_market::FredService;
+use portal_types::risk_premiums::{compute, history, MAX_CARRY_DAYS};
+use portal_types::{
+    EarningsSource, FredSeries, MetricHistoryQuery, MetricHistoryResponse, RiskPremiumsResponse,
+    Role,
+};
+
+use crate::auth::{require_role, Authenticator};
+use crate::earnings::EarningsStore;
+use crate::error::HttpError;
+
+/// How far back each series is requested; long enough to include the
+/// latest release of monthly series.
+const LOOKBACK_DAYS: i64 = 90;
+
+/// Computes the report from the latest observations and earnings.
+pub async fn load(
+    fred: &FredService,
+    earnings: &EarningsStore,
+    now: DateTime<Utc>,
+) -> RiskPremiumsResponse {
+    let start = now.date_naive() - Duration::days(LOOKBACK_DAYS);
+    let mut loaded = fred.load(&FredSeries::ALL, Some(start), None, now).await;
+    loaded.inputs.earnings = earnings
+        .source()
+        .and_then(|source| source.earnings_at(now.date_naive()));
+
+    RiskPremiumsResponse {
+        report: compute(&loaded.inputs),
+        fetched_at: loaded.fetched_at,
+        stale: loaded.stale,
+        missing: loaded.missing,
+    }
+}
+
+/// One metric between two dates. Series are requested from a little before
+/// the start so the first dates can carry values forward.
+pub async fn load_history(
+    fred: &FredService,
+    earnings: &EarningsStore,
+    query: &MetricHistoryQuery,
+    now: DateTime<Utc>,
+) -> MetricHistoryResponse {
+    let start = query
+        .observation_start
+        .map(|start| start - Duration::days(MAX_CARRY_DAYS));
+    let loaded = fred
+        .load(query.metric.series(), start, query.observation_end, now)
+        .await;
+
+    let source = earnings.source();
+    let source = source.as_ref().map(|table| table as &dyn EarningsSource);
+    let observations = history(query.metric, &loaded.inputs, source)
+        .into_iter()
+        .filter(|obs| {
+            query
+                .observation_start
+                .is_none_or(|start| obs.date >= start)
+        })
+        .collect();
+
+    MetricHistoryResponse {
+        metric: query.metric,
+        observations,
+        fetched_at: loaded.fetched_at,
+        stale: loaded.stale,
+        missing: loaded.missing,
+    }
+}
+
+pub async fn get_risk_premiums(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    fred: web::Data<FredService>,
+    earnings: web::Data<EarningsStore>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    Ok(HttpResponse::Ok().json(load(&fred, &earnings, Utc::now()).await))
+}
+
+pub async fn get_history(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    fred: web::Data<FredService>,
+    earnings: web::Data<EarningsStore>,
+    query: web::Query<MetricHistoryQuery>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::ReadOnly)?;
+
+    Ok(HttpResponse::Ok().json(load_history(&fred, &earnings, &query, Utc::now()).await))
+}
@@ -0,0 +1,73 @@
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use portal_auth::totp;
+use portal_types::{
+    AuditEntry, AuditEvent, AuditOutcome, ConfirmTwoFactorRequest, DisableTwoFactorRequest,
+    RecoveryCodes, Role,
+};
+
+use crate::auth::{client_address, require_role, Authenticator, ClientAddress};
+use crate::config::Config;
+use crate::error::HttpError;
+
+/// Starts enrollment for the signed-in user and returns the QR code to scan.
+pub async fn begin_enrollment(
+    session: Session,
+    auth: web::Data<Authenticator>,
+) -> Result<HttpResponse, HttpError> {
+    let user = require_role(&session, &auth, Role::ReadOnly)?;
+
+    let secret = auth
+        .users
+        .lock()
+        .unwrap()
+        .begin_totp_enrollment(user.username())?;
+    Ok(HttpResponse::Ok().json(totp::enrollment(user.username(), &secret)))
+}
+
+pub async fn confirm(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    request: web::Json<ConfirmTwoFactorRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let user = require_role(&session, &auth, Role::ReadOnly)?;
+
+    let codes =
+        auth.users
+            .lock()
+            .unwrap()
+            .confirm_totp(user.username(), &request.code, Utc::now())?;
+    log::info!("{} enabled two-factor authentication", user.username());
+    auth.audit(AuditEntry {
+        client: Some(client.0),
+        ..AuditEntry::new(
+            user.username(),
+            AuditEvent::TwoFactorEnabled,
+            AuditOutcome::Success,
+        )
+    });
+    Ok(HttpResponse::Ok().json(RecoveryCodes { codes }))
+}
+
+// Users need a current code to turn off their own second factor; admins can
+// turn it off for anyone who has lost their device
+pub async fn disable(
+    req: HttpRequest,
+    session: Session,
+    auth: web::Data<Authenticator>,
+    config: web::Data<Config>,
+    request: web::Json<DisableTwoFactorRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::ReadOnly)?;
+
+    let summary = auth.disable_two_factor(
+        &actor,
+        &request.username,
+        request.code.as_deref(),
+        &client_address(&req, &config),
+        Utc::now(),
+    )?;
+    Ok(HttpResponse::Ok().json(summary))
+}
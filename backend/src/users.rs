@@ -0,0 +1,164 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use portal_auth::NewPassword;
+use portal_types::{
+    AuditEntry, AuditEvent, AuditOutcome, CreateUserRequest, ResetPasswordRequest, Role,
+    SetUserDisabledRequest, SetUserRoleRequest,
+};
+
+use crate::auth::{require_role, Authenticator, ClientAddress};
+use crate::error::HttpError;
+
+/// An administrative change, recorded with the affected account as detail
+/// and the address it came from.
+pub(crate) fn admin_entry(
+    actor: &str,
+    client: &str,
+    event: AuditEvent,
+    detail: String,
+) -> AuditEntry {
+    AuditEntry {
+        client: Some(client.to_string()),
+        detail: Some(detail),
+        ..AuditEntry::new(actor, event, AuditOutcome::Success)
+    }
+}
+
+// Hashes on the blocking pool, before the store is locked, so neither the
+// worker nor other requests wait on Argon2
+async fn hash_password(password: String) -> Result<NewPassword, HttpError> {
+    Ok(web::block(move || NewPassword::hash(&password)).await??)
+}
+
+pub async fn list_users(
+    session: Session,
+    auth: web::Data<Authenticator>,
+) -> Result<HttpResponse, HttpError> {
+    require_role(&session, &auth, Role::Admin)?;
+
+    Ok(HttpResponse::Ok().json(auth.users.lock().unwrap().list()))
+}
+
+pub async fn create_user(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    request: web::Json<CreateUserRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let request = request.into_inner();
+    let password = hash_password(request.password).await?;
+    let summary =
+        auth.users
+            .lock()
+            .unwrap()
+            .create_user_hashed(&request.username, password, request.role)?;
+    log::info!(
+        "{} created {:?} user {}",
+        actor.username(),
+        summary.role,
+        summary.username
+    );
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::UserCreated,
+        format!("{} as {}", summary.username, summary.role.label()),
+    ));
+    Ok(HttpResponse::Created().json(summary))
+}
+
+pub async fn set_user_disabled(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    request: web::Json<SetUserDisabledRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .set_disabled(&request.username, request.disabled)?;
+    let event = if summary.disabled {
+        auth.sessions.lock().unwrap().revoke_user(&summary.username);
+        AuditEvent::UserDisabled
+    } else {
+        AuditEvent::UserEnabled
+    };
+    log::info!(
+        "{} set disabled={} on {}",
+        actor.username(),
+        summary.disabled,
+        summary.username
+    );
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        event,
+        summary.username.clone(),
+    ));
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+pub async fn set_user_role(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    request: web::Json<SetUserRoleRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .set_role(&request.username, request.role)?;
+    log::info!(
+        "{} set role {:?} on {}",
+        actor.username(),
+        summary.role,
+        summary.username
+    );
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::RoleChanged,
+        format!("{} to {}", summary.username, summary.role.label()),
+    ));
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+pub async fn reset_password(
+    session: Session,
+    auth: web::Data<Authenticator>,
+    client: ClientAddress,
+    request: web::Json<ResetPasswordRequest>,
+) -> Result<HttpResponse, HttpError> {
+    let actor = require_role(&session, &auth, Role::Admin)?;
+
+    let request = request.into_inner();
+    let password = hash_password(request.new_password).await?;
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .reset_password_hashed(&request.username, password)?;
+
+    // Sessions opened with the old password should not outlive it
+    auth.sessions.lock().unwrap().revoke_user(&summary.username);
+    log::info!(
+        "{} reset the password of {}",
+        actor.username(),
+        summary.username
+    );
+    auth.audit(admin_entry(
+        actor.username(),
+        &client.0,
+        AuditEvent::PasswordReset,
+        summary.username.clone(),
+    ));
+    Ok(HttpResponse::Ok().json(summary))
+}
@@ -0,0 +1,458 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use crate::{Route, components::earnings_source::EarningsSourcePanel, services::{market::use_market_service, transport::Transport}, session::use_session, types::{Alert, AlertCondition, AlertRule, AlertsResponse, ErrorCode, MetricId, Role, Unit}};
+use stylist::yew::styled_component;
+
+fn format_value(alert: &Alert) -> String {
+    match alert.metric.unit() {
+        Unit::Percent => format!("{:.2}%", alert.value),
+        Unit::Index => format!("{:.2}", alert.value),
+    }
+}
+
+// Builds a condition from the new-rule form; the server validates the numbers
+fn condition(kind: &str, amount: f64, days: u32) -> Option<AlertCondition> {
+    match kind {
+        "below" => Some(AlertCondition::Below { threshold: amount, days }),
+        "above" => Some(AlertCondition::Above { threshold: amount, days }),
+        "rises_by" => Some(AlertCondition::RisesBy { change: amount, window_days: days }),
+        "falls_by" => Some(AlertCondition::FallsBy { change: amount, window_days: days }),
+        _ => None,
+    }
+}
+
+/// Alerts raised by the rules, and the rules themselves. Anyone signed in
+/// can acknowledge alerts; analysts can change the rules.
+#[styled_component(Alerts)]
+pub fn alerts() -> Html {
+    let navigator = use_navigator().unwrap();
+    let market_service = use_market_service();
+    let session = use_session();
+    let alerts = use_state(|| None::<AlertsResponse>);
+    let rules = use_state(Vec::<AlertRule>::new);
+    let error_message = use_state(|| None::<String>);
+    let is_busy = use_state(|| false);
+    let name_ref = use_node_ref();
+    let metric_ref = use_node_ref();
+    let kind_ref = use_node_ref();
+    let amount_ref = use_node_ref();
+    let days_ref = use_node_ref();
+
+    let can_edit = session
+        .user()
+        .and_then(|user| user.role)
+        .is_some_and(|role| role.includes(Role::Analyst));
+
+    {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let alerts = alerts.clone();
+        let rules = rules.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.alerts().await {
+                    Ok(response) => alerts.set(Some(response)),
+                    Err(error) if error.code == ErrorCode::SessionExpired => {
+                        session.sign_out();
+                        return;
+                    }
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                match market_service.alert_rules().await {
+                    Ok(response) => rules.set(response),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+            });
+            || ()
+        });
+    }
+
+    // Empty means every alert
+    let acknowledge = {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let alerts = alerts.clone();
+        let error_message = error_message.clone();
+        
+        Callback::from(move |ids: Vec<u64>| {
+            let market_service = market_service.clone();
+            let session = session.clone();
+            let alerts = alerts.clone();
+            let error_message = error_message.clone();
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.acknowledge_alerts(ids).await {
+                    Ok(response) => {
+                        alerts.set(Some(response));
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+            });
+        })
+    };
+
+    // Rules are always saved as a whole list
+    let save_rules = {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let rules = rules.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let name_ref = name_ref.clone();
+        
+        Callback::from(move |updated: Vec<AlertRule>| {
+            let market_service = market_service.clone();
+            let session = session.clone();
+            let rules = rules.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            let name_ref = name_ref.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.save_alert_rules(updated).await {
+                    Ok(response) => {
+                        rules.set(response);
+                        error_message.set(None);
+                        if let Some(input) = name_ref.cast::<HtmlInputElement>() {
+                            input.set_value("");
+                        }
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let on_check = {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let alerts = alerts.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        
+        Callback::from(move |_| {
+            let market_service = market_service.clone();
+            let session = session.clone();
+            let alerts = alerts.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.check_alerts().await {
+                    Ok(response) => {
+                        alerts.set(Some(response));
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let on_add = {
+        let rules = rules.clone();
+        let save_rules = save_rules.clone();
+        let error_message = error_message.clone();
+        let name_ref = name_ref.clone();
+        let metric_ref = metric_ref.clone();
+        let kind_ref = kind_ref.clone();
+        let amount_ref = amount_ref.clone();
+        let days_ref = days_ref.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            
+            let input = |node: &NodeRef| node.cast::<HtmlInputElement>().map(|input| input.value()).unwrap_or_default();
+            let select = |node: &NodeRef| node.cast::<HtmlSelectElement>().map(|select| select.value()).unwrap_or_default();
+            
+            let metric_id = select(&metric_ref);
+            let metric = MetricId::ALL.into_iter().find(|metric| metric.id() == metric_id);
+            let amount = input(&amount_ref).trim().parse::<f64>().ok();
+            let days = input(&days_ref).trim().parse::<u32>().ok();
+            let (Some(metric), Some(amount), Some(days)) = (metric, amount, days) else {
+                error_message.set(Some("Enter a number for the value and the days".to_string()));
+                return;
+            };
+            let Some(condition) = condition(&select(&kind_ref), amount, days) else {
+                return;
+            };
+            
+            let mut updated = (*rules).clone();
+            updated.push(AlertRule {
+                id: 0,
+                name: input(&name_ref),
+                metric,
+                condition,
+                enabled: true,
+            });
+            save_rules.emit(updated);
+        })
+    };
+
+    let on_back = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Dashboard))
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        h2 {
+            font-size: 1rem;
+            font-weight: 400;
+            margin-top: 2rem;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+
+        .toolbar {
+            display: flex;
+            gap: 1rem;
+            align-items: center;
+            margin-bottom: 1.5rem;
+            font-size: 0.8rem;
+            color: rgba(255, 255, 255, 0.5);
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        table {
+            width: 100%;
+            border-collapse: collapse;
+            font-size: 0.8rem;
+        }
+
+        th, td {
+            text-align: left;
+            padding: 0.5rem;
+            border-bottom: 1px solid rgba(96, 96, 96, 0.3);
+        }
+
+        th {
+            font-weight: 400;
+            color: #808080;
+        }
+
+        .unacknowledged td {
+            color: #ffffff;
+        }
+
+        .acknowledged td {
+            color: #606060;
+        }
+
+        .rule-form {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.5rem;
+            margin-top: 1rem;
+            font-size: 0.8rem;
+        }
+
+        input, select {
+            background: #000000;
+            color: rgba(255, 255, 255, 0.9);
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 6px;
+            padding: 0.3rem;
+        }
+
+        .empty {
+            font-size: 0.8rem;
+            color: #606060;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Dashboard"}</button>
+            <h1>{"Alerts"}</h1>
+            
+            <div class="toolbar">
+                {if alerts.as_ref().is_some_and(|response| response.unacknowledged > 0) {
+                    let acknowledge = acknowledge.clone();
+                    html! { <button onclick={Callback::from(move |_| acknowledge.emit(Vec::new()))}>{"Acknowledge all"}</button> }
+                } else {
+                    html! {}
+                }}
+                {if can_edit {
+                    html! {
+                        <button onclick={on_check} disabled={*is_busy}>{"Check now"}</button>
+                    }
+                } else {
+                    html! {}
+                }}
+                <span>
+                    {alerts.as_ref()
+                        .and_then(|response| response.checked_at)
+                        .map(|time| format!("Last checked {}", time.format("%Y-%m-%d %H:%M UTC")))
+                        .unwrap_or_else(|| "Not checked yet".to_string())}
+                </span>
+            </div>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {match alerts.as_ref() {
+                Some(response) if !response.alerts.is_empty() => html! {
+                    <table>
+                        <tr>
+                            <th>{"Date"}</th>
+                            <th>{"Rule"}</th>
+                            <th>{"Condition"}</th>
+                            <th>{"Value"}</th>
+                            <th>{"Raised"}</th>
+                            <th></th>
+                        </tr>
+                        {for response.alerts.iter().map(|alert| {
+                            let acknowledge = acknowledge.clone();
+                            let id = alert.id;
+                            html! {
+                                <tr key={alert.id} class={if alert.acknowledged { "acknowledged" } else { "unacknowledged" }}>
+                                    <td>{alert.date.to_string()}</td>
+                                    <td>{&alert.rule_name}</td>
+                                    <td>{&alert.message}</td>
+                                    <td>{format_value(alert)}</td>
+                                    <td>{alert.triggered_at.format("%Y-%m-%d %H:%M UTC").to_string()}</td>
+                                    <td>
+                                        {if alert.acknowledged {
+                                            html! {}
+                                        } else {
+                                            html! { <button onclick={Callback::from(move |_| acknowledge.emit(vec![id]))}>{"Acknowledge"}</button> }
+                                        }}
+                                    </td>
+                                </tr>
+                            }
+                        })}
+                    </table>
+                },
+                Some(_) => html! { <div class="empty">{"No alerts have been raised."}</div> },
+                None => html! {},
+            }}
+            
+            <h2>{"Rules"}</h2>
+            <table>
+                <tr>
+                    <th>{"Name"}</th>
+                    <th>{"Condition"}</th>
+                    <th>{"Enabled"}</th>
+                    <th></th>
+                </tr>
+                {for rules.iter().map(|rule| {
+                    let id = rule.id;
+                    let on_toggle = {
+                        let rules = rules.clone();
+                        let save_rules = save_rules.clone();
+                        Callback::from(move |_| {
+                            let updated = rules.iter().cloned().map(|mut rule| {
+                                if rule.id == id {
+                                    rule.enabled = !rule.enabled;
+                                }
+                                rule
+                            }).collect();
+                            save_rules.emit(updated);
+                        })
+                    };
+                    let on_delete = {
+                        let rules = rules.clone();
+                        let save_rules = save_rules.clone();
+                        Callback::from(move |_| {
+                            save_rules.emit(rules.iter().filter(|rule| rule.id != id).cloned().collect());
+                        })
+                    };
+                    html! {
+                        <tr key={rule.id}>
+                            <td>{&rule.name}</td>
+                            <td>{rule.describe()}</td>
+                            <td>
+                                <input
+                                    type="checkbox"
+                                    checked={rule.enabled}
+                                    disabled={!can_edit || *is_busy}
+                                    onchange={on_toggle}
+                                />
+                            </td>
+                            <td>
+                                {if can_edit {
+                                    html! { <button onclick={on_delete} disabled={*is_busy}>{"Delete"}</button> }
+                                } else {
+                                    html! {}
+                                }}
+                            </td>
+                        </tr>
+                    }
+                })}
+            </table>
+            
+            {if can_edit {
+                html! {
+                    <form class="rule-form" onsubmit={on_add}>
+                        <input ref={name_ref} type="text" placeholder="Rule name" required=true />
+                        <select ref={metric_ref}>
+                            {for MetricId::ALL.into_iter().map(|metric| html! {
+                                <option value={metric.id()}>{metric.label()}</option>
+                            })}
+                        </select>
+                        <select ref={kind_ref}>
+                            <option value="below">{"below"}</option>
+                            <option value="above">{"above"}</option>
+                            <option value="rises_by">{"rises by"}</option>
+                            <option value="falls_by">{"falls by"}</option>
+                        </select>
+                        <input ref={amount_ref} type="number" step="any" placeholder="Value" required=true />
+                        <input ref={days_ref} type="number" min="1" placeholder="Days" required=true />
+                        <button type="submit" disabled={*is_busy}>{"Add rule"}</button>
+                    </form>
+                }
+            } else {
+                html! {}
+            }}
+            
+            // Without the Risk Premiums page, the desktop app picks its earnings here
+            {if Transport::is_desktop() {
+                html! { <EarningsSourcePanel on_change={Callback::noop()} /> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,226 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use web_sys::HtmlInputElement;
+use chrono::{NaiveDate, NaiveTime};
+use crate::{Route, config::AppConfig, services::auth::use_auth_service, session::use_session, types::{AuditEntry, AuditQuery, ErrorCode}};
+use stylist::yew::styled_component;
+
+const PAGE_SIZE: usize = 200;
+
+// Date inputs give YYYY-MM-DD; the range covers whole days in UTC
+fn day_bound(node: &NodeRef, time: NaiveTime) -> Option<chrono::DateTime<chrono::Utc>> {
+    let value = node.cast::<HtmlInputElement>()?.value();
+    let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()?;
+    Some(date.and_time(time).and_utc())
+}
+
+#[styled_component(AuditLogView)]
+pub fn audit_log_view() -> Html {
+    let navigator = use_navigator().unwrap();
+    let config = use_context::<AppConfig>().expect("AppConfig context is provided by App");
+    let auth_service = use_auth_service();
+    let session = use_session();
+    let entries = use_state(Vec::<AuditEntry>::new);
+    let error_message = use_state(|| None::<String>);
+    let is_loading = use_state(|| true);
+
+    let user_ref = use_node_ref();
+    let from_ref = use_node_ref();
+    let to_ref = use_node_ref();
+
+    let load = {
+        let auth_service = auth_service.clone();
+        let navigator = navigator.clone();
+        let session = session.clone();
+        let entries = entries.clone();
+        let error_message = error_message.clone();
+        let is_loading = is_loading.clone();
+        
+        Callback::from(move |query: AuditQuery| {
+            let auth_service = auth_service.clone();
+            let navigator = navigator.clone();
+            let session = session.clone();
+            let entries = entries.clone();
+            let error_message = error_message.clone();
+            let is_loading = is_loading.clone();
+            
+            is_loading.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth_service.audit_log(&query).await {
+                    Ok(result) => {
+                        entries.set(result);
+                        error_message.set(None);
+                    }
+                    Err(error) => match error.code {
+                        ErrorCode::SessionExpired => session.sign_out(),
+                        ErrorCode::Forbidden => navigator.push(&Route::Dashboard),
+                        _ => error_message.set(Some(error.message)),
+                    },
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    // The route guard has already checked for an admin; the page itself can be switched off
+    {
+        let navigator = navigator.clone();
+        let load = load.clone();
+        let enabled = config.features.audit_log;
+        
+        use_effect_with((), move |_| {
+            if enabled {
+                load.emit(AuditQuery {
+                    limit: Some(PAGE_SIZE),
+                    ..AuditQuery::default()
+                });
+            } else {
+                navigator.push(&Route::Dashboard);
+            }
+            || ()
+        });
+    }
+
+    let onsubmit = {
+        let user_ref = user_ref.clone();
+        let from_ref = from_ref.clone();
+        let to_ref = to_ref.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            
+            let user = user_ref
+                .cast::<HtmlInputElement>()
+                .map(|input| input.value().trim().to_string())
+                .filter(|user| !user.is_empty());
+            
+            load.emit(AuditQuery {
+                from: day_bound(&from_ref, NaiveTime::MIN),
+                to: day_bound(&to_ref, NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+                user,
+                limit: Some(PAGE_SIZE),
+            });
+        })
+    };
+
+    let on_back = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Dashboard))
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        form {
+            display: flex;
+            gap: 0.75rem;
+            align-items: flex-end;
+            margin-bottom: 1.5rem;
+        }
+
+        label {
+            display: flex;
+            flex-direction: column;
+            gap: 0.25rem;
+            font-size: 0.8rem;
+        }
+
+        input, button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+        }
+
+        button {
+            cursor: pointer;
+        }
+
+        table {
+            width: 100%;
+            border-collapse: collapse;
+            font-size: 0.85rem;
+        }
+
+        th, td {
+            text-align: left;
+            padding: 0.4rem 0.6rem;
+            border-bottom: 1px solid rgba(255, 255, 255, 0.1);
+        }
+
+        th {
+            font-weight: 500;
+            color: rgba(255, 255, 255, 0.6);
+        }
+
+        .outcome-failure, .outcome-throttled {
+            color: #fc8181;
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-bottom: 1rem;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Dashboard"}</button>
+            <h1>{"Audit Log"}</h1>
+            
+            <form {onsubmit}>
+                <label>{"User"}<input type="text" ref={user_ref} /></label>
+                <label>{"From"}<input type="date" ref={from_ref} /></label>
+                <label>{"To"}<input type="date" ref={to_ref} /></label>
+                <button type="submit" disabled={*is_loading}>{"Search"}</button>
+            </form>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            <table>
+                <thead>
+                    <tr>
+                        <th>{"Time (UTC)"}</th>
+                        <th>{"User"}</th>
+                        <th>{"Event"}</th>
+                        <th>{"Outcome"}</th>
+                        <th>{"Client"}</th>
+                        <th>{"Detail"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {for entries.iter().map(|entry| html! {
+                        <tr>
+                            <td>{entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()}</td>
+                            <td>{&entry.user}</td>
+                            <td>{entry.event.label()}</td>
+                            <td class={format!("outcome-{}", entry.outcome.label().to_lowercase())}>
+                                {entry.outcome.label()}
+                            </td>
+                            <td>{entry.client.clone().unwrap_or_default()}</td>
+                            <td>{entry.detail.clone().unwrap_or_default()}</td>
+                        </tr>
+                    })}
+                </tbody>
+            </table>
+        </div>
+    }
+}
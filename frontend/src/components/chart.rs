@@ -0,0 +1,516 @@
+use yew::prelude::*;
+use chrono::{Months, NaiveDate, Utc};
+use web_sys::Element;
+use crate::{services::market::use_market_service, session::use_session, types::{ErrorCode, FredSeries, FredSeriesQuery, MetricHistoryQuery, MetricId, Observation, Unit}};
+use stylist::yew::styled_component;
+
+// SVG user units; the chart scales to the width of its container
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 300.0;
+const LEFT: f64 = 56.0;
+const TOP: f64 = 10.0;
+const BOTTOM: f64 = 24.0;
+// Longer histories are thinned out for drawing, not for hovering or export
+const MAX_POINTS: usize = 1000;
+
+/// What a chart plots: a FRED series as published or a derived metric.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChartSource {
+    Series(FredSeries),
+    Metric(MetricId),
+}
+
+impl ChartSource {
+    pub fn id(self) -> &'static str {
+        match self {
+            ChartSource::Series(series) => series.id(),
+            ChartSource::Metric(metric) => metric.id(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartSource::Series(series) => series.label(),
+            ChartSource::Metric(metric) => metric.label(),
+        }
+    }
+
+    fn unit(self) -> Unit {
+        match self {
+            ChartSource::Series(FredSeries::Vix | FredSeries::Sp500) => Unit::Index,
+            ChartSource::Series(_) => Unit::Percent,
+            ChartSource::Metric(metric) => metric.unit(),
+        }
+    }
+
+    // A negative 10Y-2Y spread is an inverted yield curve
+    fn shades_inversion(self) -> bool {
+        self == ChartSource::Metric(MetricId::TermPremium)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChartRange {
+    OneMonth,
+    ThreeMonths,
+    SixMonths,
+    OneYear,
+    FiveYears,
+    TenYears,
+    Max,
+}
+
+impl ChartRange {
+    pub const ALL: [ChartRange; 7] = [
+        ChartRange::OneMonth,
+        ChartRange::ThreeMonths,
+        ChartRange::SixMonths,
+        ChartRange::OneYear,
+        ChartRange::FiveYears,
+        ChartRange::TenYears,
+        ChartRange::Max,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartRange::OneMonth => "1M",
+            ChartRange::ThreeMonths => "3M",
+            ChartRange::SixMonths => "6M",
+            ChartRange::OneYear => "1Y",
+            ChartRange::FiveYears => "5Y",
+            ChartRange::TenYears => "10Y",
+            ChartRange::Max => "Max",
+        }
+    }
+
+    /// First date shown, `None` for the whole history.
+    pub fn start(self, today: NaiveDate) -> Option<NaiveDate> {
+        let months = match self {
+            ChartRange::OneMonth => 1,
+            ChartRange::ThreeMonths => 3,
+            ChartRange::SixMonths => 6,
+            ChartRange::OneYear => 12,
+            ChartRange::FiveYears => 60,
+            ChartRange::TenYears => 120,
+            ChartRange::Max => return None,
+        };
+        today.checked_sub_months(Months::new(months))
+    }
+}
+
+fn format_value(value: f64, unit: Unit) -> String {
+    match unit {
+        Unit::Percent => format!("{:.2}%", value),
+        Unit::Index => format!("{:.2}", value),
+    }
+}
+
+/// One row per observation, with the source id as the value column.
+fn to_csv(source: ChartSource, observations: &[Observation]) -> String {
+    let mut csv = format!("date,{}\n", source.id());
+    for obs in observations {
+        csv.push_str(&format!("{},{}\n", obs.date, obs.value));
+    }
+    csv
+}
+
+/// Maps dates and values to SVG coordinates.
+struct Scale {
+    first: NaiveDate,
+    days: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Scale {
+    /// `observations` must be sorted and not empty.
+    fn new(observations: &[Observation], include_zero: bool) -> Self {
+        let first = observations[0].date;
+        let last = observations[observations.len() - 1].date;
+        let values = observations.iter().map(|obs| obs.value);
+        let mut min = values.clone().fold(f64::INFINITY, f64::min);
+        let mut max = values.fold(f64::NEG_INFINITY, f64::max);
+        if include_zero {
+            min = min.min(0.0);
+            max = max.max(0.0);
+        }
+        let padding = if max > min { (max - min) * 0.05 } else { 1.0 };
+
+        Self {
+            first,
+            days: ((last - first).num_days() as f64).max(1.0),
+            min: min - padding,
+            max: max + padding,
+        }
+    }
+
+    fn x(&self, date: NaiveDate) -> f64 {
+        LEFT + (date - self.first).num_days() as f64 / self.days * (WIDTH - LEFT)
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        TOP + (self.max - value) / (self.max - self.min) * (HEIGHT - TOP - BOTTOM)
+    }
+
+    /// Index of the observation nearest to SVG x coordinate `x`.
+    fn nearest(&self, observations: &[Observation], x: f64) -> usize {
+        let fraction = ((x - LEFT) / (WIDTH - LEFT)).clamp(0.0, 1.0);
+        let target = self.first + chrono::Duration::days((fraction * self.days).round() as i64);
+        let after = observations.partition_point(|obs| obs.date < target);
+        match after {
+            0 => 0,
+            i if i == observations.len() => i - 1,
+            i if target - observations[i - 1].date <= observations[i].date - target => i - 1,
+            i => i,
+        }
+    }
+}
+
+/// Date ranges over which the value is below zero, as (first, last) indices.
+fn negative_runs(observations: &[Observation]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, obs) in observations.iter().enumerate() {
+        match (obs.value < 0.0, start) {
+            (true, None) => start = Some(i),
+            (false, Some(first)) => {
+                runs.push((first, i - 1));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(first) = start {
+        runs.push((first, observations.len() - 1));
+    }
+    runs
+}
+
+#[derive(Properties, PartialEq)]
+pub struct HistoryChartProps {
+    pub source: ChartSource,
+}
+
+#[styled_component(HistoryChart)]
+pub fn history_chart(props: &HistoryChartProps) -> Html {
+    let market_service = use_market_service();
+    let session = use_session();
+    let range = use_state(|| ChartRange::OneYear);
+    let observations = use_state(|| None::<Vec<Observation>>);
+    let stale = use_state(|| false);
+    let error_message = use_state(|| None::<String>);
+    let is_loading = use_state(|| true);
+    let hover = use_state(|| None::<usize>);
+    let svg_ref = use_node_ref();
+    let source = props.source;
+
+    {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let observations = observations.clone();
+        let stale = stale.clone();
+        let error_message = error_message.clone();
+        let is_loading = is_loading.clone();
+        let hover = hover.clone();
+        
+        use_effect_with((source, *range), move |&(source, range)| {
+            let start = range.start(Utc::now().date_naive());
+            is_loading.set(true);
+            hover.set(None);
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = match source {
+                    ChartSource::Series(series) => {
+                        let query = FredSeriesQuery {
+                            series_id: series.id().to_string(),
+                            observation_start: start,
+                            observation_end: None,
+                        };
+                        market_service.series(&query).await.map(|response| (response.observations, response.stale))
+                    }
+                    ChartSource::Metric(metric) => {
+                        let query = MetricHistoryQuery {
+                            metric,
+                            observation_start: start,
+                            observation_end: None,
+                        };
+                        market_service.metric_history(&query).await.map(|response| (response.observations, response.stale))
+                    }
+                };
+                
+                match result {
+                    Ok((mut data, is_stale)) => {
+                        data.sort_by_key(|obs| obs.date);
+                        observations.set(Some(data));
+                        stale.set(is_stale);
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => {
+                        observations.set(None);
+                        error_message.set(Some(error.message));
+                    }
+                }
+                is_loading.set(false);
+            });
+            || ()
+        });
+    }
+
+    let data: &[Observation] = observations.as_deref().unwrap_or_default();
+    let scale = (!data.is_empty()).then(|| Scale::new(data, source.shades_inversion()));
+
+    let on_mouse_move = {
+        let svg_ref = svg_ref.clone();
+        let hover = hover.clone();
+        let observations = observations.clone();
+        let shades_inversion = source.shades_inversion();
+        
+        Callback::from(move |e: MouseEvent| {
+            let Some(data) = observations.as_deref().filter(|data| !data.is_empty()) else {
+                return;
+            };
+            let Some(width) = svg_ref.cast::<Element>().map(|svg| svg.client_width()).filter(|w| *w > 0) else {
+                return;
+            };
+            let x = e.offset_x() as f64 * WIDTH / width as f64;
+            hover.set(Some(Scale::new(data, shades_inversion).nearest(data, x)));
+        })
+    };
+
+    let on_mouse_leave = {
+        let hover = hover.clone();
+        Callback::from(move |_| hover.set(None))
+    };
+
+    let csv_href = (!data.is_empty()).then(|| {
+        let csv = to_csv(source, data);
+        format!("data:text/csv;charset=utf-8,{}", String::from(js_sys::encode_uri_component(&csv)))
+    });
+    let csv_name = format!("{}-{}.csv", source.id(), range.label());
+
+    let css = css!(
+        r#"
+        margin-top: 2rem;
+        padding: 1rem;
+        border: 1px solid rgba(96, 96, 96, 0.3);
+        border-radius: 4px;
+        background: rgba(96, 96, 96, 0.05);
+
+        .chart-header {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.5rem;
+            align-items: center;
+            margin-bottom: 0.75rem;
+        }
+
+        .chart-title {
+            flex: 1;
+            font-size: 0.9rem;
+            color: rgba(255, 255, 255, 0.9);
+        }
+
+        .range-button.active {
+            border-color: #0ea5e9;
+            color: #0ea5e9;
+        }
+
+        .csv-link {
+            font-size: 0.8rem;
+            color: rgba(255, 255, 255, 0.7);
+        }
+
+        .chart-area {
+            position: relative;
+        }
+
+        svg {
+            display: block;
+            width: 100%;
+            height: auto;
+        }
+
+        .axis-label {
+            fill: #808080;
+            font-size: 11px;
+        }
+
+        .grid-line {
+            stroke: rgba(96, 96, 96, 0.3);
+            stroke-width: 1;
+        }
+
+        .zero-line {
+            stroke: rgba(255, 255, 255, 0.4);
+            stroke-dasharray: 4 4;
+        }
+
+        .inversion {
+            fill: rgba(239, 68, 68, 0.15);
+        }
+
+        .series-line {
+            fill: none;
+            stroke: #0ea5e9;
+            stroke-width: 1.5;
+        }
+
+        .hover-line {
+            stroke: rgba(255, 255, 255, 0.4);
+        }
+
+        .tooltip {
+            position: absolute;
+            top: 0;
+            transform: translateX(-50%);
+            padding: 0.25rem 0.5rem;
+            background: rgba(0, 0, 0, 0.85);
+            border: 1px solid rgba(96, 96, 96, 0.5);
+            border-radius: 4px;
+            font-size: 0.75rem;
+            white-space: nowrap;
+            pointer-events: none;
+        }
+
+        .chart-note {
+            font-size: 0.75rem;
+            color: #808080;
+            padding: 2rem 0;
+            text-align: center;
+        }
+
+        .stale-notice {
+            color: #fbd38d;
+            font-size: 0.75rem;
+        }
+
+        .error-message {
+            color: #fc8181;
+            font-size: 0.8rem;
+        }
+        "#
+    );
+
+    let plot = match &scale {
+        Some(scale) => {
+            let step = data.len().div_ceil(MAX_POINTS);
+            let mut points: Vec<&Observation> = data.iter().step_by(step).collect();
+            if points.last().map(|obs| obs.date) != data.last().map(|obs| obs.date) {
+                points.extend(data.last());
+            }
+            let path = points
+                .iter()
+                .map(|obs| format!("{:.1},{:.1}", scale.x(obs.date), scale.y(obs.value)))
+                .collect::<Vec<_>>()
+                .join(" L");
+            let ticks: Vec<f64> = (0..=4).map(|i| scale.min + (scale.max - scale.min) * i as f64 / 4.0).collect();
+            let plot_bottom = HEIGHT - BOTTOM;
+
+            html! {
+                <>
+                    {for ticks.iter().map(|&tick| html! {
+                        <>
+                            <line class="grid-line" x1={LEFT.to_string()} x2={WIDTH.to_string()} y1={scale.y(tick).to_string()} y2={scale.y(tick).to_string()} />
+                            <text class="axis-label" x={(LEFT - 6.0).to_string()} y={(scale.y(tick) + 4.0).to_string()} text-anchor="end">
+                                {format_value(tick, source.unit())}
+                            </text>
+                        </>
+                    })}
+                    {if source.shades_inversion() {
+                        html! {
+                            <>
+                                {for negative_runs(data).into_iter().map(|(first, last)| {
+                                    let x = scale.x(data[first].date);
+                                    let width = (scale.x(data[last].date) - x).max(1.0);
+                                    html! {
+                                        <rect class="inversion" x={x.to_string()} y={TOP.to_string()} width={width.to_string()} height={(plot_bottom - TOP).to_string()} />
+                                    }
+                                })}
+                                <line class="zero-line" x1={LEFT.to_string()} x2={WIDTH.to_string()} y1={scale.y(0.0).to_string()} y2={scale.y(0.0).to_string()} />
+                            </>
+                        }
+                    } else {
+                        html! {}
+                    }}
+                    <path class="series-line" d={format!("M{}", path)} />
+                    <text class="axis-label" x={LEFT.to_string()} y={(HEIGHT - 6.0).to_string()}>{data[0].date.to_string()}</text>
+                    <text class="axis-label" x={WIDTH.to_string()} y={(HEIGHT - 6.0).to_string()} text-anchor="end">
+                        {data[data.len() - 1].date.to_string()}
+                    </text>
+                    {if let Some(obs) = (*hover).and_then(|i| data.get(i)) {
+                        html! {
+                            <>
+                                <line class="hover-line" x1={scale.x(obs.date).to_string()} x2={scale.x(obs.date).to_string()} y1={TOP.to_string()} y2={plot_bottom.to_string()} />
+                                <circle cx={scale.x(obs.date).to_string()} cy={scale.y(obs.value).to_string()} r="3.5" fill="#0ea5e9" />
+                            </>
+                        }
+                    } else {
+                        html! {}
+                    }}
+                </>
+            }
+        }
+        None => html! {},
+    };
+
+    html! {
+        <div class={css}>
+            <div class="chart-header">
+                <div class="chart-title">{source.label()}</div>
+                {for ChartRange::ALL.iter().map(|&option| {
+                    let range = range.clone();
+                    html! {
+                        <button
+                            class={classes!("range-button", (*range == option).then_some("active"))}
+                            onclick={Callback::from(move |_| range.set(option))}
+                        >
+                            {option.label()}
+                        </button>
+                    }
+                })}
+                {if let Some(href) = csv_href {
+                    html! { <a class="csv-link" {href} download={csv_name}>{"Export CSV"}</a> }
+                } else {
+                    html! {}
+                }}
+            </div>
+            
+            {if *stale {
+                html! { <div class="stale-notice">{"FRED could not be reached; showing cached data."}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else if *is_loading && data.is_empty() {
+                html! { <div class="chart-note">{"Loading…"}</div> }
+            } else if data.is_empty() {
+                html! { <div class="chart-note">{"No observations in this range"}</div> }
+            } else {
+                html! {
+                    <div class="chart-area">
+                        <svg
+                            ref={svg_ref}
+                            viewBox={format!("0 0 {} {}", WIDTH, HEIGHT)}
+                            onmousemove={on_mouse_move}
+                            onmouseleave={on_mouse_leave}
+                        >
+                            {plot}
+                        </svg>
+                        {if let (Some(obs), Some(scale)) = ((*hover).and_then(|i| data.get(i)), scale.as_ref()) {
+                            html! {
+                                <div class="tooltip" style={format!("left: {:.2}%", scale.x(obs.date) / WIDTH * 100.0)}>
+                                    {format!("{}: {}", obs.date, format_value(obs.value, source.unit()))}
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }}
+                    </div>
+                }
+            }}
+        </div>
+    }
+}
\ No newline at end of file
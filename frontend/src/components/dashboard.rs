@@ -1,68 +1,128 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
-use gloo_timers::callback::Timeout;
-use crate::{Route, services::auth::AuthService, types::UserInfo};
+use gloo_timers::callback::{Interval, Timeout};
+use crate::{Route, components::two_factor::TwoFactorSetup, config::AppConfig, services::{auth::use_auth_service, market::use_market_service, transport::Transport}, session::use_session, types::{ErrorCode, Role, UserInfo}};
 use stylist::yew::styled_component;
 
+const ALERT_POLL_MS: u32 = 60_000;
+
 #[styled_component(Dashboard)]
 pub fn dashboard() -> Html {
     let navigator = use_navigator().unwrap();
-    let auth_service = AuthService::new();
-    let user_info = use_state(|| UserInfo {
-        username: String::new(),
-        logged_in: false,
-    });
-    let is_loading = use_state(|| true);
+    let config = use_context::<AppConfig>().expect("AppConfig context is provided by App");
+    let auth_service = use_auth_service();
+    let session = use_session();
+    // The route guard only renders the dashboard for a signed-in user
+    let user_info = session.user().cloned().unwrap_or_else(UserInfo::logged_out);
     let show_praxis = use_state(|| false);
     let show_logout = use_state(|| false);
+    let expiry_warning = use_state(|| None::<u64>);
+    let market_service = use_market_service();
+    let unacknowledged = use_state(|| 0usize);
+    // Market data and funds are only served by the web backend
+    let is_desktop = Transport::is_desktop();
 
-    // Check authentication on component mount
+    // Bring in the logo and logout button
     {
-        let user_info = user_info.clone();
-        let is_loading = is_loading.clone();
-        let navigator = navigator.clone();
-        let auth_service = auth_service.clone();
         let show_praxis = show_praxis.clone();
         let show_logout = show_logout.clone();
         
         use_effect_with((), move |_| {
-            wasm_bindgen_futures::spawn_local(async move {
-                match auth_service.check_auth().await {
-                    Ok(user) => {
-                        if user.logged_in {
-                            user_info.set(user);
-                            is_loading.set(false);
-                            
-                            // Start animations
-                            Timeout::new(500, {
-                                let show_praxis = show_praxis.clone();
-                                move || show_praxis.set(true)
-                            }).forget();
-                            
-                            Timeout::new(1500, {
-                                let show_logout = show_logout.clone();
-                                move || show_logout.set(true)
-                            }).forget();
-                        } else {
-                            navigator.push(&Route::Login);
+            let praxis = Timeout::new(500, move || show_praxis.set(true));
+            let logout = Timeout::new(1500, move || show_logout.set(true));
+            move || {
+                drop(praxis);
+                drop(logout);
+            }
+        });
+    }
+
+    // Poll the session so the user is warned before it times out
+    {
+        let session = session.clone();
+        let auth_service = auth_service.clone();
+        let expiry_warning = expiry_warning.clone();
+        let poll_ms = config.session_poll_ms();
+        let warning_secs = config.session_warning_secs;
+
+        use_effect_with((), move |_| {
+            let interval = Interval::new(poll_ms, move || {
+                let session = session.clone();
+                let auth_service = auth_service.clone();
+                let expiry_warning = expiry_warning.clone();
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    match auth_service.check_auth().await {
+                        Ok(user) if user.logged_in => {
+                            let remaining = user.expires_in_secs.unwrap_or(u64::MAX);
+                            expiry_warning.set((remaining <= warning_secs).then_some(remaining));
                         }
+                        // A dropped connection is not a logout; try again on the next tick
+                        Err(error) if error.code == ErrorCode::ServerUnavailable => {}
+                        // Expired or ended elsewhere; the route guard returns here after login
+                        _ => session.sign_out(),
                     }
-                    Err(_) => {
-                        navigator.push(&Route::Login);
-                    }
-                }
+                });
             });
-            || ()
+            move || drop(interval)
         });
     }
 
+    // Keep the alerts badge current; the server checks the rules on its own schedule
+    {
+        let market_service = market_service.clone();
+        let unacknowledged = unacknowledged.clone();
+
+        use_effect_with((), move |_| {
+            let refresh = move || {
+                let market_service = market_service.clone();
+                let unacknowledged = unacknowledged.clone();
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(alerts) = market_service.alerts().await {
+                        unacknowledged.set(alerts.unacknowledged);
+                    }
+                });
+            };
+            refresh();
+            let interval = Interval::new(ALERT_POLL_MS, refresh);
+            move || drop(interval)
+        });
+    }
+
+    let on_stay_signed_in = {
+        let auth_service = auth_service.clone();
+        let expiry_warning = expiry_warning.clone();
+        let session = session.clone();
+        let warning_secs = config.session_warning_secs;
+
+        Callback::from(move |_| {
+            let auth_service = auth_service.clone();
+            let expiry_warning = expiry_warning.clone();
+            let session = session.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth_service.refresh_session().await {
+                    Ok(user) => {
+                        let remaining = user.expires_in_secs.unwrap_or(u64::MAX);
+                        expiry_warning.set((remaining <= warning_secs).then_some(remaining));
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(_) => {}
+                }
+            });
+        })
+    };
+
     let on_logout = {
         let auth_service = auth_service.clone();
         let navigator = navigator.clone();
+        let session = session.clone();
         
         Callback::from(move |_| {
             let auth_service = auth_service.clone();
             let navigator = navigator.clone();
+            let session = session.clone();
             
             wasm_bindgen_futures::spawn_local(async move {
                 let _ = auth_service.logout().await;
@@ -78,12 +138,33 @@ pub fn dashboard() -> Html {
                 
                 // Redirect after fade
                 Timeout::new(500, move || {
+                    session.sign_out();
                     navigator.push(&Route::Login);
                 }).forget();
             });
         })
     };
 
+    let on_audit_log = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Audit))
+    };
+
+    let on_risk_premiums = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::RiskPremiums))
+    };
+
+    let on_alerts = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Alerts))
+    };
+
+    let on_funds = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Funds))
+    };
+
     let css = css!(
         r#"
         body {
@@ -148,6 +229,75 @@ pub fn dashboard() -> Html {
             color: rgba(255, 255, 255, 0.9);
         }
 
+        .audit-link {
+            position: absolute;
+            top: 20px;
+            right: 120px;
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.7);
+            padding: 0.5rem 1rem;
+            border-radius: 8px;
+            cursor: pointer;
+            font-size: 0.9rem;
+        }
+
+        .markets-link {
+            position: absolute;
+            top: 20px;
+            left: 20px;
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.7);
+            padding: 0.5rem 1rem;
+            border-radius: 8px;
+            cursor: pointer;
+            font-size: 0.9rem;
+        }
+
+        .alerts-link {
+            position: absolute;
+            top: 20px;
+            left: 160px;
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.7);
+            padding: 0.5rem 1rem;
+            border-radius: 8px;
+            cursor: pointer;
+            font-size: 0.9rem;
+        }
+
+        .alerts-link.first {
+            left: 20px;
+        }
+
+        .funds-link {
+            position: absolute;
+            top: 20px;
+            left: 280px;
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.7);
+            padding: 0.5rem 1rem;
+            border-radius: 8px;
+            cursor: pointer;
+            font-size: 0.9rem;
+        }
+
+        .alerts-badge {
+            display: inline-block;
+            margin-left: 0.5rem;
+            min-width: 1.2rem;
+            padding: 0.05rem 0.35rem;
+            border-radius: 999px;
+            background: #e53e3e;
+            color: white;
+            font-size: 0.75rem;
+            font-weight: 600;
+            text-align: center;
+        }
+
         .dashboard-content {
             display: flex;
             justify-content: center;
@@ -164,37 +314,33 @@ pub fn dashboard() -> Html {
             letter-spacing: 0.05em;
         }
 
-        .loading-container {
+        .session-warning {
+            position: absolute;
+            top: 70px;
+            left: 50%;
+            transform: translateX(-50%);
             display: flex;
-            justify-content: center;
             align-items: center;
-            min-height: 100vh;
-            background-color: #000000;
-        }
-
-        .loading-spinner {
-            width: 40px;
-            height: 40px;
-            border: 3px solid rgba(255, 255, 255, 0.1);
-            border-radius: 50%;
-            border-top-color: rgba(255, 255, 255, 0.5);
-            animation: spin 1s ease-in-out infinite;
+            gap: 1rem;
+            padding: 0.75rem 1.25rem;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 8px;
+            background: rgba(255, 255, 255, 0.05);
+            color: rgba(255, 255, 255, 0.8);
+            font-size: 0.9rem;
         }
 
-        @keyframes spin {
-            to { transform: rotate(360deg); }
+        .session-warning button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.5);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.35rem 0.75rem;
+            border-radius: 6px;
+            cursor: pointer;
         }
         "#
     );
 
-    if *is_loading {
-        return html! {
-            <div class={classes!(css, "loading-container")}>
-                <div class="loading-spinner"></div>
-            </div>
-        };
-    }
-
     html! {
         <div class={classes!(css, "dashboard-container")}>
             <div class={classes!("praxis-logo", show_praxis.then_some("show"))}>
@@ -208,11 +354,55 @@ pub fn dashboard() -> Html {
                 {"Logout"}
             </button>
             
+            {if is_desktop {
+                html! {}
+            } else {
+                html! { <button class="markets-link" onclick={on_risk_premiums}>{"Risk premiums"}</button> }
+            }}
+            <button class={classes!("alerts-link", is_desktop.then_some("first"))} onclick={on_alerts}>
+                {"Alerts"}
+                {if *unacknowledged > 0 {
+                    html! { <span class="alerts-badge">{*unacknowledged}</span> }
+                } else {
+                    html! {}
+                }}
+            </button>
+            {if is_desktop {
+                html! {}
+            } else {
+                html! { <button class="funds-link" onclick={on_funds}>{"Funds"}</button> }
+            }}
+            
+            {if config.features.audit_log && user_info.role == Some(Role::Admin) {
+                html! {
+                    <button class="audit-link" onclick={on_audit_log}>{"Audit log"}</button>
+                }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(remaining) = *expiry_warning {
+                html! {
+                    <div class="session-warning">
+                        {format!("Your session expires in {} min", remaining.div_ceil(60))}
+                        <button onclick={on_stay_signed_in}>{"Stay signed in"}</button>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
             <div class="dashboard-content">
                 <div class="welcome-message">
                     {format!("Welcome, {}", user_info.username)}
                 </div>
             </div>
+
+            {if config.features.two_factor {
+                html! { <TwoFactorSetup username={user_info.username.clone()} /> }
+            } else {
+                html! {}
+            }}
         </div>
     }
 } 
\ No newline at end of file
@@ -0,0 +1,227 @@
+use yew::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+use crate::{services::market::use_market_service, session::use_session, types::{ASSUMED_PE, EarningsStatus, ErrorCode, ImportEarningsRequest, Role}};
+use stylist::yew::styled_component;
+
+#[derive(Properties, PartialEq)]
+pub struct EarningsSourcePanelProps {
+    /// Called after the source changes, to recompute what depends on it
+    pub on_change: Callback<()>,
+}
+
+/// Which S&P 500 earnings the equity risk premium uses. Analysts can import
+/// a CSV file to use instead of the maintained one.
+#[styled_component(EarningsSourcePanel)]
+pub fn earnings_source_panel(props: &EarningsSourcePanelProps) -> Html {
+    let market_service = use_market_service();
+    let session = use_session();
+    let status = use_state(|| None::<EarningsStatus>);
+    let error_message = use_state(|| None::<String>);
+    let is_busy = use_state(|| false);
+    let file_ref = use_node_ref();
+
+    let can_import = session
+        .user()
+        .and_then(|user| user.role)
+        .is_some_and(|role| role.includes(Role::Analyst));
+
+    {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let status = status.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.earnings_status().await {
+                    Ok(response) => status.set(Some(response)),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_import = {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let status = status.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let file_ref = file_ref.clone();
+        let on_change = props.on_change.clone();
+        
+        Callback::from(move |_| {
+            let Some(input) = file_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                error_message.set(Some("Choose a CSV file to import".to_string()));
+                return;
+            };
+            
+            let market_service = market_service.clone();
+            let session = session.clone();
+            let status = status.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            let on_change = on_change.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                let contents = JsFuture::from(file.text()).await.ok().and_then(|text| text.as_string());
+                let Some(contents) = contents else {
+                    error_message.set(Some("The file could not be read".to_string()));
+                    is_busy.set(false);
+                    return;
+                };
+                
+                let request = ImportEarningsRequest {
+                    file_name: file.name(),
+                    contents,
+                };
+                match market_service.import_earnings(&request).await {
+                    Ok(response) => {
+                        status.set(Some(response));
+                        error_message.set(None);
+                        input.set_value("");
+                        on_change.emit(());
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let on_clear = {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let status = status.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let on_change = props.on_change.clone();
+        
+        Callback::from(move |_| {
+            let market_service = market_service.clone();
+            let session = session.clone();
+            let status = status.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            let on_change = on_change.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.clear_earnings_import().await {
+                    Ok(response) => {
+                        status.set(Some(response));
+                        error_message.set(None);
+                        on_change.emit(());
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let css = css!(
+        r#"
+        margin-top: 2rem;
+        padding: 1rem;
+        border: 1px solid rgba(96, 96, 96, 0.3);
+        border-radius: 4px;
+        font-size: 0.8rem;
+
+        .panel-title {
+            font-size: 0.7rem;
+            letter-spacing: 0.1em;
+            color: #808080;
+            margin-bottom: 0.5rem;
+        }
+
+        .panel-detail {
+            color: #a0a0a0;
+            margin-bottom: 0.25rem;
+        }
+
+        .panel-actions {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.5rem;
+            align-items: center;
+            margin-top: 0.75rem;
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-top: 0.5rem;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <div class="panel-title">{"EARNINGS SOURCE"}</div>
+            {match status.as_ref() {
+                Some(status) => html! {
+                    <>
+                        <div class="panel-detail">
+                            {match (&status.source, &status.latest) {
+                                (Some(source), Some(latest)) => format!(
+                                    "{}: {} EPS of {:.2} as of {} ({} rows)",
+                                    source, latest.basis.label(), latest.eps, latest.as_of, status.rows
+                                ),
+                                _ => format!(
+                                    "No {} earnings figures, using an assumed P/E of {}",
+                                    status.basis.label(), ASSUMED_PE
+                                ),
+                            }}
+                        </div>
+                        {if let Some(import) = &status.import {
+                            html! {
+                                <div class="panel-detail">
+                                    {format!(
+                                        "Imported by {} on {}; replaces the maintained CSV",
+                                        import.imported_by, import.imported_at.format("%Y-%m-%d %H:%M UTC")
+                                    )}
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }}
+                    </>
+                },
+                None => html! {},
+            }}
+            
+            {if can_import {
+                html! {
+                    <div class="panel-actions">
+                        <input type="file" accept=".csv,text/csv" ref={file_ref} />
+                        <button onclick={on_import} disabled={*is_busy}>{"Import"}</button>
+                        {if status.as_ref().is_some_and(|status| status.import.is_some()) {
+                            html! {
+                                <button onclick={on_clear} disabled={*is_busy}>{"Use maintained CSV"}</button>
+                            }
+                        } else {
+                            html! {}
+                        }}
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,411 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use chrono::NaiveDate;
+use web_sys::HtmlInputElement;
+use crate::{Route, components::fund_detail::PALETTE, services::funds::use_fund_service, session::use_session, types::{format_month, ComparedSeries, ErrorCode, FundComparison, FundComparisonQuery, FundSummary, MAX_COMPARED, MIN_COMPARED}};
+use stylist::yew::styled_component;
+
+// SVG user units, as in the history charts
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 300.0;
+const LEFT: f64 = 64.0;
+const TOP: f64 = 10.0;
+const BOTTOM: f64 = 24.0;
+
+fn percent(value: f64) -> String {
+    format!("{:.2}%", value)
+}
+
+fn ratio(value: Option<f64>) -> String {
+    value.map(|value| format!("{:.2}", value)).unwrap_or_else(|| "—".to_string())
+}
+
+// Rows of the side-by-side table; the benchmark-relative ones are blank
+// without a benchmark
+const METRIC_ROWS: [(&str, fn(&ComparedSeries) -> String); 15] = [
+    ("End balance", |series| series.growth.last().map(|point| format!("${:.0}", point.balance)).unwrap_or_default()),
+    ("Annualized Return (CAGR)", |series| percent(series.metrics.cagr)),
+    ("Arithmetic Mean (monthly)", |series| percent(series.metrics.arithmetic_mean)),
+    ("Volatility", |series| percent(series.metrics.volatility)),
+    ("Sharpe Ratio", |series| ratio(series.metrics.sharpe)),
+    ("Sortino Ratio", |series| ratio(series.metrics.sortino)),
+    ("Maximum Drawdown", |series| percent(series.metrics.max_drawdown)),
+    ("Calmar Ratio", |series| ratio(series.metrics.calmar)),
+    ("Best Year", |series| format!("{} ({})", percent(series.metrics.best_year.1), series.metrics.best_year.0)),
+    ("Worst Year", |series| format!("{} ({})", percent(series.metrics.worst_year.1), series.metrics.worst_year.0)),
+    ("Historical VaR (5%)", |series| percent(series.metrics.value_at_risk)),
+    ("Beta", |series| ratio(series.relative.as_ref().and_then(|relative| relative.beta))),
+    ("Alpha (annualized)", |series| series.relative.as_ref().and_then(|relative| relative.alpha).map(percent).unwrap_or_else(|| "—".to_string())),
+    ("Upside Capture Ratio", |series| series.relative.as_ref().and_then(|relative| relative.upside_capture).map(percent).unwrap_or_else(|| "—".to_string())),
+    ("Downside Capture Ratio", |series| series.relative.as_ref().and_then(|relative| relative.downside_capture).map(percent).unwrap_or_else(|| "—".to_string())),
+];
+
+fn correlation_style(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value >= 0.0 => format!("background: rgba(14, 165, 233, {:.2})", value * 0.6),
+        Some(value) => format!("background: rgba(239, 68, 68, {:.2})", -value * 0.6),
+        None => String::new(),
+    }
+}
+
+/// Balance lines over the same months, each with its colour, so x is the
+/// month's position.
+pub fn growth_chart(lines: &[(&str, Vec<f64>)], first_month: NaiveDate, last_month: NaiveDate) -> Html {
+    let months = lines.first().map_or(0, |(_, balances)| balances.len());
+    let balances = lines.iter().flat_map(|(_, balances)| balances.iter().copied());
+    let min = balances.clone().fold(f64::INFINITY, f64::min);
+    let max = balances.fold(f64::NEG_INFINITY, f64::max);
+    if months == 0 || !min.is_finite() {
+        return html! {};
+    }
+    let padding = if max > min { (max - min) * 0.05 } else { 1.0 };
+    let (min, max) = (min - padding, max + padding);
+    let x = |i: usize| LEFT + i as f64 / (months.max(2) - 1) as f64 * (WIDTH - LEFT);
+    let y = |value: f64| TOP + (max - value) / (max - min) * (HEIGHT - TOP - BOTTOM);
+    let ticks: Vec<f64> = (0..=4).map(|i| min + (max - min) * i as f64 / 4.0).collect();
+
+    html! {
+        <svg viewBox={format!("0 0 {} {}", WIDTH, HEIGHT)}>
+            {for ticks.iter().map(|&tick| html! {
+                <>
+                    <line class="grid-line" x1={LEFT.to_string()} x2={WIDTH.to_string()} y1={y(tick).to_string()} y2={y(tick).to_string()} />
+                    <text class="axis-label" x={(LEFT - 6.0).to_string()} y={(y(tick) + 4.0).to_string()} text-anchor="end">
+                        {format!("${:.0}", tick)}
+                    </text>
+                </>
+            })}
+            {for lines.iter().map(|(color, balances)| {
+                let path = balances
+                    .iter()
+                    .enumerate()
+                    .map(|(m, balance)| format!("{:.1},{:.1}", x(m), y(*balance)))
+                    .collect::<Vec<_>>()
+                    .join(" L");
+                html! {
+                    <path class="series-line" d={format!("M{}", path)} stroke={color.to_string()} />
+                }
+            })}
+            <text class="axis-label" x={LEFT.to_string()} y={(HEIGHT - 6.0).to_string()}>{format_month(first_month)}</text>
+            <text class="axis-label" x={WIDTH.to_string()} y={(HEIGHT - 6.0).to_string()} text-anchor="end">
+                {format_month(last_month)}
+            </text>
+        </svg>
+    }
+}
+
+/// Two to five funds, and optionally a benchmark, over the months they all
+/// have.
+#[styled_component(FundComparisonView)]
+pub fn fund_comparison() -> Html {
+    let navigator = use_navigator().unwrap();
+    let fund_service = use_fund_service();
+    let session = use_session();
+    let funds = use_state(Vec::<FundSummary>::new);
+    let selected = use_state(Vec::<u32>::new);
+    let comparison = use_state(|| None::<FundComparison>);
+    let error_message = use_state(|| None::<String>);
+    let is_busy = use_state(|| false);
+    let benchmark_ref = use_node_ref();
+    let risk_free_ref = use_node_ref();
+
+    {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let funds = funds.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.funds().await {
+                    Ok(response) => funds.set(response),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_compare = {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let selected = selected.clone();
+        let comparison = comparison.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let benchmark_ref = benchmark_ref.clone();
+        let risk_free_ref = risk_free_ref.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            
+            let input = |node: &NodeRef| node.cast::<HtmlInputElement>().map(|input| input.value()).unwrap_or_default();
+            let benchmark = input(&benchmark_ref).trim().to_string();
+            let risk_free = input(&risk_free_ref).trim().to_string();
+            let risk_free = if risk_free.is_empty() { Some(0.0) } else { risk_free.parse::<f64>().ok() };
+            let Some(risk_free) = risk_free else {
+                error_message.set(Some("Enter the risk-free rate as a number".to_string()));
+                return;
+            };
+            let query = FundComparisonQuery {
+                funds: selected.iter().map(|number| number.to_string()).collect::<Vec<_>>().join(","),
+                benchmark: (!benchmark.is_empty()).then_some(benchmark),
+                risk_free,
+            };
+            
+            let fund_service = fund_service.clone();
+            let session = session.clone();
+            let comparison = comparison.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.compare(&query).await {
+                    Ok(response) => {
+                        comparison.set(Some(response));
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let on_back = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Funds))
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        h2 {
+            font-size: 1rem;
+            font-weight: 400;
+            margin-top: 2rem;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+
+        .compare-form {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.75rem 1.25rem;
+            align-items: center;
+            margin-bottom: 1.5rem;
+            font-size: 0.8rem;
+        }
+
+        .fund-choices {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.5rem 1rem;
+        }
+
+        input[type="text"], input[type="number"] {
+            background: #000000;
+            color: rgba(255, 255, 255, 0.9);
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 6px;
+            padding: 0.3rem;
+            width: 7rem;
+        }
+
+        .hint {
+            font-size: 0.75rem;
+            color: #808080;
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        .legend {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 1rem;
+            font-size: 0.8rem;
+            margin-bottom: 0.5rem;
+        }
+
+        .swatch {
+            display: inline-block;
+            width: 0.7rem;
+            height: 0.7rem;
+            margin-right: 0.4rem;
+            border-radius: 2px;
+        }
+
+        svg {
+            display: block;
+            width: 100%;
+            height: auto;
+        }
+
+        .axis-label {
+            fill: #808080;
+            font-size: 11px;
+        }
+
+        .grid-line {
+            stroke: rgba(96, 96, 96, 0.3);
+            stroke-width: 1;
+        }
+
+        .series-line {
+            fill: none;
+            stroke-width: 1.5;
+        }
+
+        table {
+            width: 100%;
+            border-collapse: collapse;
+            font-size: 0.8rem;
+        }
+
+        th, td {
+            text-align: left;
+            padding: 0.5rem;
+            border-bottom: 1px solid rgba(96, 96, 96, 0.3);
+        }
+
+        th {
+            font-weight: 400;
+            color: #808080;
+        }
+
+        .number {
+            text-align: right;
+            font-variant-numeric: tabular-nums;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Funds"}</button>
+            <h1>{"Compare funds"}</h1>
+            
+            <form class="compare-form" onsubmit={on_compare}>
+                <div class="fund-choices">
+                    {for funds.iter().map(|fund| {
+                        let number = fund.number;
+                        let is_checked = selected.contains(&number);
+                        let on_toggle = {
+                            let selected = selected.clone();
+                            Callback::from(move |_| {
+                                let mut updated = (*selected).clone();
+                                if updated.contains(&number) {
+                                    updated.retain(|other| *other != number);
+                                } else {
+                                    updated.push(number);
+                                }
+                                selected.set(updated);
+                            })
+                        };
+                        html! {
+                            <label key={fund.number}>
+                                <input
+                                    type="checkbox"
+                                    checked={is_checked}
+                                    disabled={!is_checked && selected.len() >= MAX_COMPARED}
+                                    onchange={on_toggle}
+                                />
+                                {format!(" Fund {}", fund.number)}
+                            </label>
+                        }
+                    })}
+                </div>
+                <input ref={benchmark_ref} type="text" placeholder="Benchmark ticker" />
+                <input ref={risk_free_ref} type="number" step="any" placeholder="Risk-free %" />
+                <button type="submit" disabled={*is_busy || selected.len() < MIN_COMPARED}>{"Compare"}</button>
+                <span class="hint">{format!("Choose {} to {} funds", MIN_COMPARED, MAX_COMPARED)}</span>
+            </form>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(comparison) = comparison.as_ref() {
+                html! {
+                    <>
+                        <h2>{format!("Growth of $10,000, {} to {}", format_month(comparison.first_month), format_month(comparison.last_month))}</h2>
+                        <div class="legend">
+                            {for comparison.series.iter().enumerate().map(|(i, series)| html! {
+                                <span>
+                                    <span class="swatch" style={format!("background: {}", PALETTE[i % PALETTE.len()])}></span>
+                                    {&series.label}
+                                </span>
+                            })}
+                        </div>
+                        {growth_chart(
+                            &comparison.series.iter().enumerate().map(|(i, series)| {
+                                (PALETTE[i % PALETTE.len()], series.growth.iter().map(|point| point.balance).collect())
+                            }).collect::<Vec<_>>(),
+                            comparison.first_month,
+                            comparison.last_month,
+                        )}
+                        
+                        <h2>{"Risk and return"}</h2>
+                        <table>
+                            <tr>
+                                <th>{"Metric"}</th>
+                                {for comparison.series.iter().map(|series| html! { <th class="number">{&series.label}</th> })}
+                            </tr>
+                            {for METRIC_ROWS.iter().map(|(metric, value)| html! {
+                                <tr>
+                                    <td>{*metric}</td>
+                                    {for comparison.series.iter().map(|series| html! { <td class="number">{value(series)}</td> })}
+                                </tr>
+                            })}
+                        </table>
+                        
+                        <h2>{"Correlation of monthly returns"}</h2>
+                        <table>
+                            <tr>
+                                <th></th>
+                                {for comparison.series.iter().map(|series| html! { <th class="number">{&series.label}</th> })}
+                            </tr>
+                            {for comparison.series.iter().zip(&comparison.correlations).map(|(series, row)| html! {
+                                <tr>
+                                    <td>{&series.label}</td>
+                                    {for row.iter().map(|value| html! {
+                                        <td class="number" style={correlation_style(*value)}>{ratio(*value)}</td>
+                                    })}
+                                </tr>
+                            })}
+                        </table>
+                    </>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,454 @@
+use std::f64::consts::PI;
+use yew::prelude::*;
+use yew_router::prelude::*;
+use crate::{Route, components::{leverage::LeveragePanel, risk_metrics::RiskMetricsPanel}, services::funds::use_fund_service, session::use_session, types::{format_month, Drawdown, ErrorCode, FundResponse, FundStat, Holding, Role, YearReturns, MONTH_NAMES}};
+use stylist::yew::styled_component;
+
+// Used for holdings whose file gives no colour, and for the comparison lines
+pub const PALETTE: [&str; 8] = ["#4285f4", "#34a853", "#fbbc05", "#ea4335", "#a142f4", "#24c1e0", "#f06292", "#9e9e9e"];
+
+// A month this far from zero gets the strongest heatmap colour
+const HEATMAP_SCALE: f64 = 5.0;
+
+fn holding_color(holding: &Holding, index: usize) -> String {
+    holding.color.clone().unwrap_or_else(|| PALETTE[index % PALETTE.len()].to_string())
+}
+
+/// SVG path of a pie slice from `start` to `end`, as fractions of a turn
+/// clockwise from twelve o'clock.
+fn slice_path(start: f64, end: f64, radius: f64) -> String {
+    let point = |fraction: f64| {
+        let angle = fraction * 2.0 * PI - PI / 2.0;
+        (radius + radius * angle.cos(), radius + radius * angle.sin())
+    };
+    let (x0, y0) = point(start);
+    let (x1, y1) = point(end);
+    let large_arc = if end - start > 0.5 { 1 } else { 0 };
+    format!(
+        "M{r},{r} L{:.2},{:.2} A{r},{r} 0 {} 1 {:.2},{:.2} Z",
+        x0, y0, large_arc, x1, y1,
+        r = radius
+    )
+}
+
+fn heatmap_style(value: Option<f64>) -> String {
+    match value {
+        Some(value) => {
+            let strength = (value.abs() / HEATMAP_SCALE).min(1.0) * 0.6 + 0.05;
+            if value >= 0.0 {
+                format!("background: rgba(16, 185, 129, {:.2})", strength)
+            } else {
+                format!("background: rgba(239, 68, 68, {:.2})", strength)
+            }
+        }
+        None => String::new(),
+    }
+}
+
+fn format_percent(value: Option<f64>) -> String {
+    value.map(|value| format!("{:.2}%", value)).unwrap_or_default()
+}
+
+fn holdings_pie(holdings: &[Holding]) -> Html {
+    const RADIUS: f64 = 100.0;
+    let total: f64 = holdings.iter().map(|holding| holding.allocation.max(0.0)).sum();
+    if total <= 0.0 {
+        return html! {};
+    }
+
+    let mut start = 0.0;
+    let slices = holdings.iter().enumerate().map(|(i, holding)| {
+        let share = holding.allocation.max(0.0) / total;
+        let end = start + share;
+        let color = holding_color(holding, i);
+        let slice = if share >= 1.0 {
+            html! { <circle cx={RADIUS.to_string()} cy={RADIUS.to_string()} r={RADIUS.to_string()} fill={color} /> }
+        } else {
+            html! {
+                <path d={slice_path(start, end, RADIUS)} fill={color}>
+                    <title>{format!("{} {:.2}%", holding.ticker, holding.allocation)}</title>
+                </path>
+            }
+        };
+        start = end;
+        slice
+    }).collect::<Html>();
+
+    html! {
+        <svg class="pie" viewBox={format!("0 0 {} {}", RADIUS * 2.0, RADIUS * 2.0)}>
+            {slices}
+        </svg>
+    }
+}
+
+fn stat_table(title: &str, rows: &[FundStat]) -> Html {
+    if rows.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="stat-table">
+            <h2>{title}</h2>
+            <table>
+                <tr>
+                    <th>{"Metric"}</th>
+                    <th class="number">{"Fund"}</th>
+                    <th class="number">{"Benchmark"}</th>
+                </tr>
+                {for rows.iter().map(|row| html! {
+                    <tr>
+                        <td>{&row.metric}</td>
+                        <td class="number">{row.fund.format()}</td>
+                        <td class="number">{row.benchmark.format()}</td>
+                    </tr>
+                })}
+            </table>
+        </div>
+    }
+}
+
+fn returns_heatmap(years: &[YearReturns]) -> Html {
+    html! {
+        <table class="heatmap">
+            <tr>
+                <th>{"Year"}</th>
+                {for MONTH_NAMES.iter().map(|name| html! { <th class="number">{*name}</th> })}
+                <th class="number">{"Total"}</th>
+                <th class="number">{"Inflation"}</th>
+            </tr>
+            // Newest year first, as on the website
+            {for years.iter().rev().map(|year| html! {
+                <tr key={year.year}>
+                    <td>{year.year}</td>
+                    {for year.months.iter().map(|month| html! {
+                        <td class="number" style={heatmap_style(*month)}>{format_percent(*month)}</td>
+                    })}
+                    <td class="number total" style={heatmap_style(year.total)}>{format_percent(year.total)}</td>
+                    <td class="number">{format_percent(year.inflation)}</td>
+                </tr>
+            })}
+        </table>
+    }
+}
+
+pub fn drawdown_table(drawdowns: &[Drawdown]) -> Html {
+    html! {
+        <table>
+            <tr>
+                <th>{"Rank"}</th>
+                <th>{"Start"}</th>
+                <th>{"Bottom"}</th>
+                <th>{"Recovered"}</th>
+                <th class="number">{"Depth"}</th>
+            </tr>
+            {for drawdowns.iter().map(|drawdown| html! {
+                <tr key={drawdown.rank}>
+                    <td>{drawdown.rank}</td>
+                    <td>{format_month(drawdown.start)}</td>
+                    <td>{format_month(drawdown.bottom)}</td>
+                    <td>{drawdown.recovered.map(format_month).unwrap_or_else(|| "Not yet".to_string())}</td>
+                    <td class="number">{format!("{:.2}%", drawdown.depth)}</td>
+                </tr>
+            })}
+        </table>
+    }
+}
+
+fn bullet_list(title: &str, items: &[String]) -> Html {
+    if items.is_empty() {
+        return html! {};
+    }
+    html! {
+        <>
+            <h3>{title}</h3>
+            <ul>
+                {for items.iter().map(|item| html! { <li>{item}</li> })}
+            </ul>
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct FundDetailProps {
+    pub id: u32,
+}
+
+/// One fund: holdings, the performance and risk tables, monthly returns,
+/// drawdowns and the strategy behind it.
+#[styled_component(FundDetail)]
+pub fn fund_detail(props: &FundDetailProps) -> Html {
+    let navigator = use_navigator().unwrap();
+    let fund_service = use_fund_service();
+    let session = use_session();
+    let response = use_state(|| None::<FundResponse>);
+    let error_message = use_state(|| None::<String>);
+
+    {
+        let response = response.clone();
+        let session = session.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with(props.id, move |&id| {
+            response.set(None);
+            error_message.set(None);
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.fund(id).await {
+                    Ok(fund) => response.set(Some(fund)),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_back = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Funds))
+    };
+
+    let is_admin = session.user().and_then(|user| user.role).is_some_and(|role| role.includes(Role::Admin));
+    let on_edit = {
+        let navigator = navigator.clone();
+        let id = props.id;
+        Callback::from(move |_| navigator.push(&Route::FundEdit { id }))
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        h2 {
+            font-size: 1rem;
+            font-weight: 400;
+            margin-top: 2rem;
+        }
+
+        h3 {
+            font-size: 0.85rem;
+            font-weight: 500;
+            color: #808080;
+            margin-top: 1.25rem;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        .issues {
+            margin-bottom: 1rem;
+            padding: 0.75rem 1rem;
+            border: 1px solid rgba(251, 211, 141, 0.4);
+            border-radius: 4px;
+            color: #fbd38d;
+            font-size: 0.8rem;
+        }
+
+        .issues ul {
+            margin: 0.5rem 0 0;
+            padding-left: 1.25rem;
+        }
+
+        .holdings {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 2rem;
+            align-items: flex-start;
+        }
+
+        .pie {
+            width: 220px;
+            height: 220px;
+            flex-shrink: 0;
+        }
+
+        .swatch {
+            display: inline-block;
+            width: 0.7rem;
+            height: 0.7rem;
+            margin-right: 0.5rem;
+            border-radius: 2px;
+        }
+
+        .tables {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 2rem;
+        }
+
+        .stat-table {
+            flex: 1;
+            min-width: 320px;
+        }
+
+        .holdings table {
+            flex: 1;
+            min-width: 320px;
+        }
+
+        table {
+            width: 100%;
+            border-collapse: collapse;
+            font-size: 0.8rem;
+        }
+
+        th, td {
+            text-align: left;
+            padding: 0.5rem;
+            border-bottom: 1px solid rgba(96, 96, 96, 0.3);
+        }
+
+        th {
+            font-weight: 400;
+            color: #808080;
+        }
+
+        .number {
+            text-align: right;
+            font-variant-numeric: tabular-nums;
+        }
+
+        .heatmap-wrapper {
+            overflow-x: auto;
+        }
+
+        .heatmap td {
+            padding: 0.4rem;
+            color: #ffffff;
+        }
+
+        .heatmap .total {
+            font-weight: 600;
+        }
+
+        .strategy {
+            max-width: 60rem;
+            font-size: 0.85rem;
+            line-height: 1.5;
+        }
+
+        .empty {
+            font-size: 0.8rem;
+            color: #606060;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Funds"}</button>
+            {if is_admin {
+                html! { <>{" "}<button onclick={on_edit}>{"Edit fund"}</button></> }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(FundResponse { fund, issues }) = response.as_ref() {
+                html! {
+                    <>
+                        <h1>{&fund.name}</h1>
+                        
+                        {if issues.is_empty() {
+                            html! {}
+                        } else {
+                            html! {
+                                <div class="issues">
+                                    {"Some of this fund's data looks wrong:"}
+                                    <ul>
+                                        {for issues.iter().map(|issue| html! { <li>{issue}</li> })}
+                                    </ul>
+                                </div>
+                            }
+                        }}
+                        
+                        <h2>{"Holdings"}</h2>
+                        <div class="holdings">
+                            {holdings_pie(&fund.holdings)}
+                            <table>
+                                <tr>
+                                    <th>{"Ticker"}</th>
+                                    <th>{"Name"}</th>
+                                    <th class="number">{"Allocation"}</th>
+                                </tr>
+                                {for fund.holdings.iter().enumerate().map(|(i, holding)| html! {
+                                    <tr>
+                                        <td>
+                                            <span class="swatch" style={format!("background: {}", holding_color(holding, i))}></span>
+                                            {&holding.ticker}
+                                        </td>
+                                        <td>{&holding.name}</td>
+                                        <td class="number">{format!("{:.2}%", holding.allocation)}</td>
+                                    </tr>
+                                })}
+                            </table>
+                        </div>
+                        
+                        <div class="tables">
+                            {stat_table("Performance", &fund.performance)}
+                            {stat_table("Risk", &fund.risk_metrics)}
+                        </div>
+                        
+                        <h2>{"Risk from monthly returns"}</h2>
+                        <RiskMetricsPanel id={fund.number} />
+                        
+                        <h2>{"Monthly returns"}</h2>
+                        {if fund.monthly_returns.is_empty() {
+                            html! { <div class="empty">{"No monthly returns."}</div> }
+                        } else {
+                            html! { <div class="heatmap-wrapper">{returns_heatmap(&fund.monthly_returns)}</div> }
+                        }}
+                        
+                        <h2>{"Drawdowns"}</h2>
+                        {if fund.drawdowns.is_empty() {
+                            html! { <div class="empty">{"No drawdowns."}</div> }
+                        } else {
+                            drawdown_table(&fund.drawdowns)
+                        }}
+                        
+                        <h2>{"Leverage"}</h2>
+                        <LeveragePanel id={fund.number} />
+                        
+                        <h2>{"Strategy"}</h2>
+                        <div class="strategy">
+                            <p>{&fund.strategy.overview}</p>
+                            {bullet_list("Assets", &fund.strategy.assets)}
+                            {bullet_list("Why it works", &fund.strategy.why_it_works)}
+                            {bullet_list("Best suited for", &fund.strategy.best_suited)}
+                            {bullet_list("Effective during", &fund.strategy.effective_during)}
+                        </div>
+                    </>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,776 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use crate::{Route, services::funds::use_fund_service, session::use_session, types::{ErrorCode, FundChange, FundDefinition, FundDiff, FundDiffQuery, FundVersionRequest, FundVersions, Holding, SaveFundRequest, Strategy, MAX_HOLDINGS}};
+use stylist::yew::styled_component;
+
+// What the colour picker shows for a holding without a colour
+const DEFAULT_COLOR: &str = "#888888";
+
+fn blank_holding() -> Holding {
+    Holding {
+        ticker: String::new(),
+        name: String::new(),
+        allocation: 0.0,
+        color: None,
+    }
+}
+
+fn new_definition() -> FundDefinition {
+    FundDefinition {
+        holdings: vec![blank_holding()],
+        ..FundDefinition::default()
+    }
+}
+
+// Applies an edit to the draft; the server trims and re-checks everything on save
+fn update(draft: &UseStateHandle<Option<FundDefinition>>, change: impl FnOnce(&mut FundDefinition)) {
+    if let Some(mut definition) = (**draft).clone() {
+        change(&mut definition);
+        draft.set(Some(definition));
+    }
+}
+
+fn input_value(e: &InputEvent) -> String {
+    e.target_unchecked_into::<HtmlInputElement>().value()
+}
+
+// Strategy lists are edited one entry per line; blank lines are dropped on save
+fn list_editor(label: &str, draft: &UseStateHandle<Option<FundDefinition>>, field: fn(&mut Strategy) -> &mut Vec<String>) -> Html {
+    let Some(definition) = draft.as_ref() else {
+        return html! {};
+    };
+    let mut strategy = definition.strategy.clone();
+    let text = field(&mut strategy).join("\n");
+    let oninput = {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            let text = e.target_unchecked_into::<HtmlTextAreaElement>().value();
+            update(&draft, |definition| *field(&mut definition.strategy) = text.split('\n').map(str::to_string).collect());
+        })
+    };
+
+    html! {
+        <label class="field">
+            <span>{format!("{} (one per line)", label)}</span>
+            <textarea rows="4" value={text} {oninput} />
+        </label>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct FundEditorProps {
+    /// `None` for a new fund
+    #[prop_or_default]
+    pub id: Option<u32>,
+}
+
+/// An admin's form for a fund's name, holdings and strategy. Every save is
+/// kept as a version, which can be compared with any other or restored.
+#[styled_component(FundEditor)]
+pub fn fund_editor(props: &FundEditorProps) -> Html {
+    let navigator = use_navigator().unwrap();
+    let fund_service = use_fund_service();
+    let session = use_session();
+    let draft = use_state(|| None::<FundDefinition>);
+    let versions = use_state(|| None::<FundVersions>);
+    let diff = use_state(|| None::<FundDiff>);
+    let error_message = use_state(|| None::<String>);
+    let notice = use_state(|| None::<String>);
+    let is_busy = use_state(|| false);
+    let confirm_delete = use_state(|| false);
+    let from_ref = use_node_ref();
+    let to_ref = use_node_ref();
+
+    {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let draft = draft.clone();
+        let versions = versions.clone();
+        let diff = diff.clone();
+        let error_message = error_message.clone();
+        let confirm_delete = confirm_delete.clone();
+        
+        use_effect_with(props.id, move |&id| {
+            diff.set(None);
+            error_message.set(None);
+            confirm_delete.set(false);
+
+            match id {
+                None => {
+                    versions.set(None);
+                    draft.set(Some(new_definition()));
+                }
+                Some(id) => wasm_bindgen_futures::spawn_local(async move {
+                    match fund_service.fund_versions(id).await {
+                        Ok(response) => {
+                            draft.set(response.current.clone());
+                            versions.set(Some(response));
+                        }
+                        Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                        Err(error) => error_message.set(Some(error.message)),
+                    }
+                }),
+            }
+            || ()
+        });
+    }
+
+    // Every change to the fund comes back as its versions
+    let on_saved = {
+        let navigator = navigator.clone();
+        let draft = draft.clone();
+        let versions = versions.clone();
+        let diff = diff.clone();
+        let error_message = error_message.clone();
+        let notice = notice.clone();
+        let confirm_delete = confirm_delete.clone();
+        let id = props.id;
+        
+        Callback::from(move |(response, message): (FundVersions, String)| {
+            notice.set(Some(message));
+            error_message.set(None);
+            confirm_delete.set(false);
+            diff.set(None);
+            if id.is_none() {
+                navigator.push(&Route::FundEdit { id: response.number });
+                return;
+            }
+            draft.set(response.current.clone());
+            versions.set(Some(response));
+        })
+    };
+
+    let on_save = {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let draft = draft.clone();
+        let versions = versions.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let on_saved = on_saved.clone();
+        let id = props.id;
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let Some(definition) = (*draft).clone() else {
+                return;
+            };
+            let request = SaveFundRequest {
+                definition,
+                base_version: versions.as_ref().and_then(FundVersions::latest_version),
+            };
+            let fund_service = fund_service.clone();
+            let session = session.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            let on_saved = on_saved.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = match id {
+                    Some(id) => fund_service.save_fund(id, &request).await,
+                    None => fund_service.create_fund(&request).await,
+                };
+                match result {
+                    Ok(response) => {
+                        let message = match response.latest_version() {
+                            Some(version) => format!("Saved as version {}", version),
+                            None => "Saved".to_string(),
+                        };
+                        on_saved.emit((response, message));
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    // The first click asks for a second
+    let on_delete = {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let versions = versions.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let confirm_delete = confirm_delete.clone();
+        let on_saved = on_saved.clone();
+        let id = props.id;
+        
+        Callback::from(move |_| {
+            let Some(id) = id else {
+                return;
+            };
+            if !*confirm_delete {
+                confirm_delete.set(true);
+                return;
+            }
+            let request = FundVersionRequest {
+                base_version: versions.as_ref().and_then(FundVersions::latest_version),
+            };
+            let fund_service = fund_service.clone();
+            let session = session.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            let on_saved = on_saved.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.delete_fund(id, &request).await {
+                    Ok(response) => on_saved.emit((response, "Fund deleted; restore a version to bring it back".to_string())),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let restore = {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let versions = versions.clone();
+        let error_message = error_message.clone();
+        let is_busy = is_busy.clone();
+        let on_saved = on_saved.clone();
+        let id = props.id;
+        
+        Callback::from(move |version: u32| {
+            let Some(id) = id else {
+                return;
+            };
+            let request = FundVersionRequest {
+                base_version: versions.as_ref().and_then(FundVersions::latest_version),
+            };
+            let fund_service = fund_service.clone();
+            let session = session.clone();
+            let error_message = error_message.clone();
+            let is_busy = is_busy.clone();
+            let on_saved = on_saved.clone();
+            
+            is_busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.restore_fund_version(id, version, &request).await {
+                    Ok(response) => on_saved.emit((response, format!("Restored version {}", version))),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let show_diff = {
+        let fund_service = fund_service.clone();
+        let session = session.clone();
+        let diff = diff.clone();
+        let error_message = error_message.clone();
+        let id = props.id;
+        
+        Callback::from(move |query: FundDiffQuery| {
+            let Some(id) = id else {
+                return;
+            };
+            let fund_service = fund_service.clone();
+            let session = session.clone();
+            let diff = diff.clone();
+            let error_message = error_message.clone();
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.diff_fund_versions(id, query).await {
+                    Ok(response) => diff.set(Some(response)),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+            });
+        })
+    };
+
+    let on_compare = {
+        let show_diff = show_diff.clone();
+        let from_ref = from_ref.clone();
+        let to_ref = to_ref.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let version = |node: &NodeRef| node.cast::<HtmlSelectElement>().and_then(|select| select.value().parse::<u32>().ok());
+            if let (Some(from), Some(to)) = (version(&from_ref), version(&to_ref)) {
+                show_diff.emit(FundDiffQuery { from, to });
+            }
+        })
+    };
+
+    let on_back = {
+        let navigator = navigator.clone();
+        let id = props.id.filter(|_| versions.as_ref().is_some_and(|versions| versions.current.is_some()));
+        Callback::from(move |_| match id {
+            Some(id) => navigator.push(&Route::Fund { id }),
+            None => navigator.push(&Route::Funds),
+        })
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        h2 {
+            font-size: 1rem;
+            font-weight: 400;
+            margin-top: 2rem;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+
+        button:disabled {
+            opacity: 0.4;
+            cursor: default;
+        }
+
+        button.danger {
+            border-color: rgba(252, 129, 129, 0.6);
+            color: #fc8181;
+        }
+
+        input, select, textarea {
+            background: #000000;
+            color: rgba(255, 255, 255, 0.9);
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 6px;
+            padding: 0.3rem;
+            font-family: inherit;
+            font-size: 0.8rem;
+        }
+
+        input[type="color"] {
+            width: 2.5rem;
+            height: 1.8rem;
+            padding: 0.1rem;
+        }
+
+        .field {
+            display: flex;
+            flex-direction: column;
+            gap: 0.3rem;
+            max-width: 40rem;
+            margin-bottom: 1rem;
+            font-size: 0.8rem;
+            color: #808080;
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        .notice {
+            color: #68d391;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        .issues {
+            color: #fbd38d;
+            font-size: 0.8rem;
+            margin-bottom: 1rem;
+        }
+
+        table {
+            width: 100%;
+            max-width: 60rem;
+            border-collapse: collapse;
+            font-size: 0.8rem;
+        }
+
+        th, td {
+            text-align: left;
+            padding: 0.4rem 0.5rem;
+            border-bottom: 1px solid rgba(96, 96, 96, 0.3);
+        }
+
+        th {
+            font-weight: 400;
+            color: #808080;
+        }
+
+        td input {
+            width: 100%;
+            box-sizing: border-box;
+        }
+
+        .number {
+            text-align: right;
+        }
+
+        .off-total {
+            color: #fbd38d;
+        }
+
+        .toolbar {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.5rem;
+            align-items: center;
+            margin-top: 1rem;
+            font-size: 0.8rem;
+        }
+
+        .removed {
+            color: #fc8181;
+        }
+
+        .added {
+            color: #68d391;
+        }
+
+        .empty {
+            font-size: 0.8rem;
+            color: #606060;
+        }
+        "#
+    );
+
+    let title = match (props.id, draft.as_ref()) {
+        (None, _) => "New fund".to_string(),
+        (Some(_), Some(definition)) if !definition.name.trim().is_empty() => format!("Edit {}", definition.name),
+        (Some(id), _) => format!("Fund {}", id),
+    };
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Back"}</button>
+            <h1>{title}</h1>
+
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            {if let Some(message) = notice.as_ref() {
+                html! { <div class="notice">{message}</div> }
+            } else {
+                html! {}
+            }}
+
+            {if let Some(definition) = draft.as_ref() {
+                let errors = definition.clone().normalized().validate();
+                let total: f64 = definition.holdings.iter().map(|holding| holding.allocation).sum();
+                let on_name = {
+                    let draft = draft.clone();
+                    Callback::from(move |e: InputEvent| {
+                        let value = input_value(&e);
+                        update(&draft, |definition| definition.name = value);
+                    })
+                };
+                let on_balance = {
+                    let draft = draft.clone();
+                    Callback::from(move |e: InputEvent| {
+                        let value = input_value(&e);
+                        let value = value.trim();
+                        if value.is_empty() {
+                            update(&draft, |definition| definition.starting_balance = None);
+                        } else if let Ok(balance) = value.parse::<f64>() {
+                            update(&draft, |definition| definition.starting_balance = Some(balance));
+                        }
+                    })
+                };
+                let on_overview = {
+                    let draft = draft.clone();
+                    Callback::from(move |e: InputEvent| {
+                        let value = e.target_unchecked_into::<HtmlTextAreaElement>().value();
+                        update(&draft, |definition| definition.strategy.overview = value);
+                    })
+                };
+                let on_add_holding = {
+                    let draft = draft.clone();
+                    Callback::from(move |_| update(&draft, |definition| definition.holdings.push(blank_holding())))
+                };
+
+                html! {
+                    <form onsubmit={on_save}>
+                        <label class="field">
+                            <span>{"Name"}</span>
+                            <input type="text" value={definition.name.clone()} oninput={on_name} />
+                        </label>
+                        <label class="field">
+                            <span>{"Starting balance ($, optional)"}</span>
+                            <input
+                                type="number"
+                                min="0"
+                                step="any"
+                                value={definition.starting_balance.map(|balance| balance.to_string()).unwrap_or_default()}
+                                oninput={on_balance}
+                            />
+                        </label>
+
+                        <h2>{"Holdings"}</h2>
+                        <table>
+                            <tr>
+                                <th>{"Ticker"}</th>
+                                <th>{"Name"}</th>
+                                <th class="number">{"Allocation (%)"}</th>
+                                <th>{"Colour"}</th>
+                                <th></th>
+                            </tr>
+                            {for definition.holdings.iter().enumerate().map(|(i, holding)| {
+                                let on_ticker = {
+                                    let draft = draft.clone();
+                                    Callback::from(move |e: InputEvent| {
+                                        let value = input_value(&e);
+                                        update(&draft, |definition| definition.holdings[i].ticker = value);
+                                    })
+                                };
+                                let on_holding_name = {
+                                    let draft = draft.clone();
+                                    Callback::from(move |e: InputEvent| {
+                                        let value = input_value(&e);
+                                        update(&draft, |definition| definition.holdings[i].name = value);
+                                    })
+                                };
+                                let on_allocation = {
+                                    let draft = draft.clone();
+                                    Callback::from(move |e: InputEvent| {
+                                        let value = input_value(&e);
+                                        let allocation = if value.trim().is_empty() { Some(0.0) } else { value.trim().parse::<f64>().ok() };
+                                        if let Some(allocation) = allocation {
+                                            update(&draft, |definition| definition.holdings[i].allocation = allocation);
+                                        }
+                                    })
+                                };
+                                let on_color = {
+                                    let draft = draft.clone();
+                                    Callback::from(move |e: InputEvent| {
+                                        let value = input_value(&e);
+                                        update(&draft, |definition| definition.holdings[i].color = Some(value));
+                                    })
+                                };
+                                let on_remove = {
+                                    let draft = draft.clone();
+                                    Callback::from(move |_| update(&draft, |definition| {
+                                        definition.holdings.remove(i);
+                                    }))
+                                };
+                                html! {
+                                    <tr>
+                                        <td><input type="text" value={holding.ticker.clone()} oninput={on_ticker} /></td>
+                                        <td><input type="text" value={holding.name.clone()} oninput={on_holding_name} /></td>
+                                        <td>
+                                            <input
+                                                type="number"
+                                                class="number"
+                                                min="0"
+                                                max="100"
+                                                step="any"
+                                                value={if holding.allocation == 0.0 { String::new() } else { holding.allocation.to_string() }}
+                                                oninput={on_allocation}
+                                            />
+                                        </td>
+                                        <td>
+                                            <input
+                                                type="color"
+                                                value={holding.color.clone().unwrap_or_else(|| DEFAULT_COLOR.to_string())}
+                                                oninput={on_color}
+                                            />
+                                        </td>
+                                        <td><button type="button" onclick={on_remove}>{"Remove"}</button></td>
+                                    </tr>
+                                }
+                            })}
+                            <tr>
+                                <td colspan="2">{"Total"}</td>
+                                <td class={classes!("number", ((total - 100.0).abs() > 0.1).then_some("off-total"))}>
+                                    {format!("{:.2}%", total)}
+                                </td>
+                                <td colspan="2"></td>
+                            </tr>
+                        </table>
+                        <div class="toolbar">
+                            <button type="button" onclick={on_add_holding} disabled={definition.holdings.len() >= MAX_HOLDINGS}>
+                                {"Add holding"}
+                            </button>
+                        </div>
+
+                        <h2>{"Strategy"}</h2>
+                        <label class="field">
+                            <span>{"Overview"}</span>
+                            <textarea rows="5" value={definition.strategy.overview.clone()} oninput={on_overview} />
+                        </label>
+                        {list_editor("Assets", &draft, |strategy| &mut strategy.assets)}
+                        {list_editor("Why it works", &draft, |strategy| &mut strategy.why_it_works)}
+                        {list_editor("Best suited for", &draft, |strategy| &mut strategy.best_suited)}
+                        {list_editor("Effective during", &draft, |strategy| &mut strategy.effective_during)}
+
+                        {if errors.is_empty() {
+                            html! {}
+                        } else {
+                            html! {
+                                <div class="issues">
+                                    {"Fix these before saving:"}
+                                    <ul>
+                                        {for errors.iter().map(|error| html! { <li>{error}</li> })}
+                                    </ul>
+                                </div>
+                            }
+                        }}
+
+                        <div class="toolbar">
+                            <button type="submit" disabled={*is_busy || !errors.is_empty()}>{"Save"}</button>
+                            {if props.id.is_some() {
+                                html! {
+                                    <button type="button" class="danger" onclick={on_delete} disabled={*is_busy}>
+                                        {if *confirm_delete { "Click again to delete" } else { "Delete fund" }}
+                                    </button>
+                                }
+                            } else {
+                                html! {}
+                            }}
+                        </div>
+                    </form>
+                }
+            } else if let Some(deleted) = versions.as_ref().and_then(|versions| versions.versions.last()).filter(|latest| latest.change == FundChange::Deleted) {
+                html! {
+                    <div class="issues">
+                        {format!(
+                            "{} deleted this fund on {}. Restore a version below to bring it back.",
+                            deleted.saved_by,
+                            deleted.saved_at.format("%Y-%m-%d %H:%M UTC")
+                        )}
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {if let Some(response) = versions.as_ref() {
+                html! {
+                    <>
+                        <h2>{"Versions"}</h2>
+                        {if response.versions.is_empty() {
+                            html! { <div class="empty">{"No saved versions yet. The first save also keeps the file as it is now."}</div> }
+                        } else {
+                            let latest = response.latest_version();
+                            let options = |selected: Option<u32>| html! {
+                                {for response.versions.iter().rev().map(|saved| html! {
+                                    <option value={saved.version.to_string()} selected={Some(saved.version) == selected}>
+                                        {format!("Version {}", saved.version)}
+                                    </option>
+                                })}
+                            };
+                            html! {
+                                <>
+                                    <table>
+                                        <tr>
+                                            <th>{"Version"}</th>
+                                            <th>{"Change"}</th>
+                                            <th>{"Saved by"}</th>
+                                            <th>{"Saved at"}</th>
+                                            <th></th>
+                                        </tr>
+                                        {for response.versions.iter().rev().map(|saved| {
+                                            let version = saved.version;
+                                            let show_diff = show_diff.clone();
+                                            let restore = restore.clone();
+                                            // Restoring the latest version only makes sense once it is deleted
+                                            let can_restore = Some(version) != latest || response.current.is_none();
+                                            html! {
+                                                <tr key={version}>
+                                                    <td>{version}</td>
+                                                    <td>{saved.change.label()}</td>
+                                                    <td>{&saved.saved_by}</td>
+                                                    <td>{saved.saved_at.format("%Y-%m-%d %H:%M UTC").to_string()}</td>
+                                                    <td>
+                                                        {if version > 1 {
+                                                            html! {
+                                                                <button onclick={Callback::from(move |_| show_diff.emit(FundDiffQuery { from: version - 1, to: version }))}>
+                                                                    {"Changes"}
+                                                                </button>
+                                                            }
+                                                        } else {
+                                                            html! {}
+                                                        }}
+                                                        {" "}
+                                                        {if can_restore && saved.change != FundChange::Deleted {
+                                                            html! {
+                                                                <button onclick={Callback::from(move |_| restore.emit(version))} disabled={*is_busy}>
+                                                                    {"Restore"}
+                                                                </button>
+                                                            }
+                                                        } else {
+                                                            html! {}
+                                                        }}
+                                                    </td>
+                                                </tr>
+                                            }
+                                        })}
+                                    </table>
+
+                                    <form class="toolbar" onsubmit={on_compare}>
+                                        {"Compare"}
+                                        <select ref={from_ref}>{options(latest.map(|latest| latest.saturating_sub(1).max(1)))}</select>
+                                        {"with"}
+                                        <select ref={to_ref}>{options(latest)}</select>
+                                        <button type="submit">{"Show changes"}</button>
+                                    </form>
+                                </>
+                            }
+                        }}
+
+                        {if let Some(shown) = diff.as_ref() {
+                            html! {
+                                <>
+                                    <h2>{format!("Changes from version {} to {}", shown.from, shown.to)}</h2>
+                                    {if shown.changes.is_empty() {
+                                        html! { <div class="empty">{"The two versions are the same."}</div> }
+                                    } else {
+                                        html! {
+                                            <table>
+                                                <tr>
+                                                    <th>{"Field"}</th>
+                                                    <th>{format!("Version {}", shown.from)}</th>
+                                                    <th>{format!("Version {}", shown.to)}</th>
+                                                </tr>
+                                                {for shown.changes.iter().map(|change| html! {
+                                                    <tr>
+                                                        <td>{&change.field}</td>
+                                                        <td class="removed">{change.before.clone().unwrap_or_default()}</td>
+                                                        <td class="added">{change.after.clone().unwrap_or_default()}</td>
+                                                    </tr>
+                                                })}
+                                            </table>
+                                        }
+                                    }}
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }}
+                    </>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,226 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use crate::{Route, services::funds::use_fund_service, session::use_session, types::{DeletedFund, ErrorCode, FundSummary, Role}};
+use stylist::yew::styled_component;
+
+fn years(fund: &FundSummary) -> String {
+    match (fund.first_year, fund.last_year) {
+        (Some(first), Some(last)) if first == last => first.to_string(),
+        (Some(first), Some(last)) => format!("{}–{}", first, last),
+        _ => "—".to_string(),
+    }
+}
+
+/// The All Weather funds, each linking to its own page.
+#[styled_component(Funds)]
+pub fn funds() -> Html {
+    let navigator = use_navigator().unwrap();
+    let fund_service = use_fund_service();
+    let session = use_session();
+    let funds = use_state(|| None::<Vec<FundSummary>>);
+    let deleted = use_state(Vec::<DeletedFund>::new);
+    let error_message = use_state(|| None::<String>);
+    let is_admin = session.user().and_then(|user| user.role).is_some_and(|role| role.includes(Role::Admin));
+
+    {
+        let funds = funds.clone();
+        let deleted = deleted.clone();
+        let session = session.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with(is_admin, move |&is_admin| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.funds().await {
+                    Ok(response) => funds.set(Some(response)),
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                // Only admins can bring deleted funds back
+                if is_admin {
+                    match fund_service.deleted_funds().await {
+                        Ok(response) => deleted.set(response),
+                        Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                        Err(error) => error_message.set(Some(error.message)),
+                    }
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_back = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Dashboard))
+    };
+
+    let on_compare = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::FundComparison))
+    };
+
+    let on_new = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::FundNew))
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+
+        .toolbar {
+            display: flex;
+            gap: 0.5rem;
+            margin-bottom: 1.5rem;
+        }
+
+        h2 {
+            font-size: 1rem;
+            font-weight: 400;
+            margin-top: 2rem;
+        }
+
+        .error-message {
+            color: #fc8181;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        table {
+            width: 100%;
+            border-collapse: collapse;
+            font-size: 0.8rem;
+        }
+
+        th, td {
+            text-align: left;
+            padding: 0.5rem;
+            border-bottom: 1px solid rgba(96, 96, 96, 0.3);
+        }
+
+        th {
+            font-weight: 400;
+            color: #808080;
+        }
+
+        tr.fund-row {
+            cursor: pointer;
+        }
+
+        tr.fund-row:hover td {
+            background: rgba(96, 96, 96, 0.15);
+            color: #ffffff;
+        }
+
+        .issues {
+            color: #fbd38d;
+        }
+
+        .empty {
+            font-size: 0.8rem;
+            color: #606060;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Dashboard"}</button>
+            <h1>{"All Weather funds"}</h1>
+            <div class="toolbar">
+                <button onclick={on_compare}>{"Compare funds"}</button>
+                {if is_admin {
+                    html! { <button onclick={on_new}>{"New fund"}</button> }
+                } else {
+                    html! {}
+                }}
+            </div>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {match funds.as_ref() {
+                Some(list) if !list.is_empty() => html! {
+                    <table>
+                        <tr>
+                            <th>{"Fund"}</th>
+                            <th>{"Name"}</th>
+                            <th>{"Holdings"}</th>
+                            <th>{"Years"}</th>
+                            <th>{"Data issues"}</th>
+                        </tr>
+                        {for list.iter().map(|fund| {
+                            let navigator = navigator.clone();
+                            let id = fund.number;
+                            html! {
+                                <tr key={fund.number} class="fund-row" onclick={Callback::from(move |_| navigator.push(&Route::Fund { id }))}>
+                                    <td>{fund.number}</td>
+                                    <td>{&fund.name}</td>
+                                    <td>{fund.holdings}</td>
+                                    <td>{years(fund)}</td>
+                                    <td class={classes!((!fund.issues.is_empty()).then_some("issues"))}>
+                                        {if fund.issues.is_empty() { "None".to_string() } else { fund.issues.len().to_string() }}
+                                    </td>
+                                </tr>
+                            }
+                        })}
+                    </table>
+                },
+                Some(_) => html! { <div class="empty">{"No funds are available."}</div> },
+                None => html! {},
+            }}
+            
+            {if deleted.is_empty() {
+                html! {}
+            } else {
+                html! {
+                    <>
+                        <h2>{"Deleted funds"}</h2>
+                        <table>
+                            <tr>
+                                <th>{"Fund"}</th>
+                                <th>{"Name"}</th>
+                                <th>{"Deleted by"}</th>
+                                <th>{"Deleted at"}</th>
+                            </tr>
+                            {for deleted.iter().map(|fund| {
+                                let navigator = navigator.clone();
+                                let id = fund.number;
+                                html! {
+                                    <tr key={fund.number} class="fund-row" onclick={Callback::from(move |_| navigator.push(&Route::FundEdit { id }))}>
+                                        <td>{fund.number}</td>
+                                        <td>{&fund.name}</td>
+                                        <td>{&fund.deleted_by}</td>
+                                        <td>{fund.deleted_at.format("%Y-%m-%d %H:%M UTC").to_string()}</td>
+                                    </tr>
+                                }
+                            })}
+                        </table>
+                    </>
+                }
+            }}
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+use yew_router::prelude::*;
+use crate::{Route, session::use_session, types::Role};
+
+/// Who may see a route.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Access {
+    /// Only signed-out visitors; signed-in users are sent on to the app.
+    Guest,
+    SignedIn,
+    Role(Role),
+}
+
+/// Query string carried by the login page while a protected route waits.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReturnTo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_to: Option<String>,
+}
+
+impl ReturnTo {
+    /// Where to go after signing in. Only paths of this app are followed,
+    /// so the parameter cannot send anyone off-site.
+    pub fn route(&self) -> Route {
+        self.return_to
+            .as_deref()
+            .and_then(Route::recognize)
+            .filter(|route| *route != Route::Login)
+            .unwrap_or(Route::Dashboard)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Redirect {
+    Login(ReturnTo),
+    To(Route),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RouteGuardProps {
+    pub access: Access,
+    pub children: Html,
+}
+
+/// Renders its children only when the session satisfies `access`, and
+/// redirects otherwise.
+#[function_component(RouteGuard)]
+pub fn route_guard(props: &RouteGuardProps) -> Html {
+    let session = use_session();
+    let navigator = use_navigator().unwrap();
+    let location = use_location().unwrap();
+
+    let redirect = match session.user() {
+        None => None,
+        Some(user) => match props.access {
+            Access::Guest if user.logged_in => {
+                Some(Redirect::To(location.query::<ReturnTo>().unwrap_or_default().route()))
+            }
+            Access::Guest => None,
+            _ if !user.logged_in => Some(Redirect::Login(ReturnTo {
+                return_to: Some(location.path().to_string()),
+            })),
+            Access::Role(required) if !user.role.is_some_and(|role| role.includes(required)) => {
+                Some(Redirect::To(Route::Dashboard))
+            }
+            _ => None,
+        },
+    };
+
+    {
+        let redirect = redirect.clone();
+        
+        use_effect_with(redirect, move |redirect| {
+            match redirect {
+                Some(Redirect::Login(query)) => {
+                    let _ = navigator.replace_with_query(&Route::Login, query);
+                }
+                Some(Redirect::To(route)) => navigator.replace(route),
+                None => {}
+            }
+            || ()
+        });
+    }
+
+    if !session.is_checked() {
+        return html! { <div class="loading"></div> };
+    }
+
+    match redirect {
+        Some(_) => html! {},
+        None => props.children.clone(),
+    }
+}
\ No newline at end of file
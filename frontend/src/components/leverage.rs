@@ -0,0 +1,292 @@
+use yew::prelude::*;
+use web_sys::HtmlSelectElement;
+use crate::{components::{fund_comparison::growth_chart, fund_detail::{drawdown_table, PALETTE}}, services::funds::use_fund_service, session::use_session, types::{format_month, ErrorCode, LeverageQuery, LeverageRebalance, LeverageResponse, LEVERAGE_OPTIONS}};
+use stylist::yew::styled_component;
+
+// Leveraged drawdowns listed under the chart
+const DRAWDOWNS_SHOWN: usize = 5;
+
+fn percent(value: f64) -> String {
+    format!("{:.2}%", value)
+}
+
+fn ratio(value: Option<f64>) -> String {
+    value.map(|value| format!("{:.2}", value)).unwrap_or_else(|| "—".to_string())
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LeveragePanelProps {
+    pub id: u32,
+}
+
+/// The fund at 1.5x, 2x or 3x, borrowing at the fed funds rate, next to
+/// the fund itself.
+#[styled_component(LeveragePanel)]
+pub fn leverage_panel(props: &LeveragePanelProps) -> Html {
+    let fund_service = use_fund_service();
+    let session = use_session();
+    let leverage = use_state(|| 0usize);
+    let rebalance = use_state(LeverageRebalance::default);
+    let response = use_state(|| None::<LeverageResponse>);
+    let error_message = use_state(|| None::<String>);
+    let leverage_select = use_node_ref();
+    let rebalance_select = use_node_ref();
+
+    {
+        let session = session.clone();
+        let response = response.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with((props.id, *leverage, *rebalance), move |&(id, leverage, rebalance)| {
+            let query = LeverageQuery {
+                leverage: LEVERAGE_OPTIONS[leverage],
+                rebalance,
+            };
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.leverage(id, &query).await {
+                    Ok(result) => {
+                        response.set(Some(result));
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => {
+                        response.set(None);
+                        error_message.set(Some(error.message));
+                    }
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_leverage_change = {
+        let leverage = leverage.clone();
+        let leverage_select = leverage_select.clone();
+        Callback::from(move |_: Event| {
+            let Some(select) = leverage_select.cast::<HtmlSelectElement>() else {
+                return;
+            };
+            if let Ok(index) = select.value().parse() {
+                leverage.set(index);
+            }
+        })
+    };
+
+    let on_rebalance_change = {
+        let rebalance = rebalance.clone();
+        let rebalance_select = rebalance_select.clone();
+        Callback::from(move |_: Event| {
+            let Some(select) = rebalance_select.cast::<HtmlSelectElement>() else {
+                return;
+            };
+            let label = select.value();
+            if let Some(option) = LeverageRebalance::ALL.into_iter().find(|option| option.label() == label) {
+                rebalance.set(option);
+            }
+        })
+    };
+
+    let css = css!(
+        r#"
+        padding: 1rem;
+        border: 1px solid rgba(96, 96, 96, 0.3);
+        border-radius: 4px;
+        background: rgba(96, 96, 96, 0.05);
+
+        .leverage-header {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.75rem;
+            align-items: center;
+            margin-bottom: 0.75rem;
+            font-size: 0.8rem;
+        }
+
+        .leverage-title {
+            flex: 1;
+            font-size: 0.9rem;
+            color: rgba(255, 255, 255, 0.9);
+        }
+
+        .legend {
+            display: flex;
+            gap: 1rem;
+            font-size: 0.8rem;
+            margin-bottom: 0.5rem;
+        }
+
+        .swatch {
+            display: inline-block;
+            width: 0.7rem;
+            height: 0.7rem;
+            margin-right: 0.4rem;
+            border-radius: 2px;
+        }
+
+        .axis-label {
+            fill: #808080;
+            font-size: 11px;
+        }
+
+        .grid-line {
+            stroke: rgba(96, 96, 96, 0.3);
+            stroke-width: 1;
+        }
+
+        .series-line {
+            fill: none;
+            stroke-width: 1.5;
+        }
+
+        .notice {
+            color: #fbd38d;
+            font-size: 0.75rem;
+            margin-bottom: 0.5rem;
+        }
+
+        .note {
+            font-size: 0.75rem;
+            color: #808080;
+            margin-top: 0.5rem;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <div class="leverage-header">
+                <div class="leverage-title">{"Borrowing at the fed funds rate"}</div>
+                <label>
+                    {"Leverage "}
+                    <select ref={leverage_select} onchange={on_leverage_change}>
+                        {for LEVERAGE_OPTIONS.iter().enumerate().map(|(i, option)| html! {
+                            <option value={i.to_string()} selected={i == *leverage}>{format!("{}x", option)}</option>
+                        })}
+                    </select>
+                </label>
+                <label>
+                    {"Rebalance "}
+                    <select ref={rebalance_select} onchange={on_rebalance_change}>
+                        {for LeverageRebalance::ALL.into_iter().map(|option| html! {
+                            <option value={option.label()} selected={option == *rebalance}>{option.label()}</option>
+                        })}
+                    </select>
+                </label>
+            </div>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(LeverageResponse { result, stale }) = response.as_ref() {
+                let first = result.months.first().map(|month| month.month);
+                let last = result.months.last().map(|month| month.month);
+                let end_balance = |balance: Option<f64>| balance.map(|balance| format!("${:.0}", balance)).unwrap_or_default();
+                html! {
+                    <>
+                        {if *stale {
+                            html! { <div class="notice">{"FRED could not be reached; borrowing rates are from cached data."}</div> }
+                        } else {
+                            html! {}
+                        }}
+                        {if let Some(month) = result.wiped_out {
+                            html! { <div class="notice">{format!("The leveraged balance was wiped out in {}.", format_month(month))}</div> }
+                        } else {
+                            html! {}
+                        }}
+                        
+                        <div class="legend">
+                            <span><span class="swatch" style={format!("background: {}", PALETTE[0])}></span>{"Fund"}</span>
+                            <span><span class="swatch" style={format!("background: {}", PALETTE[3])}></span>{format!("{}x leveraged", result.leverage)}</span>
+                        </div>
+                        {if let (Some(first), Some(last)) = (first, last) {
+                            growth_chart(
+                                &[
+                                    (PALETTE[0], result.months.iter().map(|month| month.fund_balance).collect()),
+                                    (PALETTE[3], result.months.iter().map(|month| month.leveraged_balance).collect()),
+                                ],
+                                first,
+                                last,
+                            )
+                        } else {
+                            html! {}
+                        }}
+                        
+                        <table>
+                            <tr>
+                                <th>{"Metric"}</th>
+                                <th class="number">{"Fund"}</th>
+                                <th class="number">{"Leveraged"}</th>
+                            </tr>
+                            <tr>
+                                <td>{"End Balance"}</td>
+                                <td class="number">{end_balance(result.months.last().map(|month| month.fund_balance))}</td>
+                                <td class="number">{end_balance(result.months.last().map(|month| month.leveraged_balance))}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Annualized Return (CAGR)"}</td>
+                                <td class="number">{percent(result.fund.cagr)}</td>
+                                <td class="number">{percent(result.leveraged.cagr)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Volatility"}</td>
+                                <td class="number">{percent(result.fund.volatility)}</td>
+                                <td class="number">{percent(result.leveraged.volatility)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Maximum Drawdown"}</td>
+                                <td class="number">{percent(result.fund.max_drawdown)}</td>
+                                <td class="number">{percent(result.leveraged.max_drawdown)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Worst Year"}</td>
+                                <td class="number">{format!("{} ({})", percent(result.fund.worst_year.1), result.fund.worst_year.0)}</td>
+                                <td class="number">{format!("{} ({})", percent(result.leveraged.worst_year.1), result.leveraged.worst_year.0)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Sharpe Ratio"}</td>
+                                <td class="number">{ratio(result.fund.sharpe)}</td>
+                                <td class="number">{ratio(result.leveraged.sharpe)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Sortino Ratio"}</td>
+                                <td class="number">{ratio(result.fund.sortino)}</td>
+                                <td class="number">{ratio(result.leveraged.sortino)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Average borrowing rate"}</td>
+                                <td class="number"></td>
+                                <td class="number">{percent(result.average_borrow_rate)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"CAGR from leverage alone"}</td>
+                                <td class="number"></td>
+                                <td class="number">{percent(result.expected_cagr)}</td>
+                            </tr>
+                            <tr>
+                                <td>{"Volatility drag"}</td>
+                                <td class="number"></td>
+                                <td class="number">{percent(result.volatility_drag)}</td>
+                            </tr>
+                        </table>
+                        
+                        {if result.drawdowns.is_empty() {
+                            html! {}
+                        } else {
+                            drawdown_table(&result.drawdowns[..result.drawdowns.len().min(DRAWDOWNS_SHOWN)])
+                        }}
+                        
+                        <div class="note">
+                            {"Borrowing costs the fed funds rate on the amount above 1x. The fund files only have monthly returns, so daily rebalancing spreads each month's return evenly over its trading days."}
+                        </div>
+                    </>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
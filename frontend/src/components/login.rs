@@ -1,34 +1,45 @@
 use yew::prelude::*;
-use yew_router::prelude::*;
 use web_sys::HtmlInputElement;
 use wasm_bindgen::JsCast;
 use gloo_timers::callback::Timeout;
-use crate::{Route, services::auth::AuthService};
+use crate::{services::auth::use_auth_service, session::use_session, types::{ErrorCode, UserInfo}};
 use stylist::yew::styled_component;
 
 #[styled_component(Login)]
 pub fn login() -> Html {
-    let navigator = use_navigator().unwrap();
-    let auth_service = AuthService::new();
+    let session = use_session();
+    let auth_service = use_auth_service();
     
     let username_ref = use_node_ref();
     let password_ref = use_node_ref();
     let error_message = use_state(|| None::<String>);
     let is_loading = use_state(|| false);
     let is_animating = use_state(|| false);
+    let is_throttled = use_state(|| false);
+    // Set once the password is accepted for an account with two-factor enabled
+    let code_ref = use_node_ref();
+    let two_factor_token = use_state(|| None::<String>);
     
     let onsubmit = {
         let username_ref = username_ref.clone();
         let password_ref = password_ref.clone();
+        let code_ref = code_ref.clone();
+        let two_factor_token = two_factor_token.clone();
         let error_message = error_message.clone();
         let is_loading = is_loading.clone();
         let is_animating = is_animating.clone();
-        let navigator = navigator.clone();
+        let is_throttled = is_throttled.clone();
+        let session = session.clone();
         let auth_service = auth_service.clone();
         
         Callback::from(move |e: SubmitEvent| {
             e.prevent_default();
             
+            let code = code_ref
+                .cast::<HtmlInputElement>()
+                .map(|input| input.value().trim().to_string())
+                .unwrap_or_default();
+            
             let username = username_ref
                 .cast::<HtmlInputElement>()
                 .map(|input| input.value().trim().to_string())
@@ -39,7 +50,12 @@ pub fn login() -> Html {
                 .map(|input| input.value())
                 .unwrap_or_default();
             
-            if username.is_empty() || password.is_empty() {
+            let pending_token = (*two_factor_token).clone();
+            let missing = match pending_token {
+                Some(_) => code.is_empty(),
+                None => username.is_empty() || password.is_empty(),
+            };
+            if missing {
                 error_message.set(Some("Please fill in all fields".to_string()));
                 return;
             }
@@ -52,44 +68,87 @@ pub fn login() -> Html {
             let error_message = error_message.clone();
             let is_loading = is_loading.clone();
             let is_animating = is_animating.clone();
-            let navigator = navigator.clone();
+            let is_throttled = is_throttled.clone();
+            let session = session.clone();
             let auth_service = auth_service.clone();
             let password_ref = password_ref.clone();
+            let code_ref = code_ref.clone();
+            let two_factor_token = two_factor_token.clone();
             
             wasm_bindgen_futures::spawn_local(async move {
-                match auth_service.login(username, password).await {
+                let result = match pending_token {
+                    Some(token) => auth_service.login_two_factor(token, code).await,
+                    None => auth_service.login(username, password).await,
+                };
+                
+                match result {
                     Ok(response) => {
                         is_loading.set(false);
-                        if response.success {
+                        if let Some(token) = response.two_factor_token {
+                            // Password accepted, ask for the authenticator code
+                            two_factor_token.set(Some(token));
+                        } else {
                             // Start login animation
                             is_animating.set(true);
+                            let user = auth_service.check_auth().await.unwrap_or_else(|_| UserInfo::logged_out());
                             
-                            // After animation completes, navigate to dashboard
-                            let navigator = navigator.clone();
+                            // After animation completes, the route guard moves on to
+                            // the dashboard, or wherever the user was headed
+                            let session = session.clone();
+                            let is_animating = is_animating.clone();
+                            let error_message = error_message.clone();
                             Timeout::new(4000, move || {
-                                navigator.push(&Route::Dashboard);
+                                if !user.logged_in {
+                                    is_animating.set(false);
+                                    error_message.set(Some("Could not start your session, please try again".to_string()));
+                                }
+                                session.set_user(user);
                             }).forget();
-                        } else {
-                            error_message.set(Some(response.message));
-                            // Clear password field
-                            if let Some(input) = password_ref.cast::<HtmlInputElement>() {
-                                input.set_value("");
-                            }
                         }
                     }
-                    Err(_) => {
+                    Err(error) => {
                         is_loading.set(false);
-                        error_message.set(Some("Network error. Please try again.".to_string()));
-                        // Clear password field
+                        match error.code {
+                            // Keep the form disabled until the server will accept another attempt
+                            ErrorCode::LockedOut => {
+                                is_throttled.set(true);
+                                let is_throttled = is_throttled.clone();
+                                let error_message = error_message.clone();
+                                let retry_after = error.retry_after_secs.unwrap_or(1);
+                                let delay_ms = u32::try_from(retry_after.saturating_mul(1000)).unwrap_or(u32::MAX);
+                                Timeout::new(delay_ms, move || {
+                                    is_throttled.set(false);
+                                    error_message.set(None);
+                                }).forget();
+                            }
+                            // The pending second step is gone, start over from the password
+                            ErrorCode::TwoFactorExpired => two_factor_token.set(None),
+                            _ => {}
+                        }
+                        error_message.set(Some(error.message));
+                        // Clear password and code fields
                         if let Some(input) = password_ref.cast::<HtmlInputElement>() {
                             input.set_value("");
                         }
+                        if let Some(input) = code_ref.cast::<HtmlInputElement>() {
+                            input.set_value("");
+                        }
                     }
                 }
             });
         })
     };
 
+    let on_back = {
+        let two_factor_token = two_factor_token.clone();
+        let error_message = error_message.clone();
+        
+        Callback::from(move |_| {
+            two_factor_token.set(None);
+            error_message.set(None);
+        })
+    };
+
     let css = css!(
         r#"
         body {
@@ -245,6 +304,17 @@ pub fn login() -> Html {
             cursor: not-allowed;
         }
 
+        .back-link {
+            display: block;
+            width: 100%;
+            margin-top: 0.75rem;
+            background: none;
+            border: none;
+            color: #667eea;
+            font-size: 0.9rem;
+            cursor: pointer;
+        }
+
         .error-message {
             background: #fed7d7;
             color: #c53030;
@@ -282,31 +352,54 @@ pub fn login() -> Html {
                 <div class="login-form">
                     <h2>{"Portal Access"}</h2>
                     <form {onsubmit}>
-                        <div class="input-group">
-                            <label for="username">{"Key"}</label>
-                            <input 
-                                type="text" 
-                                id="username" 
-                                name="username" 
-                                ref={username_ref}
-                                required=true
-                                disabled={*is_loading}
-                            />
-                        </div>
+                        {if two_factor_token.is_some() {
+                            html! {
+                                <div class="input-group">
+                                    <label for="code">{"Authentication code"}</label>
+                                    <input 
+                                        type="text" 
+                                        id="code" 
+                                        name="code" 
+                                        ref={code_ref}
+                                        inputmode="numeric"
+                                        autocomplete="one-time-code"
+                                        placeholder="123456 or a recovery code"
+                                        required=true
+                                        disabled={*is_loading}
+                                    />
+                                </div>
+                            }
+                        } else {
+                            html! {
+                                <>
+                                    <div class="input-group">
+                                        <label for="username">{"Key"}</label>
+                                        <input 
+                                            type="text" 
+                                            id="username" 
+                                            name="username" 
+                                            ref={username_ref}
+                                            required=true
+                                            disabled={*is_loading}
+                                        />
+                                    </div>
                         
-                        <div class="input-group">
-                            <label for="password">{"Password"}</label>
-                            <input 
-                                type="password" 
-                                id="password" 
-                                name="password" 
-                                ref={password_ref}
-                                required=true
-                                disabled={*is_loading}
-                            />
-                        </div>
+                                    <div class="input-group">
+                                        <label for="password">{"Password"}</label>
+                                        <input 
+                                            type="password" 
+                                            id="password" 
+                                            name="password" 
+                                            ref={password_ref}
+                                            required=true
+                                            disabled={*is_loading}
+                                        />
+                                    </div>
+                                </>
+                            }
+                        }}
                         
-                        <button type="submit" class="login-btn" disabled={*is_loading}>
+                        <button type="submit" class="login-btn" disabled={*is_loading || *is_throttled}>
                             {if *is_loading {
                                 html! {
                                     <>
@@ -315,9 +408,19 @@ pub fn login() -> Html {
                                     </>
                                 }
                             } else {
-                                html! { "Login" }
+                                html! { {if two_factor_token.is_some() { "Verify" } else { "Login" }} }
                             }}
                         </button>
+                        
+                        {if two_factor_token.is_some() {
+                            html! {
+                                <button type="button" class="back-link" onclick={on_back}>
+                                    {"Use a different account"}
+                                </button>
+                            }
+                        } else {
+                            html! {}
+                        }}
                     </form>
                     
                     {if let Some(error) = error_message.as_ref() {
@@ -1,2 +1,15 @@
 pub mod login;
-pub mod dashboard; 
\ No newline at end of file
+pub mod dashboard;
+pub mod two_factor;
+pub mod audit_log;
+pub mod guard;
+pub mod risk_premiums;
+pub mod chart;
+pub mod earnings_source;
+pub mod alerts;
+pub mod funds;
+pub mod fund_detail;
+pub mod risk_metrics;
+pub mod fund_comparison;
+pub mod leverage;
+pub mod fund_editor;
\ No newline at end of file
@@ -0,0 +1,157 @@
+use yew::prelude::*;
+use web_sys::HtmlInputElement;
+use crate::{services::funds::use_fund_service, session::use_session, types::{format_month, ErrorCode, RiskMetricsQuery, RiskMetricsResponse}};
+use stylist::yew::styled_component;
+
+#[derive(Properties, PartialEq)]
+pub struct RiskMetricsPanelProps {
+    pub id: u32,
+}
+
+/// The fund's risk table recomputed from its monthly returns, on its own or
+/// against a benchmark ticker from the returns dataset.
+#[styled_component(RiskMetricsPanel)]
+pub fn risk_metrics_panel(props: &RiskMetricsPanelProps) -> Html {
+    let fund_service = use_fund_service();
+    let session = use_session();
+    let query = use_state(|| RiskMetricsQuery { benchmark: None, risk_free: 0.0 });
+    let response = use_state(|| None::<RiskMetricsResponse>);
+    let error_message = use_state(|| None::<String>);
+    let benchmark_ref = use_node_ref();
+    let risk_free_ref = use_node_ref();
+
+    {
+        let session = session.clone();
+        let response = response.clone();
+        let error_message = error_message.clone();
+        
+        use_effect_with((props.id, (*query).clone()), move |(id, query)| {
+            let (id, query) = (*id, query.clone());
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match fund_service.metrics(id, &query).await {
+                    Ok(result) => {
+                        response.set(Some(result));
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => {
+                        response.set(None);
+                        error_message.set(Some(error.message));
+                    }
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_submit = {
+        let query = query.clone();
+        let error_message = error_message.clone();
+        let benchmark_ref = benchmark_ref.clone();
+        let risk_free_ref = risk_free_ref.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            
+            let input = |node: &NodeRef| node.cast::<HtmlInputElement>().map(|input| input.value()).unwrap_or_default();
+            let benchmark = input(&benchmark_ref).trim().to_string();
+            let risk_free = input(&risk_free_ref).trim().to_string();
+            let risk_free = if risk_free.is_empty() { Some(0.0) } else { risk_free.parse::<f64>().ok() };
+            let Some(risk_free) = risk_free else {
+                error_message.set(Some("Enter the risk-free rate as a number".to_string()));
+                return;
+            };
+            query.set(RiskMetricsQuery {
+                benchmark: (!benchmark.is_empty()).then_some(benchmark),
+                risk_free,
+            });
+        })
+    };
+
+    let css = css!(
+        r#"
+        padding: 1rem;
+        border: 1px solid rgba(96, 96, 96, 0.3);
+        border-radius: 4px;
+        background: rgba(96, 96, 96, 0.05);
+
+        .metrics-form {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 0.75rem;
+            align-items: center;
+            margin-bottom: 0.75rem;
+            font-size: 0.8rem;
+        }
+
+        input[type="text"], input[type="number"] {
+            background: #000000;
+            color: rgba(255, 255, 255, 0.9);
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 6px;
+            padding: 0.3rem;
+            width: 7rem;
+        }
+
+        .note {
+            font-size: 0.75rem;
+            color: #808080;
+            margin-top: 0.5rem;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <form class="metrics-form" onsubmit={on_submit}>
+                <input ref={benchmark_ref} type="text" placeholder="Benchmark ticker" />
+                <input ref={risk_free_ref} type="number" step="any" placeholder="Risk-free %" />
+                <button type="submit">{"Recompute"}</button>
+            </form>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {if let Some(result) = response.as_ref() {
+                let has_benchmark = result.benchmark.is_some();
+                html! {
+                    <>
+                        <table>
+                            <tr>
+                                <th>{"Metric"}</th>
+                                <th class="number">{"Fund"}</th>
+                                {if let Some(benchmark) = &result.benchmark {
+                                    html! { <th class="number">{benchmark}</th> }
+                                } else {
+                                    html! {}
+                                }}
+                            </tr>
+                            {for result.metrics.iter().map(|row| html! {
+                                <tr>
+                                    <td>{&row.metric}</td>
+                                    <td class="number">{row.fund.format()}</td>
+                                    {if has_benchmark {
+                                        html! { <td class="number">{row.benchmark.format()}</td> }
+                                    } else {
+                                        html! {}
+                                    }}
+                                </tr>
+                            })}
+                        </table>
+                        {if let (Some(first), Some(last)) = (result.first_month, result.last_month) {
+                            html! { <div class="note">{format!("From {} to {}, risk-free rate {:.2}%.", format_month(first), format_month(last), result.risk_free)}</div> }
+                        } else {
+                            html! {}
+                        }}
+                    </>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
\ No newline at end of file
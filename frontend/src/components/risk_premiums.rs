@@ -0,0 +1,406 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use chrono::{DateTime, Utc};
+use web_sys::HtmlSelectElement;
+use crate::{Route, components::{chart::{ChartSource, HistoryChart}, earnings_source::EarningsSourcePanel}, services::market::use_market_service, session::use_session, types::{ASSUMED_PE, ErrorCode, FredSeries, Metric, MetricId, RiskPremiumsResponse, Signal, Unit}};
+use stylist::yew::styled_component;
+
+fn format_value(metric: &Metric) -> String {
+    match (metric.value, metric.id.unit()) {
+        (Some(value), Unit::Percent) => format!("{:.2}%", value),
+        (Some(value), Unit::Index) => format!("{:.2}", value),
+        (None, _) => "--".to_string(),
+    }
+}
+
+fn signal_class(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Favourable => "signal-favourable",
+        Signal::Neutral => "signal-neutral",
+        Signal::Warning => "signal-warning",
+        Signal::Crisis => "signal-crisis",
+    }
+}
+
+// Derived metrics first, then the FRED series they come from
+fn chart_sources() -> Vec<ChartSource> {
+    MetricId::ALL
+        .into_iter()
+        .map(ChartSource::Metric)
+        .chain(FredSeries::ALL.into_iter().map(ChartSource::Series))
+        .collect()
+}
+
+// Where the earnings behind the equity metrics came from
+fn earnings_note(response: &RiskPremiumsResponse) -> String {
+    match &response.report.earnings {
+        Some(earnings) => format!(
+            "{} {} EPS of {:.2}, as of {}",
+            earnings.source, earnings.basis.label(), earnings.eps, earnings.as_of
+        ),
+        None => format!("Assumed P/E of {}", ASSUMED_PE),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct MetricCardProps {
+    metric: Metric,
+    #[prop_or_default]
+    note: Option<String>,
+    /// The value rests on the assumed P/E rather than reported earnings
+    #[prop_or_default]
+    estimate: bool,
+}
+
+// Click a card to see the formula and the inputs behind the number
+#[function_component(MetricCard)]
+fn metric_card(props: &MetricCardProps) -> Html {
+    let expanded = use_state(|| false);
+    let metric = &props.metric;
+    let band = metric.band();
+
+    let onclick = {
+        let expanded = expanded.clone();
+        Callback::from(move |_| expanded.set(!*expanded))
+    };
+
+    html! {
+        <div class={classes!("metric-card", band.map(|band| signal_class(band.signal)))} {onclick}>
+            <div class="metric-label">
+                {metric.id.label()}
+                {if props.estimate {
+                    html! {
+                        <span class="estimate-badge" title="No earnings data is loaded, so this uses the assumed P/E">
+                            {"Estimate"}
+                        </span>
+                    }
+                } else {
+                    html! {}
+                }}
+            </div>
+            <div class="metric-value">{format_value(metric)}</div>
+            {if let Some(band) = band {
+                html! { <div class="metric-band">{band.label}</div> }
+            } else {
+                html! {}
+            }}
+            <div class="metric-description">{metric.id.description()}</div>
+            <div class="metric-as-of">
+                {metric.as_of.map(|date| format!("As of {}", date)).unwrap_or_else(|| "No data".to_string())}
+            </div>
+            {if let Some(note) = &props.note {
+                html! { <div class="metric-as-of">{format!("Earnings: {}", note)}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {if *expanded {
+                html! {
+                    <div class="metric-detail">
+                        <div class="metric-formula">{&metric.formula}</div>
+                        {for metric.inputs.iter().map(|input| html! {
+                            <div class="metric-input">
+                                {format!("{}: {:.2}", input.name, input.value)}
+                                {input.date.map(|date| format!(" ({})", date)).unwrap_or_default()}
+                            </div>
+                        })}
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
+
+#[styled_component(RiskPremiums)]
+pub fn risk_premiums() -> Html {
+    let navigator = use_navigator().unwrap();
+    let market_service = use_market_service();
+    let session = use_session();
+    let data = use_state(|| None::<RiskPremiumsResponse>);
+    let loaded_at = use_state(|| None::<DateTime<Utc>>);
+    let error_message = use_state(|| None::<String>);
+    let is_loading = use_state(|| true);
+    let chart_source = use_state(|| ChartSource::Metric(MetricId::TermPremium));
+    let chart_select = use_node_ref();
+
+    let load = {
+        let market_service = market_service.clone();
+        let session = session.clone();
+        let data = data.clone();
+        let loaded_at = loaded_at.clone();
+        let error_message = error_message.clone();
+        let is_loading = is_loading.clone();
+        
+        Callback::from(move |_: ()| {
+            let market_service = market_service.clone();
+            let session = session.clone();
+            let data = data.clone();
+            let loaded_at = loaded_at.clone();
+            let error_message = error_message.clone();
+            let is_loading = is_loading.clone();
+            
+            is_loading.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match market_service.risk_premiums().await {
+                    Ok(response) => {
+                        data.set(Some(response));
+                        loaded_at.set(Some(Utc::now()));
+                        error_message.set(None);
+                    }
+                    Err(error) if error.code == ErrorCode::SessionExpired => session.sign_out(),
+                    Err(error) => error_message.set(Some(error.message)),
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    {
+        let load = load.clone();
+        use_effect_with((), move |_| {
+            load.emit(());
+            || ()
+        });
+    }
+
+    let on_refresh = {
+        let load = load.clone();
+        Callback::from(move |_| load.emit(()))
+    };
+
+    let on_chart_change = {
+        let chart_source = chart_source.clone();
+        let chart_select = chart_select.clone();
+        Callback::from(move |_: Event| {
+            let Some(select) = chart_select.cast::<HtmlSelectElement>() else {
+                return;
+            };
+            let id = select.value();
+            if let Some(source) = chart_sources().into_iter().find(|source| source.id() == id) {
+                chart_source.set(source);
+            }
+        })
+    };
+
+    let on_back = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::Dashboard))
+    };
+
+    let css = css!(
+        r#"
+        min-height: 100vh;
+        padding: 2rem;
+        background-color: #000000;
+        color: rgba(255, 255, 255, 0.8);
+        font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        box-sizing: border-box;
+
+        h1 {
+            font-size: 1.4rem;
+            font-weight: 400;
+            letter-spacing: 0.05em;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.4rem 0.6rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+
+        .toolbar {
+            display: flex;
+            gap: 1rem;
+            align-items: center;
+            margin-bottom: 1.5rem;
+            font-size: 0.8rem;
+            color: rgba(255, 255, 255, 0.5);
+        }
+
+        .stale-notice, .error-message {
+            color: #fbd38d;
+            margin-bottom: 1rem;
+            font-size: 0.85rem;
+        }
+
+        .error-message {
+            color: #fc8181;
+        }
+
+        .metrics-grid {
+            display: grid;
+            grid-template-columns: repeat(auto-fill, minmax(260px, 1fr));
+            gap: 1rem;
+        }
+
+        .metric-card {
+            background: rgba(96, 96, 96, 0.1);
+            border: 1px solid rgba(96, 96, 96, 0.3);
+            border-radius: 4px;
+            padding: 1rem;
+            cursor: pointer;
+            transition: border-color 0.3s ease;
+        }
+
+        .metric-card:hover {
+            border-color: rgba(160, 160, 160, 0.5);
+        }
+
+        .metric-label {
+            font-size: 0.7rem;
+            letter-spacing: 0.1em;
+            color: #808080;
+            margin-bottom: 0.5rem;
+        }
+
+        .metric-value {
+            font-size: 1.6rem;
+            font-weight: 300;
+            color: #ffffff;
+        }
+
+        .estimate-badge {
+            margin-left: 0.5rem;
+            padding: 0.05rem 0.35rem;
+            border: 1px solid #f59e0b;
+            border-radius: 3px;
+            color: #f59e0b;
+            letter-spacing: 0.05em;
+        }
+
+        .metric-band {
+            font-size: 0.8rem;
+            margin: 0.25rem 0 0.5rem;
+        }
+
+        .signal-favourable .metric-value, .signal-favourable .metric-band {
+            color: #10b981;
+        }
+
+        .signal-warning .metric-value, .signal-warning .metric-band {
+            color: #f59e0b;
+        }
+
+        .signal-crisis .metric-value, .signal-crisis .metric-band {
+            color: #ef4444;
+        }
+
+        .metric-description, .metric-as-of {
+            font-size: 0.7rem;
+            color: #606060;
+        }
+
+        .metric-detail {
+            margin-top: 0.75rem;
+            padding-top: 0.75rem;
+            border-top: 1px solid rgba(96, 96, 96, 0.3);
+            font-size: 0.7rem;
+            color: #a0a0a0;
+        }
+
+        .chart-picker {
+            margin-top: 2rem;
+            font-size: 0.8rem;
+            color: rgba(255, 255, 255, 0.5);
+        }
+
+        .chart-picker select {
+            margin-left: 0.5rem;
+            background: #000000;
+            color: rgba(255, 255, 255, 0.9);
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 6px;
+            padding: 0.3rem;
+        }
+
+        .metric-formula {
+            font-family: 'JetBrains Mono', monospace;
+            color: #0ea5e9;
+            margin-bottom: 0.25rem;
+        }
+        "#
+    );
+
+    html! {
+        <div class={css}>
+            <button onclick={on_back}>{"← Dashboard"}</button>
+            <h1>{"Risk Premiums & Discount Rates"}</h1>
+            
+            <div class="toolbar">
+                <button onclick={on_refresh} disabled={*is_loading}>
+                    {if *is_loading { "Loading…" } else { "Refresh" }}
+                </button>
+                {if let Some(response) = data.as_ref() {
+                    html! {
+                        <>
+                            <span>{response.report.as_of.map(|date| format!("Data as of {}", date)).unwrap_or_default()}</span>
+                            <span>{response.fetched_at.map(|time| format!("Fetched from FRED {}", time.format("%Y-%m-%d %H:%M UTC"))).unwrap_or_default()}</span>
+                        </>
+                    }
+                } else {
+                    html! {}
+                }}
+                {if let Some(time) = *loaded_at {
+                    html! { <span>{format!("Last updated {}", time.format("%H:%M:%S UTC"))}</span> }
+                } else {
+                    html! {}
+                }}
+            </div>
+            
+            {if let Some(error) = error_message.as_ref() {
+                html! { <div class="error-message">{error}</div> }
+            } else {
+                html! {}
+            }}
+            
+            {match data.as_ref() {
+                Some(response) => html! {
+                    <>
+                        {if response.stale || !response.missing.is_empty() {
+                            html! {
+                                <div class="stale-notice">
+                                    {"FRED could not be reached for some series; older or missing values are shown."}
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }}
+                        <div class="metrics-grid">
+                            {for response.report.metrics.iter().map(|metric| {
+                                let note = metric.id.uses_earnings().then(|| earnings_note(response));
+                                let estimate = metric.id.uses_earnings() && response.report.earnings.is_none();
+                                html! {
+                                    <MetricCard key={format!("{:?}", metric.id)} metric={metric.clone()} {note} {estimate} />
+                                }
+                            })}
+                        </div>
+                    </>
+                },
+                None => html! {},
+            }}
+            
+            <div class="chart-picker">
+                <label>
+                    {"History"}
+                    <select ref={chart_select} onchange={on_chart_change}>
+                        {for chart_sources().into_iter().map(|source| html! {
+                            <option value={source.id()} selected={source == *chart_source}>
+                                {match source {
+                                    ChartSource::Metric(_) => source.label().to_string(),
+                                    ChartSource::Series(series) => format!("{} ({})", series.label(), series.id()),
+                                }}
+                            </option>
+                        })}
+                    </select>
+                </label>
+            </div>
+            <HistoryChart source={*chart_source} />
+            <EarningsSourcePanel on_change={load.clone()} />
+        </div>
+    }
+}
\ No newline at end of file
@@ -0,0 +1,220 @@
+use yew::prelude::*;
+use web_sys::HtmlInputElement;
+use crate::{services::auth::use_auth_service, types::TotpEnrollment};
+use stylist::yew::styled_component;
+
+#[derive(Properties, PartialEq)]
+pub struct TwoFactorSetupProps {
+    pub username: String,
+}
+
+#[derive(Clone, PartialEq)]
+enum Step {
+    Idle,
+    Scanning(TotpEnrollment),
+    // Recovery codes are only ever shown here, right after enrollment
+    Enabled(Vec<String>),
+    Disabling,
+}
+
+#[styled_component(TwoFactorSetup)]
+pub fn two_factor_setup(props: &TwoFactorSetupProps) -> Html {
+    let auth_service = use_auth_service();
+    let step = use_state(|| Step::Idle);
+    let code_ref = use_node_ref();
+    let message = use_state(|| None::<String>);
+
+    let read_code = {
+        let code_ref = code_ref.clone();
+        move || {
+            code_ref
+                .cast::<HtmlInputElement>()
+                .map(|input| input.value().trim().to_string())
+                .unwrap_or_default()
+        }
+    };
+
+    let on_begin = {
+        let auth_service = auth_service.clone();
+        let step = step.clone();
+        let message = message.clone();
+        
+        Callback::from(move |_| {
+            let auth_service = auth_service.clone();
+            let step = step.clone();
+            let message = message.clone();
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth_service.begin_two_factor_enrollment().await {
+                    Ok(enrollment) => {
+                        message.set(None);
+                        step.set(Step::Scanning(enrollment));
+                    }
+                    Err(error) => message.set(Some(error.message)),
+                }
+            });
+        })
+    };
+
+    let on_confirm = {
+        let auth_service = auth_service.clone();
+        let step = step.clone();
+        let message = message.clone();
+        let read_code = read_code.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let code = read_code();
+            let auth_service = auth_service.clone();
+            let step = step.clone();
+            let message = message.clone();
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth_service.confirm_two_factor(code).await {
+                    Ok(recovery) => {
+                        message.set(None);
+                        step.set(Step::Enabled(recovery.codes));
+                    }
+                    Err(error) => message.set(Some(error.message)),
+                }
+            });
+        })
+    };
+
+    let on_disable = {
+        let auth_service = auth_service.clone();
+        let step = step.clone();
+        let message = message.clone();
+        let username = props.username.clone();
+        
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let code = read_code();
+            let auth_service = auth_service.clone();
+            let step = step.clone();
+            let message = message.clone();
+            let username = username.clone();
+            
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth_service.disable_two_factor(username, Some(code)).await {
+                    Ok(_) => {
+                        message.set(Some("Two-factor authentication is off".to_string()));
+                        step.set(Step::Idle);
+                    }
+                    Err(error) => message.set(Some(error.message)),
+                }
+            });
+        })
+    };
+
+    let show_disable = {
+        let step = step.clone();
+        let message = message.clone();
+        Callback::from(move |_| {
+            message.set(None);
+            step.set(Step::Disabling);
+        })
+    };
+
+    let css = css!(
+        r#"
+        position: absolute;
+        bottom: 20px;
+        right: 20px;
+        max-width: 320px;
+        padding: 1rem 1.25rem;
+        border: 1px solid rgba(255, 255, 255, 0.2);
+        border-radius: 8px;
+        background: rgba(255, 255, 255, 0.05);
+        color: rgba(255, 255, 255, 0.8);
+        font-size: 0.85rem;
+
+        h3 {
+            margin: 0 0 0.75rem;
+            font-size: 0.95rem;
+            font-weight: 500;
+        }
+
+        .qr svg {
+            width: 200px;
+            height: 200px;
+        }
+
+        .secret, .codes {
+            font-family: 'JetBrains Mono', monospace;
+            word-break: break-all;
+        }
+
+        input {
+            width: 100%;
+            margin: 0.5rem 0;
+            padding: 0.4rem 0.6rem;
+            border: 1px solid rgba(255, 255, 255, 0.3);
+            border-radius: 6px;
+            background: transparent;
+            color: white;
+            box-sizing: border-box;
+        }
+
+        button {
+            background: transparent;
+            border: 1px solid rgba(255, 255, 255, 0.5);
+            color: rgba(255, 255, 255, 0.9);
+            padding: 0.35rem 0.75rem;
+            margin-right: 0.5rem;
+            border-radius: 6px;
+            cursor: pointer;
+        }
+        "#
+    );
+
+    let body = match &*step {
+        Step::Idle => html! {
+            <>
+                <button onclick={on_begin}>{"Set up"}</button>
+                <button onclick={show_disable}>{"Turn off"}</button>
+            </>
+        },
+        Step::Scanning(enrollment) => html! {
+            <form onsubmit={on_confirm}>
+                <p>{"Scan this code with your authenticator app, then enter the code it shows."}</p>
+                {match &enrollment.qr_svg {
+                    Some(svg) => html! {
+                        <div class="qr">{Html::from_html_unchecked(AttrValue::from(svg.clone()))}</div>
+                    },
+                    None => html! {},
+                }}
+                <p class="secret">{&enrollment.secret}</p>
+                <input type="text" ref={code_ref.clone()} inputmode="numeric" autocomplete="one-time-code" />
+                <button type="submit">{"Confirm"}</button>
+            </form>
+        },
+        Step::Enabled(codes) => html! {
+            <>
+                <p>{"Two-factor authentication is on. Store these recovery codes somewhere safe; each works once and they will not be shown again."}</p>
+                <ul class="codes">
+                    {for codes.iter().map(|code| html! { <li>{code}</li> })}
+                </ul>
+            </>
+        },
+        Step::Disabling => html! {
+            <form onsubmit={on_disable}>
+                <p>{"Enter a current authentication code or a recovery code."}</p>
+                <input type="text" ref={code_ref.clone()} autocomplete="one-time-code" />
+                <button type="submit">{"Turn off"}</button>
+            </form>
+        },
+    };
+
+    html! {
+        <div class={css}>
+            <h3>{"Two-factor authentication"}</h3>
+            {body}
+            {if let Some(message) = message.as_ref() {
+                html! { <p>{message}</p> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,130 @@
+use gloo_net::http::Request;
+use serde::Deserialize;
+
+/// Where the runtime configuration is fetched from, relative to the page.
+const CONFIG_PATH: &str = "/config.json";
+
+// Build-time profile, e.g. `PORTAL_PROFILE=production trunk build --release`
+const BUILD_PROFILE: Option<&str> = option_env!("PORTAL_PROFILE");
+const BUILD_API_BASE: Option<&str> = option_env!("PORTAL_API_BASE");
+
+/// Settings the frontend reads at startup. Defaults come from the profile,
+/// which `config.json` can choose at runtime; any other key in the file
+/// overrides its profile default without a rebuild.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppConfig {
+    pub profile: String,
+    /// Origin and prefix of the HTTP API, e.g. `https://portal.example.com/api`.
+    pub api_base_url: String,
+    /// Requests still pending after this long fail as `server_unavailable`.
+    pub request_timeout_secs: u32,
+    /// How often the dashboard checks that the session is still alive.
+    pub session_poll_secs: u32,
+    /// Warn about session expiry once this little time is left.
+    pub session_warning_secs: u64,
+    pub features: FeatureToggles,
+}
+
+/// Optional parts of the UI. The server still enforces access on its own.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FeatureToggles {
+    pub two_factor: bool,
+    pub audit_log: bool,
+}
+
+impl Default for FeatureToggles {
+    fn default() -> Self {
+        Self {
+            two_factor: true,
+            audit_log: true,
+        }
+    }
+}
+
+/// `config.json` as written: every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    profile: Option<String>,
+    api_base_url: Option<String>,
+    request_timeout_secs: Option<u32>,
+    session_poll_secs: Option<u32>,
+    session_warning_secs: Option<u64>,
+    features: Option<FeatureToggles>,
+}
+
+impl AppConfig {
+    /// Defaults for a named profile. In production the backend serves the
+    /// frontend itself, so the API is on the same origin.
+    pub fn for_profile(profile: &str) -> Self {
+        let api_base_url = match profile {
+            "production" => "/api",
+            _ => "http://127.0.0.1:8080/api",
+        };
+
+        Self {
+            profile: profile.to_string(),
+            api_base_url: BUILD_API_BASE.unwrap_or(api_base_url).to_string(),
+            request_timeout_secs: 15,
+            session_poll_secs: 30,
+            session_warning_secs: 120,
+            features: FeatureToggles::default(),
+        }
+    }
+
+    /// Fetches `config.json`, keeping the build defaults when it is missing
+    /// or unreadable so a bare `trunk serve` still works.
+    pub async fn load() -> Self {
+        let file = match Request::get(CONFIG_PATH).send().await {
+            Ok(response) if response.ok() => response.json::<ConfigFile>().await.map_err(|error| error.to_string()),
+            _ => return Self::default(),
+        };
+        file.and_then(Self::from_file).unwrap_or_else(|error| {
+            web_sys::console::warn_1(&format!("Ignoring invalid {CONFIG_PATH}: {error}").into());
+            Self::default()
+        })
+    }
+
+    // The profile picks the defaults the other keys then override
+    fn from_file(file: ConfigFile) -> Result<Self, String> {
+        let mut config = match file.profile.as_deref() {
+            Some(profile) => Self::for_profile(profile),
+            None => Self::default(),
+        };
+
+        if let Some(api_base_url) = file.api_base_url {
+            config.api_base_url = api_base_url;
+        }
+        if let Some(secs) = file.request_timeout_secs {
+            if secs == 0 {
+                return Err("request_timeout_secs must be at least 1".to_string());
+            }
+            config.request_timeout_secs = secs;
+        }
+        if let Some(secs) = file.session_poll_secs {
+            config.session_poll_secs = secs;
+        }
+        if let Some(secs) = file.session_warning_secs {
+            config.session_warning_secs = secs;
+        }
+        if let Some(features) = file.features {
+            config.features = features;
+        }
+        Ok(config)
+    }
+
+    pub fn request_timeout_ms(&self) -> u32 {
+        self.request_timeout_secs.saturating_mul(1000)
+    }
+
+    pub fn session_poll_ms(&self) -> u32 {
+        self.session_poll_secs.max(1).saturating_mul(1000)
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self::for_profile(BUILD_PROFILE.unwrap_or("development"))
+    }
+}
\ No newline at end of file
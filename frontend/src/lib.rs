@@ -2,12 +2,16 @@ use yew::prelude::*;
 use yew_router::prelude::*;
 
 mod components;
+mod config;
 mod services;
+mod session;
 mod types;
 
-use components::{login::Login, dashboard::Dashboard};
-use services::auth::AuthService;
-use types::UserInfo;
+use components::{login::Login, dashboard::Dashboard, audit_log::AuditLogView, risk_premiums::RiskPremiums, alerts::Alerts, funds::Funds, fund_detail::FundDetail, fund_comparison::FundComparisonView, fund_editor::FundEditor, guard::{Access, RouteGuard}};
+use config::AppConfig;
+use services::transport::Transport;
+use session::SessionProvider;
+use types::Role;
 
 #[derive(Clone, Routable, PartialEq)]
 enum Route {
@@ -15,43 +19,108 @@ enum Route {
     Login,
     #[at("/dashboard")]
     Dashboard,
+    #[at("/audit")]
+    Audit,
+    #[at("/risk-premiums")]
+    RiskPremiums,
+    #[at("/alerts")]
+    Alerts,
+    #[at("/funds")]
+    Funds,
+    #[at("/funds/compare")]
+    FundComparison,
+    #[at("/funds/new")]
+    FundNew,
+    #[at("/funds/:id")]
+    Fund { id: u32 },
+    #[at("/funds/:id/edit")]
+    FundEdit { id: u32 },
+}
+
+impl Route {
+    fn access(&self) -> Access {
+        match self {
+            Route::Login => Access::Guest,
+            Route::Dashboard => Access::SignedIn,
+            Route::Audit => Access::Role(Role::Admin),
+            Route::RiskPremiums => Access::SignedIn,
+            Route::Alerts => Access::SignedIn,
+            Route::Funds => Access::SignedIn,
+            Route::FundComparison => Access::SignedIn,
+            Route::Fund { .. } => Access::SignedIn,
+            Route::FundNew => Access::Role(Role::Admin),
+            Route::FundEdit { .. } => Access::Role(Role::Admin),
+        }
+    }
+
+    /// Pages the desktop app has no commands for: the risk premiums and
+    /// their charts, the fund list, each fund's page, Compare and the fund
+    /// editor pages.
+    fn needs_server(&self) -> bool {
+        matches!(
+            self,
+            Route::RiskPremiums
+                | Route::Funds
+                | Route::FundComparison
+                | Route::Fund { .. }
+                | Route::FundNew
+                | Route::FundEdit { .. }
+        )
+    }
 }
 
 fn switch(routes: Route) -> Html {
-    match routes {
+    if routes.needs_server() && Transport::is_desktop() {
+        return html! { <Redirect<Route> to={Route::Dashboard} /> };
+    }
+
+    let page = match routes {
         Route::Login => html! { <Login /> },
         Route::Dashboard => html! { <Dashboard /> },
+        Route::Audit => html! { <AuditLogView /> },
+        Route::RiskPremiums => html! { <RiskPremiums /> },
+        Route::Alerts => html! { <Alerts /> },
+        Route::Funds => html! { <Funds /> },
+        Route::FundComparison => html! { <FundComparisonView /> },
+        Route::Fund { id } => html! { <FundDetail {id} /> },
+        Route::FundNew => html! { <FundEditor /> },
+        Route::FundEdit { id } => html! { <FundEditor id={Some(id)} /> },
+    };
+
+    html! {
+        <RouteGuard access={routes.access()}>
+            {page}
+        </RouteGuard>
     }
 }
 
 #[function_component(App)]
 fn app() -> Html {
-    let user_state = use_state(|| UserInfo {
-        username: String::new(),
-        logged_in: false,
-    });
-
-    let auth_service = AuthService::new();
-    
-    // Check authentication status on app start
+    let config = use_state(|| None::<AppConfig>);
+
+    // Load the runtime configuration before anything talks to the API
     {
-        let user_state = user_state.clone();
-        let auth_service = auth_service.clone();
+        let config = config.clone();
         
         use_effect_with((), move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                if let Ok(user_info) = auth_service.check_auth().await {
-                    user_state.set(user_info);
-                }
+                config.set(Some(AppConfig::load().await));
             });
             || ()
         });
     }
 
-    html! {
-        <BrowserRouter>
-            <Switch<Route> render={switch} />
-        </BrowserRouter>
+    match (*config).clone() {
+        Some(config) => html! {
+            <ContextProvider<AppConfig> context={config}>
+                <SessionProvider>
+                    <BrowserRouter>
+                        <Switch<Route> render={switch} />
+                    </BrowserRouter>
+                </SessionProvider>
+            </ContextProvider<AppConfig>>
+        },
+        None => html! { <div class="loading"></div> },
     }
 }
 
@@ -1,46 +1,132 @@
-use gloo_net::http::Request;
-use crate::types::{LoginRequest, LoginResponse, UserInfo};
+use serde_json::json;
+use yew::prelude::*;
+use crate::config::AppConfig;
+use crate::services::transport::Transport;
+use crate::types::{
+    ApiError, AuditEntry, AuditQuery, ConfirmTwoFactorRequest, DisableTwoFactorRequest, LoginRequest,
+    LoginResponse, RecoveryCodes, TotpEnrollment, TwoFactorLoginRequest, UserInfo, UserSummary,
+};
 
-#[derive(Clone)]
+/// Authentication and account calls, over whichever transport the app is
+/// running with.
+#[derive(Clone, PartialEq)]
 pub struct AuthService {
-    base_url: String,
+    transport: Transport,
+}
+
+/// The `AuthService` for the `AppConfig` provided at the root of the app.
+#[hook]
+pub fn use_auth_service() -> AuthService {
+    let config = use_context::<AppConfig>().expect("AppConfig context is provided by App");
+    AuthService::new(&config)
 }
 
 impl AuthService {
-    pub fn new() -> Self {
+    pub fn new(config: &AppConfig) -> Self {
         Self {
-            base_url: "http://127.0.0.1:8080/api".to_string(),
+            transport: Transport::detect(config),
         }
     }
 
-    pub async fn login(&self, username: String, password: String) -> Result<LoginResponse, gloo_net::Error> {
-        let login_request = LoginRequest { username, password };
+    pub async fn login(&self, username: String, password: String) -> Result<LoginResponse, ApiError> {
+        let request = LoginRequest { username, password };
         
-        let response = Request::post(&format!("{}/login", self.base_url))
-            .header("Content-Type", "application/json")
-            .credentials(web_sys::RequestCredentials::Include)
-            .json(&login_request)?
-            .send()
-            .await?;
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/login", &request).await,
+            Transport::Ipc(ipc) => {
+                let response: LoginResponse = ipc.invoke("login", json!({ "request": request })).await?;
+                if let Some(token) = &response.session_token {
+                    ipc.set_token(Some(token));
+                }
+                Ok(response)
+            }
+        }
+    }
+
+    pub async fn login_two_factor(&self, two_factor_token: String, code: String) -> Result<LoginResponse, ApiError> {
+        let request = TwoFactorLoginRequest { two_factor_token, code };
+
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/login/two-factor", &request).await,
+            Transport::Ipc(ipc) => {
+                let response: LoginResponse = ipc.invoke("login_two_factor", json!({ "request": request })).await?;
+                if let Some(token) = &response.session_token {
+                    ipc.set_token(Some(token));
+                }
+                Ok(response)
+            }
+        }
+    }
+
+    pub async fn logout(&self) -> Result<LoginResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post("/logout").await,
+            Transport::Ipc(ipc) => {
+                let result = ipc.invoke_with_token("logout", json!({})).await;
+                ipc.set_token(None);
+                result
+            }
+        }
+    }
+
+    pub async fn check_auth(&self) -> Result<UserInfo, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/auth", Vec::new()).await,
+            Transport::Ipc(ipc) => ipc.invoke("check_auth", json!({ "token": ipc.token() })).await,
+        }
+    }
+
+    pub async fn refresh_session(&self) -> Result<UserInfo, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post("/session/refresh").await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("refresh_session", json!({})).await,
+        }
+    }
 
-        response.json::<LoginResponse>().await
+    pub async fn begin_two_factor_enrollment(&self) -> Result<TotpEnrollment, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post("/two-factor/enroll").await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("begin_two_factor_enrollment", json!({})).await,
+        }
     }
 
-    pub async fn logout(&self) -> Result<LoginResponse, gloo_net::Error> {
-        let response = Request::post(&format!("{}/logout", self.base_url))
-            .credentials(web_sys::RequestCredentials::Include)
-            .send()
-            .await?;
+    pub async fn confirm_two_factor(&self, code: String) -> Result<RecoveryCodes, ApiError> {
+        let request = ConfirmTwoFactorRequest { code };
 
-        response.json::<LoginResponse>().await
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/two-factor/confirm", &request).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("confirm_two_factor", json!({ "request": request })).await,
+        }
     }
 
-    pub async fn check_auth(&self) -> Result<UserInfo, gloo_net::Error> {
-        let response = Request::get(&format!("{}/auth", self.base_url))
-            .credentials(web_sys::RequestCredentials::Include)
-            .send()
-            .await?;
+    pub async fn disable_two_factor(&self, username: String, code: Option<String>) -> Result<UserSummary, ApiError> {
+        let request = DisableTwoFactorRequest { username, code };
+
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/two-factor/disable", &request).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("disable_two_factor", json!({ "request": request })).await,
+        }
+    }
 
-        response.json::<UserInfo>().await
+    pub async fn audit_log(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let mut params = Vec::new();
+                if let Some(from) = query.from {
+                    params.push(("from", from.to_rfc3339()));
+                }
+                if let Some(to) = query.to {
+                    params.push(("to", to.to_rfc3339()));
+                }
+                if let Some(user) = &query.user {
+                    params.push(("user", user.clone()));
+                }
+                if let Some(limit) = query.limit {
+                    params.push(("limit", limit.to_string()));
+                }
+                http.get("/audit", params).await
+            }
+            Transport::Ipc(ipc) => ipc.invoke_with_token("query_audit_log", json!({ "query": query })).await,
+        }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,151 @@
+use yew::prelude::*;
+use crate::config::AppConfig;
+use crate::services::transport::Transport;
+use crate::types::{
+    ApiError, DeletedFund, ErrorCode, FundComparison, FundComparisonQuery, FundDiff, FundDiffQuery, FundResponse,
+    FundSummary, FundVersionRequest, FundVersions, LeverageQuery, LeverageRebalance, LeverageResponse, RiskMetricsQuery,
+    RiskMetricsResponse, SaveFundRequest,
+};
+
+/// The All Weather funds served by the portal backend.
+#[derive(Clone, PartialEq)]
+pub struct FundService {
+    transport: Transport,
+}
+
+#[hook]
+pub fn use_fund_service() -> FundService {
+    let config = use_context::<AppConfig>().expect("AppConfig context is provided by App");
+    FundService::new(&config)
+}
+
+impl FundService {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            transport: Transport::detect(config),
+        }
+    }
+
+    pub async fn funds(&self) -> Result<Vec<FundSummary>, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/funds", Vec::new()).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn fund(&self, number: u32) -> Result<FundResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get(&format!("/funds/{}", number), Vec::new()).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    /// The fund's risk table recomputed from its returns, optionally against
+    /// a benchmark ticker.
+    pub async fn metrics(&self, number: u32, query: &RiskMetricsQuery) -> Result<RiskMetricsResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let mut params = vec![("risk_free", query.risk_free.to_string())];
+                if let Some(benchmark) = &query.benchmark {
+                    params.push(("benchmark", benchmark.clone()));
+                }
+                http.get(&format!("/funds/{}/metrics", number), params).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    /// The fund run at leverage, borrowing at the fed funds rate.
+    pub async fn leverage(&self, number: u32, query: &LeverageQuery) -> Result<LeverageResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let rebalance = match query.rebalance {
+                    LeverageRebalance::Daily => "daily",
+                    LeverageRebalance::Monthly => "monthly",
+                };
+                let params = vec![("leverage", query.leverage.to_string()), ("rebalance", rebalance.to_string())];
+                http.get(&format!("/funds/{}/leverage", number), params).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn compare(&self, query: &FundComparisonQuery) -> Result<FundComparison, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let mut params = vec![("funds", query.funds.clone()), ("risk_free", query.risk_free.to_string())];
+                if let Some(benchmark) = &query.benchmark {
+                    params.push(("benchmark", benchmark.clone()));
+                }
+                http.get("/funds/compare", params).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    /// Saves a new fund; the server picks its number.
+    pub async fn create_fund(&self, request: &SaveFundRequest) -> Result<FundVersions, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/funds", request).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn save_fund(&self, number: u32, request: &SaveFundRequest) -> Result<FundVersions, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post_json(&format!("/funds/{}", number), request).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn delete_fund(&self, number: u32, request: &FundVersionRequest) -> Result<FundVersions, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post_json(&format!("/funds/{}/delete", number), request).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn deleted_funds(&self) -> Result<Vec<DeletedFund>, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/funds/deleted", Vec::new()).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    /// The fund's definition as it stands and every saved version of it.
+    pub async fn fund_versions(&self, number: u32) -> Result<FundVersions, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get(&format!("/funds/{}/versions", number), Vec::new()).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn diff_fund_versions(&self, number: u32, query: FundDiffQuery) -> Result<FundDiff, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let params = vec![("from", query.from.to_string()), ("to", query.to.to_string())];
+                http.get(&format!("/funds/{}/versions/diff", number), params).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn restore_fund_version(
+        &self,
+        number: u32,
+        version: u32,
+        request: &FundVersionRequest,
+    ) -> Result<FundVersions, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                http.post_json(&format!("/funds/{}/versions/{}/restore", number, version), request).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+}
+
+// The fund files and returns data are only deployed with the web server
+fn desktop_unavailable() -> ApiError {
+    ApiError::new(ErrorCode::NotFound, "Funds are not available in the desktop app yet")
+}
\ No newline at end of file
@@ -0,0 +1,133 @@
+use serde_json::json;
+use yew::prelude::*;
+use crate::config::AppConfig;
+use crate::services::transport::Transport;
+use crate::types::{
+    AcknowledgeAlertsRequest, AlertRule, AlertsResponse, ApiError, EarningsStatus, ErrorCode, FredSeriesQuery, FredSeriesResponse, ImportEarningsRequest,
+    MetricHistoryQuery, MetricHistoryResponse, RiskPremiumsResponse, SaveAlertRulesRequest,
+};
+
+/// Market data served by the portal backend.
+#[derive(Clone, PartialEq)]
+pub struct MarketService {
+    transport: Transport,
+}
+
+#[hook]
+pub fn use_market_service() -> MarketService {
+    let config = use_context::<AppConfig>().expect("AppConfig context is provided by App");
+    MarketService::new(&config)
+}
+
+impl MarketService {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            transport: Transport::detect(config),
+        }
+    }
+
+    pub async fn risk_premiums(&self) -> Result<RiskPremiumsResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/risk-premiums", Vec::new()).await,
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn series(&self, query: &FredSeriesQuery) -> Result<FredSeriesResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let mut params = vec![("series_id", query.series_id.clone())];
+                if let Some(start) = query.observation_start {
+                    params.push(("observation_start", start.to_string()));
+                }
+                if let Some(end) = query.observation_end {
+                    params.push(("observation_end", end.to_string()));
+                }
+                http.get("/fred/series", params).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn metric_history(&self, query: &MetricHistoryQuery) -> Result<MetricHistoryResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => {
+                let mut params = vec![("metric", query.metric.id().to_string())];
+                if let Some(start) = query.observation_start {
+                    params.push(("observation_start", start.to_string()));
+                }
+                if let Some(end) = query.observation_end {
+                    params.push(("observation_end", end.to_string()));
+                }
+                http.get("/risk-premiums/history", params).await
+            }
+            Transport::Ipc(_) => Err(desktop_unavailable()),
+        }
+    }
+
+    pub async fn earnings_status(&self) -> Result<EarningsStatus, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/earnings", Vec::new()).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("earnings_status", json!({})).await,
+        }
+    }
+
+    pub async fn import_earnings(&self, request: &ImportEarningsRequest) -> Result<EarningsStatus, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/earnings/import", request).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("import_earnings", json!({ "request": request })).await,
+        }
+    }
+
+    pub async fn clear_earnings_import(&self) -> Result<EarningsStatus, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post("/earnings/import/clear").await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("clear_earnings_import", json!({})).await,
+        }
+    }
+
+    pub async fn alerts(&self) -> Result<AlertsResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/alerts", Vec::new()).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("list_alerts", json!({})).await,
+        }
+    }
+
+    /// Acknowledges the given alerts, or every alert when `ids` is empty.
+    pub async fn acknowledge_alerts(&self, ids: Vec<u64>) -> Result<AlertsResponse, ApiError> {
+        let request = AcknowledgeAlertsRequest { ids };
+
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/alerts/acknowledge", &request).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("acknowledge_alerts", json!({ "request": request })).await,
+        }
+    }
+
+    pub async fn alert_rules(&self) -> Result<Vec<AlertRule>, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.get("/alerts/rules", Vec::new()).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("alert_rules", json!({})).await,
+        }
+    }
+
+    pub async fn save_alert_rules(&self, rules: Vec<AlertRule>) -> Result<Vec<AlertRule>, ApiError> {
+        let request = SaveAlertRulesRequest { rules };
+
+        match &self.transport {
+            Transport::Http(http) => http.post_json("/alerts/rules", &request).await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("save_alert_rules", json!({ "request": request })).await,
+        }
+    }
+
+    pub async fn check_alerts(&self) -> Result<AlertsResponse, ApiError> {
+        match &self.transport {
+            Transport::Http(http) => http.post("/alerts/check").await,
+            Transport::Ipc(ipc) => ipc.invoke_with_token("check_alerts", json!({})).await,
+        }
+    }
+}
+
+// The desktop app only fetches FRED data and earnings to check alerts
+fn desktop_unavailable() -> ApiError {
+    ApiError::new(ErrorCode::NotFound, "Market data is not available in the desktop app yet")
+}
\ No newline at end of file
@@ -1 +1,4 @@
-pub mod auth; 
\ No newline at end of file
+pub mod auth; 
+pub mod funds;
+pub mod market;
+pub mod transport;
\ No newline at end of file
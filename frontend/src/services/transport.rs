@@ -0,0 +1,190 @@
+use gloo_net::http::{Request, RequestBuilder};
+use gloo_storage::{SessionStorage, Storage};
+use gloo_timers::callback::Timeout;
+use js_sys::{Function, Promise, Reflect};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::AbortController;
+use crate::config::AppConfig;
+use crate::types::ApiError;
+
+const TOKEN_STORAGE_KEY: &str = "ms_portal_session_token";
+
+/// How the frontend reaches the portal: the HTTP API when served from the
+/// web, or Tauri commands when running inside the desktop shell.
+#[derive(Clone, PartialEq)]
+pub enum Transport {
+    Http(HttpTransport),
+    Ipc(IpcTransport),
+}
+
+impl Transport {
+    /// Whether the page is running inside the desktop shell.
+    pub fn is_desktop() -> bool {
+        IpcTransport::invoke_fn().is_some()
+    }
+
+    /// Picks IPC when the Tauri runtime has injected itself into the page.
+    pub fn detect(config: &AppConfig) -> Self {
+        match IpcTransport::invoke_fn() {
+            Some(_) => Transport::Ipc(IpcTransport),
+            None => Transport::Http(HttpTransport::new(&config.api_base_url, config.request_timeout_ms())),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct HttpTransport {
+    base_url: String,
+    timeout_ms: u32,
+}
+
+impl HttpTransport {
+    pub fn new(base_url: impl Into<String>, timeout_ms: u32) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            timeout_ms,
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str, query: Vec<(&str, String)>) -> Result<T, ApiError> {
+        let timeout = RequestTimeout::start(self.timeout_ms);
+        send(self.builder(Request::get(&self.url(path)), timeout.as_ref()).query(query).build()).await
+    }
+
+    pub async fn post<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        let timeout = RequestTimeout::start(self.timeout_ms);
+        send(self.builder(Request::post(&self.url(path)), timeout.as_ref()).build()).await
+    }
+
+    pub async fn post_json<T: DeserializeOwned, B: Serialize>(&self, path: &str, body: &B) -> Result<T, ApiError> {
+        let timeout = RequestTimeout::start(self.timeout_ms);
+        send(self.builder(Request::post(&self.url(path)), timeout.as_ref()).json(body)).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    // The session lives in a cookie, so every request must carry credentials
+    fn builder(&self, builder: RequestBuilder, timeout: Option<&RequestTimeout>) -> RequestBuilder {
+        let signal = timeout.map(|timeout| timeout.controller.signal());
+        builder
+            .credentials(web_sys::RequestCredentials::Include)
+            .abort_signal(signal.as_ref())
+    }
+}
+
+// Aborts the request it is attached to once the timer fires; dropping it
+// first (the request finished) cancels the timer
+struct RequestTimeout {
+    controller: AbortController,
+    _timer: Timeout,
+}
+
+impl RequestTimeout {
+    fn start(timeout_ms: u32) -> Option<Self> {
+        if timeout_ms == 0 {
+            return None;
+        }
+        let controller = AbortController::new().ok()?;
+        let abort = controller.clone();
+        let timer = Timeout::new(timeout_ms, move || abort.abort());
+        Some(Self {
+            controller,
+            _timer: timer,
+        })
+    }
+}
+
+// Transport failures become `server_unavailable`; error responses carry an
+// `ApiError` body, unless something in between (a proxy) answered instead
+async fn send<T: DeserializeOwned>(request: Result<Request, gloo_net::Error>) -> Result<T, ApiError> {
+    let response = request
+        .map_err(|_| ApiError::server_unavailable())?
+        .send()
+        .await
+        .map_err(|_| ApiError::server_unavailable())?;
+
+    if response.ok() {
+        return response
+            .json::<T>()
+            .await
+            .map_err(|_| ApiError::internal("Unexpected response from the server"));
+    }
+
+    let status = response.status();
+    Err(response.json::<ApiError>().await.unwrap_or_else(|_| {
+        if status >= 500 {
+            ApiError::server_unavailable()
+        } else {
+            ApiError::internal("Unexpected response from the server")
+        }
+    }))
+}
+
+/// Calls the desktop app's Tauri commands. Commands take the session token
+/// explicitly, so it is kept in session storage for the life of the window.
+#[derive(Clone, PartialEq)]
+pub struct IpcTransport;
+
+impl IpcTransport {
+    // Tauri 2 always injects `__TAURI_INTERNALS__`, even without `withGlobalTauri`
+    fn invoke_fn() -> Option<Function> {
+        let window = web_sys::window()?;
+        let internals = Reflect::get(&window, &JsValue::from_str("__TAURI_INTERNALS__")).ok()?;
+        if internals.is_undefined() {
+            return None;
+        }
+        Reflect::get(&internals, &JsValue::from_str("invoke"))
+            .ok()?
+            .dyn_into::<Function>()
+            .ok()
+    }
+
+    pub fn token(&self) -> Option<String> {
+        SessionStorage::get::<String>(TOKEN_STORAGE_KEY).ok()
+    }
+
+    pub fn set_token(&self, token: Option<&str>) {
+        match token {
+            Some(token) => {
+                let _ = SessionStorage::set(TOKEN_STORAGE_KEY, token);
+            }
+            None => SessionStorage::delete(TOKEN_STORAGE_KEY),
+        }
+    }
+
+    /// Invokes `command` with `args`, an object of named command arguments.
+    /// Rejections carry the command's `ApiError`.
+    pub async fn invoke<T: DeserializeOwned>(&self, command: &str, args: Value) -> Result<T, ApiError> {
+        let invoke = Self::invoke_fn().ok_or_else(ApiError::server_unavailable)?;
+        let args = args
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|_| ApiError::internal("Could not encode the request"))?;
+
+        let promise = invoke
+            .call2(&JsValue::NULL, &JsValue::from_str(command), &args)
+            .map_err(|_| ApiError::server_unavailable())?
+            .dyn_into::<Promise>()
+            .map_err(|_| ApiError::internal("Unexpected response from the desktop app"))?;
+
+        match JsFuture::from(promise).await {
+            Ok(value) => serde_wasm_bindgen::from_value(value)
+                .map_err(|_| ApiError::internal("Unexpected response from the desktop app")),
+            Err(error) => Err(serde_wasm_bindgen::from_value(error)
+                .unwrap_or_else(|_| ApiError::internal("The desktop app rejected the request"))),
+        }
+    }
+
+    /// Like `invoke`, adding the stored session token as the `token` argument.
+    pub async fn invoke_with_token<T: DeserializeOwned>(&self, command: &str, mut args: Value) -> Result<T, ApiError> {
+        let token = self.token().ok_or_else(ApiError::session_expired)?;
+        if let Value::Object(map) = &mut args {
+            map.insert("token".to_string(), Value::String(token));
+        }
+        self.invoke(command, args).await
+    }
+}
@@ -0,0 +1,76 @@
+use yew::prelude::*;
+use crate::services::auth::use_auth_service;
+use crate::types::UserInfo;
+
+/// The signed-in user, shared by every page. `user` is `None` until the
+/// first `check_auth` answers.
+#[derive(Clone, PartialEq)]
+pub struct Session {
+    user: Option<UserInfo>,
+    set_user: Callback<UserInfo>,
+}
+
+impl Session {
+    pub fn user(&self) -> Option<&UserInfo> {
+        self.user.as_ref()
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.user.is_some()
+    }
+
+    /// Records what the server last said about the session.
+    pub fn set_user(&self, user: UserInfo) {
+        self.set_user.emit(user);
+    }
+
+    /// Forgets the user; guarded routes send them back to the login page.
+    pub fn sign_out(&self) {
+        self.set_user.emit(UserInfo::logged_out());
+    }
+}
+
+#[hook]
+pub fn use_session() -> Session {
+    use_context::<Session>().expect("Session context is provided by App")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SessionProviderProps {
+    pub children: Html,
+}
+
+/// Checks the session once on startup and provides it to the whole app.
+#[function_component(SessionProvider)]
+pub fn session_provider(props: &SessionProviderProps) -> Html {
+    let auth_service = use_auth_service();
+    let user = use_state(|| None::<UserInfo>);
+
+    let set_user = {
+        let user = user.clone();
+        use_callback((), move |info: UserInfo, _| user.set(Some(info)))
+    };
+
+    {
+        let set_user = set_user.clone();
+        
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                let info = auth_service.check_auth().await.unwrap_or_else(|_| UserInfo::logged_out());
+                set_user.emit(info);
+            });
+            || ()
+        });
+    }
+
+    let session = Session {
+        user: (*user).clone(),
+        set_user,
+    };
+
+    html! {
+        <ContextProvider<Session> context={session}>
+            {props.children.clone()}
+        </ContextProvider<Session>>
+    }
+}
\ No newline at end of file
@@ -1,19 +1,17 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct LoginRequest {
-    pub username: String,
-    pub password: String,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct LoginResponse {
-    pub success: bool,
-    pub message: String,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct UserInfo {
-    pub username: String,
-    pub logged_in: bool,
-} 
\ No newline at end of file
+pub use portal_types::{
+    AcknowledgeAlertsRequest, Alert, AlertCondition, AlertRule, AlertsResponse, ApiError, AuditEntry,
+    AuditQuery, ComparedSeries, ConfirmTwoFactorRequest,
+    DeletedFund, DisableTwoFactorRequest, Drawdown, EarningsStatus, ErrorCode, FredSeries, FredSeriesQuery,
+    FredSeriesResponse, FundChange, FundComparison, FundComparisonQuery, FundDefinition, FundDiff,
+    FundDiffQuery, FundResponse, FundStat, FundSummary, FundVersionRequest, FundVersions, Holding, ImportEarningsRequest,
+    LeverageQuery, LeverageRebalance, LeverageResponse, LoginRequest, LoginResponse, Metric, MetricHistoryQuery,
+    MetricHistoryResponse, MetricId, Observation, RecoveryCodes,
+    RiskMetricsQuery, RiskMetricsResponse, RiskPremiumsResponse, Role, SaveAlertRulesRequest, SaveFundRequest,
+    Signal, Strategy, TotpEnrollment, TwoFactorLoginRequest, Unit, UserInfo, UserSummary,
+    YearReturns,
+};
+pub use portal_types::comparison::{MAX_COMPARED, MIN_COMPARED};
+pub use portal_types::fund_editor::MAX_HOLDINGS;
+pub use portal_types::funds::{format_month, MONTH_NAMES};
+pub use portal_types::leverage::LEVERAGE_OPTIONS;
+pub use portal_types::risk_premiums::ASSUMED_PE;
\ No newline at end of file
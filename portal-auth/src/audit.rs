@@ -0,0 +1,140 @@
+//! Append-only audit log stored as JSON lines.
+
+use portal_types::{AuditEntry, AuditQuery};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Entries returned when a query does not set a limit.
+pub const DEFAULT_QUERY_LIMIT: usize = 500;
+
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends one entry. Each line goes out in a single write to a file
+    /// opened in append mode, so concurrent writers never interleave.
+    pub fn append(&self, entry: &AuditEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)
+    }
+
+    /// Matching entries, newest first. Lines that do not parse, such as one
+    /// torn by a crash mid-write, are skipped.
+    pub fn query(&self, query: &AuditQuery) -> io::Result<Vec<AuditEntry>> {
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let Ok(entry) = serde_json::from_str::<AuditEntry>(&line?) else {
+                continue;
+            };
+            if matches(query, &entry) {
+                entries.push(entry);
+            }
+        }
+
+        entries.reverse();
+        entries.truncate(query.limit.unwrap_or(DEFAULT_QUERY_LIMIT));
+        Ok(entries)
+    }
+}
+
+fn matches(query: &AuditQuery, entry: &AuditEntry) -> bool {
+    query.from.is_none_or(|from| entry.timestamp >= from)
+        && query.to.is_none_or(|to| entry.timestamp <= to)
+        && query.user.as_deref().is_none_or(|user| entry.user == user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use portal_types::{AuditEvent, AuditOutcome};
+
+    fn at(hour: u32, user: &str, event: AuditEvent) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc.with_ymd_and_hms(2025, 3, 3, hour, 0, 0).unwrap(),
+            ..AuditEntry::new(user, event, AuditOutcome::Success)
+        }
+    }
+
+    fn hour(hour: u32) -> Option<DateTime<Utc>> {
+        Some(Utc.with_ymd_and_hms(2025, 3, 3, hour, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn appends_and_filters_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("logs").join("audit.jsonl"));
+
+        log.append(&at(9, "admin", AuditEvent::Login)).unwrap();
+        log.append(&at(10, "analyst", AuditEvent::Login)).unwrap();
+        log.append(&at(11, "admin", AuditEvent::UserCreated))
+            .unwrap();
+        log.append(&at(12, "admin", AuditEvent::Logout)).unwrap();
+
+        let all = log.query(&AuditQuery::default()).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].event, AuditEvent::Logout);
+
+        let admin_window = log
+            .query(&AuditQuery {
+                from: hour(10),
+                to: hour(11),
+                user: Some("admin".to_string()),
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(admin_window, vec![at(11, "admin", AuditEvent::UserCreated)]);
+
+        let latest = log
+            .query(&AuditQuery {
+                limit: Some(1),
+                ..AuditQuery::default()
+            })
+            .unwrap();
+        assert_eq!(latest, vec![at(12, "admin", AuditEvent::Logout)]);
+    }
+
+    #[test]
+    fn missing_file_is_empty_and_torn_lines_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        assert!(log.query(&AuditQuery::default()).unwrap().is_empty());
+
+        log.append(&at(9, "admin", AuditEvent::Login)).unwrap();
+        fs::write(
+            log.path(),
+            format!(
+                "{}{{\"timestamp\":",
+                fs::read_to_string(log.path()).unwrap()
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(log.query(&AuditQuery::default()).unwrap().len(), 1);
+    }
+}
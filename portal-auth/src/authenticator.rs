@@ -0,0 +1,552 @@
+//! The login flow shared by the server and the desktop app: throttling,
+//! password and second-factor checks, sessions and their audit entries.
+
+use chrono::{DateTime, Utc};
+use portal_types::{ApiError, AuditEntry, AuditEvent, AuditOutcome, Role, UserInfo, UserSummary};
+use std::sync::Mutex;
+
+use crate::audit::AuditLog;
+use crate::session::{SessionLimits, SessionRecord, SessionTable};
+use crate::store::{check_password, UserStore, UserStoreError};
+use crate::throttle::{LoginThrottle, ThrottleDenial, ThrottlePolicy};
+
+/// A live session together with the account's current role.
+pub struct CurrentUser {
+    pub session: SessionRecord,
+    pub role: Role,
+}
+
+impl CurrentUser {
+    pub fn username(&self) -> &str {
+        &self.session.username
+    }
+}
+
+/// Where a correct password leads.
+pub enum LoginStep {
+    Session(SessionRecord),
+    /// The account has two-factor on; the code goes with this challenge token
+    TwoFactor(String),
+}
+
+/// Accounts, live sessions and failed-login counters. `client` identifies
+/// where an attempt came from: an address on the server, a window on the
+/// desktop.
+pub struct Authenticator {
+    pub users: Mutex<UserStore>,
+    pub sessions: Mutex<SessionTable>,
+    pub audit_log: AuditLog,
+    /// Logins waiting for a second factor, keyed by their challenge token
+    challenges: Mutex<SessionTable>,
+    user_attempts: Mutex<LoginThrottle>,
+    client_attempts: Mutex<LoginThrottle>,
+}
+
+impl Authenticator {
+    pub fn new(users: UserStore, limits: SessionLimits, audit_log: AuditLog) -> Self {
+        Self {
+            users: Mutex::new(users),
+            sessions: Mutex::new(SessionTable::new(limits)),
+            audit_log,
+            challenges: Mutex::new(SessionTable::new(SessionLimits::two_factor_challenge())),
+            user_attempts: Mutex::new(LoginThrottle::new(ThrottlePolicy::per_user())),
+            client_attempts: Mutex::new(LoginThrottle::new(ThrottlePolicy::per_client())),
+        }
+    }
+
+    /// Records an audit entry. A failed write is logged but never fails the
+    /// action that caused it.
+    pub fn audit(&self, entry: AuditEntry) {
+        if let Err(e) = self.audit_log.append(&entry) {
+            log::error!("Could not write audit log: {}", e);
+        }
+    }
+
+    /// Checks a password. Failed attempts are counted per username and per
+    /// client, and a blocked key is refused before the password is checked.
+    /// Hashing makes this slow, so servers call it off their request threads.
+    pub fn login(
+        &self,
+        username: &str,
+        password: &str,
+        client: &str,
+        now: DateTime<Utc>,
+    ) -> Result<LoginStep, ApiError> {
+        if let Some(denial) = self.throttled(username, client, now) {
+            log::warn!("Throttled login for {} from {}", username, client);
+            self.audit(login_entry(username, AuditOutcome::Throttled, client, None));
+            return Err(denial.into());
+        }
+
+        // The hash is checked on a copy so other logins and account changes
+        // do not queue behind it
+        let user = self.users.lock().unwrap().get(username).cloned();
+        let two_factor = check_password(user.as_ref(), password)
+            .then(|| user.is_some_and(|user| user.two_factor_enabled()));
+
+        match two_factor {
+            // The password alone does not clear the failure counters yet
+            Some(true) => {
+                let challenge = self.challenges.lock().unwrap().create(username, now);
+                log::info!(
+                    "Password accepted for {} from {}, awaiting second factor",
+                    username,
+                    client
+                );
+                Ok(LoginStep::TwoFactor(challenge.token))
+            }
+            Some(false) => Ok(LoginStep::Session(
+                self.start_session(username, client, now),
+            )),
+            None => {
+                log::info!("Login failed for {} from {}", username, client);
+                self.audit(login_entry(username, AuditOutcome::Failure, client, None));
+                Err(self.failed_attempt(username, client, now, ApiError::invalid_credentials()))
+            }
+        }
+    }
+
+    /// The second login step. The challenge token proves the password; the
+    /// code is throttled against the same counters as passwords so it cannot
+    /// be brute forced.
+    pub fn login_two_factor(
+        &self,
+        challenge: &str,
+        code: &str,
+        client: &str,
+        now: DateTime<Utc>,
+    ) -> Result<SessionRecord, ApiError> {
+        let username = self
+            .challenges
+            .lock()
+            .unwrap()
+            .get(challenge, now)
+            .ok_or_else(ApiError::two_factor_expired)?
+            .username;
+
+        if let Some(denial) = self.throttled(&username, client, now) {
+            log::warn!("Throttled second factor for {} from {}", username, client);
+            self.audit(login_entry(
+                &username,
+                AuditOutcome::Throttled,
+                client,
+                Some("second factor"),
+            ));
+            return Err(denial.into());
+        }
+
+        let verified = self
+            .users
+            .lock()
+            .unwrap()
+            .verify_two_factor(&username, code, now)
+            .map_err(store_error)?;
+        if verified {
+            self.challenges.lock().unwrap().revoke(challenge);
+            return Ok(self.start_session(&username, client, now));
+        }
+
+        log::info!("Invalid second factor for {} from {}", username, client);
+        self.audit(login_entry(
+            &username,
+            AuditOutcome::Failure,
+            client,
+            Some("invalid authentication code"),
+        ));
+        Err(self.failed_attempt(&username, client, now, ApiError::invalid_two_factor_code()))
+    }
+
+    pub fn logout(&self, token: &str, client: &str) {
+        if let Some(record) = self.sessions.lock().unwrap().revoke(token) {
+            self.audit(AuditEntry {
+                client: Some(client.to_string()),
+                ..AuditEntry::new(record.username, AuditEvent::Logout, AuditOutcome::Success)
+            });
+        }
+    }
+
+    /// Resolves a session token to an enabled account, optionally counting
+    /// the lookup as activity for the idle timeout.
+    pub fn session_user(&self, token: &str, touch: bool) -> Option<CurrentUser> {
+        let now = Utc::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        let record = if touch {
+            sessions.touch(token, now)?
+        } else {
+            sessions.get(token, now)?
+        };
+
+        // Accounts disabled since login lose their sessions
+        match self.users.lock().unwrap().get(&record.username) {
+            Some(user) if !user.disabled => Some(CurrentUser {
+                role: user.role,
+                session: record,
+            }),
+            _ => {
+                sessions.revoke(token);
+                None
+            }
+        }
+    }
+
+    /// Resolves a session for a call that counts as user activity and checks
+    /// that the account holds at least `required`.
+    pub fn require_role(&self, token: &str, required: Role) -> Result<CurrentUser, ApiError> {
+        let user = self
+            .session_user(token, true)
+            .ok_or_else(ApiError::session_expired)?;
+
+        if !user.role.includes(required) {
+            log::warn!(
+                "{} ({:?}) denied an action requiring {:?}",
+                user.username(),
+                user.role,
+                required
+            );
+            self.audit(AuditEntry {
+                detail: Some(format!("requires {}", required.label())),
+                ..AuditEntry::new(
+                    user.username(),
+                    AuditEvent::AccessDenied,
+                    AuditOutcome::Failure,
+                )
+            });
+            return Err(ApiError::forbidden());
+        }
+
+        Ok(user)
+    }
+
+    /// Turns off two-factor for `username`. Users need a current code to turn
+    /// off their own, checked against the login counters so a stolen session
+    /// cannot guess it; admins can turn it off for anyone who has lost their
+    /// device.
+    pub fn disable_two_factor(
+        &self,
+        actor: &CurrentUser,
+        username: &str,
+        code: Option<&str>,
+        client: &str,
+        now: DateTime<Utc>,
+    ) -> Result<UserSummary, ApiError> {
+        if username == actor.username() {
+            if let Some(denial) = self.throttled(username, client, now) {
+                log::warn!(
+                    "Throttled turning off two-factor for {} from {}",
+                    username,
+                    client
+                );
+                self.audit(denied_entry(
+                    username,
+                    AuditOutcome::Throttled,
+                    client,
+                    "turning off two-factor",
+                ));
+                return Err(denial.into());
+            }
+
+            let code = code.unwrap_or_default();
+            let verified = self
+                .users
+                .lock()
+                .unwrap()
+                .verify_two_factor(username, code, now)
+                .map_err(store_error)?;
+            if !verified {
+                log::info!(
+                    "Invalid code to turn off two-factor for {} from {}",
+                    username,
+                    client
+                );
+                self.audit(denied_entry(
+                    username,
+                    AuditOutcome::Failure,
+                    client,
+                    "invalid authentication code to turn off two-factor",
+                ));
+                return Err(self.failed_attempt(
+                    username,
+                    client,
+                    now,
+                    ApiError::invalid_two_factor_code(),
+                ));
+            }
+            self.user_attempts.lock().unwrap().record_success(username);
+            self.client_attempts.lock().unwrap().record_success(client);
+        } else if !actor.role.includes(Role::Admin) {
+            log::warn!(
+                "{} ({:?}) denied turning off two-factor for {}",
+                actor.username(),
+                actor.role,
+                username
+            );
+            self.audit(denied_entry(
+                actor.username(),
+                AuditOutcome::Failure,
+                client,
+                &format!("turning off two-factor for {}", username),
+            ));
+            return Err(ApiError::forbidden());
+        }
+
+        let summary = self
+            .users
+            .lock()
+            .unwrap()
+            .disable_totp(username)
+            .map_err(store_error)?;
+        log::info!(
+            "{} disabled two-factor authentication for {}",
+            actor.username(),
+            summary.username
+        );
+        self.audit(AuditEntry {
+            client: Some(client.to_string()),
+            detail: Some(summary.username.clone()),
+            ..AuditEntry::new(
+                actor.username(),
+                AuditEvent::TwoFactorDisabled,
+                AuditOutcome::Success,
+            )
+        });
+        Ok(summary)
+    }
+
+    pub fn user_info(&self, user: &CurrentUser) -> UserInfo {
+        let limits = *self.sessions.lock().unwrap().limits();
+        let remaining = user.session.remaining(&limits, Utc::now());
+
+        UserInfo {
+            username: user.session.username.clone(),
+            logged_in: true,
+            role: Some(user.role),
+            expires_in_secs: Some(remaining.num_seconds().max(0) as u64),
+        }
+    }
+
+    fn start_session(&self, username: &str, client: &str, now: DateTime<Utc>) -> SessionRecord {
+        self.user_attempts.lock().unwrap().record_success(username);
+        self.client_attempts.lock().unwrap().record_success(client);
+        let record = self.sessions.lock().unwrap().create(username, now);
+
+        log::info!("Login successful for {} from {}", username, client);
+        self.audit(login_entry(username, AuditOutcome::Success, client, None));
+        record
+    }
+
+    fn throttled(
+        &self,
+        username: &str,
+        client: &str,
+        now: DateTime<Utc>,
+    ) -> Option<ThrottleDenial> {
+        let user_check = self.user_attempts.lock().unwrap().check(username, now);
+        let client_check = self.client_attempts.lock().unwrap().check(client, now);
+        ThrottleDenial::strictest(user_check.err(), client_check.err())
+    }
+
+    /// Counts a failure against both keys. The error is `fallback` unless
+    /// either key is now blocked.
+    fn failed_attempt(
+        &self,
+        username: &str,
+        client: &str,
+        now: DateTime<Utc>,
+        fallback: ApiError,
+    ) -> ApiError {
+        let user_denial = self
+            .user_attempts
+            .lock()
+            .unwrap()
+            .record_failure(username, now);
+        let client_denial = self
+            .client_attempts
+            .lock()
+            .unwrap()
+            .record_failure(client, now);
+        ThrottleDenial::strictest(user_denial, client_denial).map_or(fallback, ApiError::from)
+    }
+}
+
+/// Internal failures are logged here and reach the caller only as a generic
+/// message.
+pub fn store_error(error: UserStoreError) -> ApiError {
+    if error.is_internal() {
+        log::error!("User store failure: {}", error);
+    }
+    error.into()
+}
+
+fn login_entry(
+    username: &str,
+    outcome: AuditOutcome,
+    client: &str,
+    detail: Option<&str>,
+) -> AuditEntry {
+    AuditEntry {
+        client: Some(client.to_string()),
+        detail: detail.map(str::to_string),
+        ..AuditEntry::new(username, AuditEvent::Login, outcome)
+    }
+}
+
+fn denied_entry(username: &str, outcome: AuditOutcome, client: &str, detail: &str) -> AuditEntry {
+    AuditEntry {
+        client: Some(client.to_string()),
+        detail: Some(detail.to_string()),
+        ..AuditEntry::new(username, AuditEvent::AccessDenied, outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use portal_types::{AuditQuery, ErrorCode};
+
+    fn authenticator() -> (tempfile::TempDir, Authenticator) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut users = UserStore::open(dir.path().join("users.json")).unwrap();
+        users
+            .create_user("analyst", "analyst-password", Role::Analyst)
+            .unwrap();
+        let audit_log = AuditLog::new(dir.path().join("audit.jsonl"));
+        (
+            dir,
+            Authenticator::new(users, SessionLimits::default(), audit_log),
+        )
+    }
+
+    #[test]
+    fn logins_start_sessions_and_are_audited() {
+        let (_dir, auth) = authenticator();
+        let now = Utc::now();
+
+        let Ok(LoginStep::Session(record)) =
+            auth.login("analyst", "analyst-password", "10.0.0.1", now)
+        else {
+            panic!("expected a session");
+        };
+        let user = auth.require_role(&record.token, Role::ReadOnly).unwrap();
+        assert_eq!(user.username(), "analyst");
+        assert_eq!(
+            auth.require_role(&record.token, Role::Admin)
+                .err()
+                .unwrap()
+                .code,
+            ErrorCode::Forbidden
+        );
+
+        auth.logout(&record.token, "10.0.0.1");
+        assert!(auth.session_user(&record.token, false).is_none());
+
+        let events: Vec<_> = auth
+            .audit_log
+            .query(&AuditQuery::default())
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.event, entry.outcome))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (AuditEvent::Logout, AuditOutcome::Success),
+                (AuditEvent::AccessDenied, AuditOutcome::Failure),
+                (AuditEvent::Login, AuditOutcome::Success),
+            ]
+        );
+    }
+
+    #[test]
+    fn turning_off_two_factor_is_throttled_and_audited() {
+        let (_dir, auth) = authenticator();
+        let now = Utc::now();
+        {
+            let mut users = auth.users.lock().unwrap();
+            users
+                .create_user("reader", "reader-password", Role::ReadOnly)
+                .unwrap();
+            let secret = users.begin_totp_enrollment("analyst").unwrap();
+            let key = data_encoding::BASE32_NOPAD
+                .decode(secret.as_bytes())
+                .unwrap();
+            let code = format!(
+                "{:06}",
+                crate::totp::code_at(&key, crate::totp::step_at(now))
+            );
+            users.confirm_totp("analyst", &code, now).unwrap();
+        }
+        let session = |username: &str| CurrentUser {
+            session: auth.sessions.lock().unwrap().create(username, now),
+            role: auth.users.lock().unwrap().get(username).unwrap().role,
+        };
+
+        // Someone else's second factor needs an admin
+        let reader = session("reader");
+        let error = auth
+            .disable_two_factor(&reader, "analyst", None, "10.0.0.1", now)
+            .err()
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::Forbidden);
+
+        // Guessing codes with the analyst's session runs into the lockout
+        let analyst = session("analyst");
+        for _ in 0..ThrottlePolicy::per_user().free_attempts {
+            let error = auth
+                .disable_two_factor(&analyst, "analyst", Some("000000"), "10.0.0.1", now)
+                .err()
+                .unwrap();
+            assert_eq!(error.code, ErrorCode::InvalidTwoFactorCode);
+        }
+        let error = auth
+            .disable_two_factor(&analyst, "analyst", Some("000000"), "10.0.0.1", now)
+            .err()
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::LockedOut);
+        assert!(auth
+            .users
+            .lock()
+            .unwrap()
+            .get("analyst")
+            .unwrap()
+            .two_factor_enabled());
+
+        let denied = auth
+            .audit_log
+            .query(&AuditQuery::default())
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.event == AuditEvent::AccessDenied)
+            .count();
+        assert_eq!(
+            denied,
+            1 + ThrottlePolicy::per_user().free_attempts as usize + 1
+        );
+    }
+
+    #[test]
+    fn repeated_failures_lock_the_account_out() {
+        let (_dir, auth) = authenticator();
+        let now = Utc::now();
+
+        for _ in 0..ThrottlePolicy::per_user().free_attempts {
+            let error = auth
+                .login("analyst", "wrong", "10.0.0.1", now)
+                .err()
+                .unwrap();
+            assert_eq!(error.code, ErrorCode::InvalidCredentials);
+        }
+        let error = auth
+            .login("analyst", "wrong", "10.0.0.1", now)
+            .err()
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::LockedOut);
+
+        // Even the right password waits, from any client
+        let error = auth
+            .login("analyst", "analyst-password", "10.0.0.2", now)
+            .err()
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::LockedOut);
+        assert!(error.retry_after_secs.is_some());
+    }
+}
@@ -0,0 +1,16 @@
+//! Account storage, password handling, the login flow and audit logging shared by the Actix
+//! backend and the Tauri desktop app.
+
+pub mod audit;
+pub mod authenticator;
+pub mod password;
+pub mod session;
+pub mod store;
+pub mod throttle;
+pub mod totp;
+
+pub use audit::AuditLog;
+pub use authenticator::{store_error, Authenticator, CurrentUser, LoginStep};
+pub use session::{SessionLimits, SessionRecord, SessionTable};
+pub use store::{NewPassword, TotpState, UserRecord, UserStore, UserStoreError};
+pub use throttle::{LoginThrottle, ThrottleDenial, ThrottlePolicy};
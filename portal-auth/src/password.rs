@@ -0,0 +1,45 @@
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
+use rand::{distributions::Alphanumeric, Rng};
+use std::sync::OnceLock;
+
+/// Hashes a password with Argon2id, returning the PHC string to store.
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+/// Checks a password against a stored PHC string.
+///
+/// The digest comparison inside `argon2` is constant-time; a malformed stored
+/// hash is treated as a mismatch.
+pub fn verify_password(password: &str, stored_hash: &str) -> bool {
+    match PasswordHash::new(stored_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Burns the same amount of work as a real verification so that unknown
+/// usernames cannot be told apart from wrong passwords by response time.
+pub fn verify_dummy(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    let hash = DUMMY_HASH.get_or_init(|| {
+        hash_password("portal-dummy-password").expect("hashing a constant cannot fail")
+    });
+    let _ = verify_password(password, hash);
+}
+
+/// Generates a random alphanumeric password, used for bootstrap accounts.
+pub fn generate_password(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
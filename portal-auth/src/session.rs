@@ -0,0 +1,209 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use std::collections::HashMap;
+
+/// How long a session may live without activity, and in total.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionLimits {
+    pub idle_timeout: Duration,
+    pub absolute_timeout: Duration,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::minutes(30),
+            absolute_timeout: Duration::hours(8),
+        }
+    }
+}
+
+impl SessionLimits {
+    /// Limits for the short-lived challenge between a correct password and
+    /// the second factor.
+    pub fn two_factor_challenge() -> Self {
+        Self {
+            idle_timeout: Duration::minutes(5),
+            absolute_timeout: Duration::minutes(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub token: String,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl SessionRecord {
+    /// The earlier of the idle and absolute deadlines.
+    pub fn expires_at(&self, limits: &SessionLimits) -> DateTime<Utc> {
+        let idle_deadline = self.last_seen + limits.idle_timeout;
+        let absolute_deadline = self.created_at + limits.absolute_timeout;
+        idle_deadline.min(absolute_deadline)
+    }
+
+    pub fn remaining(&self, limits: &SessionLimits, now: DateTime<Utc>) -> Duration {
+        (self.expires_at(limits) - now).max(Duration::zero())
+    }
+}
+
+/// In-memory table of active sessions keyed by opaque random token.
+///
+/// All methods take the current time so expiry can be tested without sleeping.
+pub struct SessionTable {
+    limits: SessionLimits,
+    sessions: HashMap<String, SessionRecord>,
+}
+
+impl SessionTable {
+    pub fn new(limits: SessionLimits) -> Self {
+        Self {
+            limits,
+            sessions: HashMap::new(),
+        }
+    }
+
+    pub fn limits(&self) -> &SessionLimits {
+        &self.limits
+    }
+
+    pub fn create(&mut self, username: &str, now: DateTime<Utc>) -> SessionRecord {
+        self.purge_expired(now);
+
+        let record = SessionRecord {
+            token: generate_token(),
+            username: username.to_string(),
+            created_at: now,
+            last_seen: now,
+        };
+        self.sessions.insert(record.token.clone(), record.clone());
+        record
+    }
+
+    /// Looks up a live session without counting it as activity.
+    pub fn get(&mut self, token: &str, now: DateTime<Utc>) -> Option<SessionRecord> {
+        let record = self.sessions.get(token)?;
+        if record.expires_at(&self.limits) <= now {
+            self.sessions.remove(token);
+            return None;
+        }
+        Some(record.clone())
+    }
+
+    /// Looks up a live session and resets its idle timer.
+    pub fn touch(&mut self, token: &str, now: DateTime<Utc>) -> Option<SessionRecord> {
+        self.get(token, now)?;
+        let record = self.sessions.get_mut(token)?;
+        record.last_seen = now;
+        Some(record.clone())
+    }
+
+    pub fn revoke(&mut self, token: &str) -> Option<SessionRecord> {
+        self.sessions.remove(token)
+    }
+
+    /// Ends every session of a user, e.g. after a password reset.
+    pub fn revoke_user(&mut self, username: &str) -> usize {
+        let before = self.sessions.len();
+        self.sessions
+            .retain(|_, record| record.username != username);
+        before - self.sessions.len()
+    }
+
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) {
+        let limits = self.limits;
+        self.sessions
+            .retain(|_, record| record.expires_at(&limits) > now);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn tokens_are_unique_and_opaque() {
+        let mut table = SessionTable::new(SessionLimits::default());
+        let a = table.create("admin", start());
+        let b = table.create("admin", start());
+
+        assert_ne!(a.token, b.token);
+        assert_eq!(a.token.len(), 64);
+        assert!(!a.token.contains("admin"));
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn idle_sessions_expire_unless_touched() {
+        let mut table = SessionTable::new(SessionLimits::default());
+        let session = table.create("admin", start());
+
+        let later = start() + Duration::minutes(20);
+        assert!(table.touch(&session.token, later).is_some());
+
+        // Idle timer restarted at `later`, so 40 minutes in is still fine
+        assert!(table
+            .get(&session.token, start() + Duration::minutes(40))
+            .is_some());
+        assert!(table
+            .get(&session.token, later + Duration::minutes(30))
+            .is_none());
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn absolute_timeout_wins_over_activity() {
+        let limits = SessionLimits {
+            idle_timeout: Duration::minutes(30),
+            absolute_timeout: Duration::hours(1),
+        };
+        let mut table = SessionTable::new(limits);
+        let session = table.create("admin", start());
+
+        let mut now = start();
+        for _ in 0..2 {
+            now += Duration::minutes(25);
+            table.touch(&session.token, now).unwrap();
+        }
+
+        let record = table.get(&session.token, now).unwrap();
+        assert_eq!(record.expires_at(&limits), start() + Duration::hours(1));
+        assert_eq!(record.remaining(&limits, now), Duration::minutes(10));
+        assert!(table
+            .get(&session.token, start() + Duration::hours(1))
+            .is_none());
+    }
+
+    #[test]
+    fn revoke_user_ends_all_of_their_sessions() {
+        let mut table = SessionTable::new(SessionLimits::default());
+        table.create("admin", start());
+        table.create("admin", start());
+        let other = table.create("analyst", start());
+
+        assert_eq!(table.revoke_user("admin"), 2);
+        assert!(table.get(&other.token, start()).is_some());
+    }
+}
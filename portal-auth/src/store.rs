@@ -0,0 +1,702 @@
+use chrono::{DateTime, Utc};
+use portal_fs::write_json_atomic;
+use portal_types::{ApiError, ErrorCode, Role, UserSummary};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::password::{hash_password, verify_dummy, verify_password};
+use crate::totp;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UserStoreError {
+    #[error("user store I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("user store file is malformed: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("failed to hash password")]
+    Hash,
+    #[error("user '{0}' already exists")]
+    UserExists(String),
+    #[error("user '{0}' not found")]
+    UserNotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl UserStoreError {
+    /// Whether the failure is on the server's side rather than the request's.
+    pub fn is_internal(&self) -> bool {
+        matches!(
+            self,
+            UserStoreError::Io(_) | UserStoreError::Format(_) | UserStoreError::Hash
+        )
+    }
+}
+
+// Internal details stay out of the message; callers log them first
+impl From<UserStoreError> for ApiError {
+    fn from(error: UserStoreError) -> Self {
+        let code = match error {
+            UserStoreError::UserExists(_) => ErrorCode::Conflict,
+            UserStoreError::UserNotFound(_) => ErrorCode::NotFound,
+            UserStoreError::Invalid(_) => ErrorCode::Validation,
+            UserStoreError::Io(_) | UserStoreError::Format(_) | UserStoreError::Hash => {
+                return ApiError::internal("Could not update user store")
+            }
+        };
+        ApiError::new(code, error.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub username: String,
+    pub password_hash: String,
+    #[serde(default = "legacy_role")]
+    pub role: Role,
+    #[serde(default)]
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub password_changed_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<TotpState>,
+}
+
+/// An account's authenticator secret. Until `confirmed` is set the secret is
+/// only pending enrollment and login does not ask for a code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpState {
+    pub secret: String,
+    #[serde(default)]
+    pub confirmed: bool,
+    /// The last step a code was accepted for, so it cannot be reused
+    #[serde(default)]
+    pub last_used_step: Option<u64>,
+    /// SHA-256 hashes of the unused recovery codes
+    #[serde(default)]
+    pub recovery_codes: Vec<String>,
+}
+
+// Accounts saved before roles existed had full access, so they keep it
+fn legacy_role() -> Role {
+    Role::Admin
+}
+
+impl UserRecord {
+    pub fn two_factor_enabled(&self) -> bool {
+        self.totp.as_ref().is_some_and(|state| state.confirmed)
+    }
+
+    pub fn summary(&self) -> UserSummary {
+        UserSummary {
+            username: self.username.clone(),
+            role: self.role,
+            disabled: self.disabled,
+            two_factor_enabled: self.two_factor_enabled(),
+            created_at: self.created_at,
+            password_changed_at: self.password_changed_at,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct StoreFile {
+    users: Vec<UserRecord>,
+}
+
+/// User accounts persisted as JSON, with Argon2id password hashes.
+///
+/// Every mutation is written through to disk before returning, and only
+/// takes effect once it has been.
+pub struct UserStore {
+    path: PathBuf,
+    users: BTreeMap<String, UserRecord>,
+}
+
+impl UserStore {
+    /// Loads the store at `path`, starting empty if the file does not exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, UserStoreError> {
+        let path = path.into();
+        let file = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<StoreFile>(&contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => StoreFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let users = file
+            .users
+            .into_iter()
+            .map(|user| (user.username.clone(), user))
+            .collect();
+
+        Ok(Self { path, users })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    pub fn get(&self, username: &str) -> Option<&UserRecord> {
+        self.users.get(username)
+    }
+
+    pub fn list(&self) -> Vec<UserSummary> {
+        self.users.values().map(UserRecord::summary).collect()
+    }
+
+    /// Creates the given admin account if the store has no users at all.
+    ///
+    /// When no password is supplied a random one is generated and returned so
+    /// the caller can show it once.
+    pub fn bootstrap(
+        &mut self,
+        username: &str,
+        password: Option<String>,
+    ) -> Result<Option<String>, UserStoreError> {
+        if !self.is_empty() {
+            return Ok(None);
+        }
+
+        let generated = password.is_none();
+        let password = password.unwrap_or_else(|| crate::password::generate_password(20));
+        self.create_user(username, &password, Role::Admin)?;
+
+        Ok(generated.then_some(password))
+    }
+
+    /// Returns the account if the password matches and the account is enabled.
+    pub fn verify(&self, username: &str, password: &str) -> Option<&UserRecord> {
+        let user = self.users.get(username);
+        check_password(user, password).then_some(user).flatten()
+    }
+
+    pub fn create_user(
+        &mut self,
+        username: &str,
+        password: &str,
+        role: Role,
+    ) -> Result<UserSummary, UserStoreError> {
+        self.create_user_hashed(username, NewPassword::hash(password)?, role)
+    }
+
+    /// Creates an account with a password hashed beforehand, so a server can
+    /// hash without holding the store.
+    pub fn create_user_hashed(
+        &mut self,
+        username: &str,
+        password: NewPassword,
+        role: Role,
+    ) -> Result<UserSummary, UserStoreError> {
+        let username = username.trim();
+        if username.is_empty() {
+            return Err(UserStoreError::Invalid(
+                "Username must not be empty".to_string(),
+            ));
+        }
+        if self.users.contains_key(username) {
+            return Err(UserStoreError::UserExists(username.to_string()));
+        }
+
+        let now = Utc::now();
+        let user = UserRecord {
+            username: username.to_string(),
+            password_hash: password.0,
+            role,
+            disabled: false,
+            created_at: now,
+            password_changed_at: now,
+            totp: None,
+        };
+        let summary = user.summary();
+
+        let mut users = self.users.clone();
+        users.insert(user.username.clone(), user);
+        self.replace(users)?;
+        Ok(summary)
+    }
+
+    pub fn set_disabled(
+        &mut self,
+        username: &str,
+        disabled: bool,
+    ) -> Result<UserSummary, UserStoreError> {
+        if disabled && self.is_last_active_admin(username) {
+            return Err(UserStoreError::Invalid(
+                "Cannot disable the last active admin".to_string(),
+            ));
+        }
+
+        self.change_user(username, |user| {
+            user.disabled = disabled;
+            Ok(user.summary())
+        })
+    }
+
+    pub fn set_role(&mut self, username: &str, role: Role) -> Result<UserSummary, UserStoreError> {
+        if role != Role::Admin && self.is_last_active_admin(username) {
+            return Err(UserStoreError::Invalid(
+                "Cannot remove the role of the last active admin".to_string(),
+            ));
+        }
+
+        self.change_user(username, |user| {
+            user.role = role;
+            Ok(user.summary())
+        })
+    }
+
+    pub fn reset_password(
+        &mut self,
+        username: &str,
+        new_password: &str,
+    ) -> Result<UserSummary, UserStoreError> {
+        self.reset_password_hashed(username, NewPassword::hash(new_password)?)
+    }
+
+    /// Replaces a password with one hashed beforehand, so a server can hash
+    /// without holding the store.
+    pub fn reset_password_hashed(
+        &mut self,
+        username: &str,
+        new_password: NewPassword,
+    ) -> Result<UserSummary, UserStoreError> {
+        self.change_user(username, |user| {
+            user.password_hash = new_password.0;
+            user.password_changed_at = Utc::now();
+            Ok(user.summary())
+        })
+    }
+
+    /// Starts (or restarts) enrollment with a fresh secret, returned for the
+    /// QR code. Refused while two-factor is already on.
+    pub fn begin_totp_enrollment(&mut self, username: &str) -> Result<String, UserStoreError> {
+        self.change_user(username, |user| {
+            if user.two_factor_enabled() {
+                return Err(UserStoreError::Invalid(
+                    "Two-factor authentication is already enabled".to_string(),
+                ));
+            }
+
+            let secret = totp::generate_secret();
+            user.totp = Some(TotpState {
+                secret: secret.clone(),
+                confirmed: false,
+                last_used_step: None,
+                recovery_codes: Vec::new(),
+            });
+            Ok(secret)
+        })
+    }
+
+    /// Turns two-factor on once the user proves their app produces valid
+    /// codes, returning recovery codes to show once.
+    pub fn confirm_totp(
+        &mut self,
+        username: &str,
+        code: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<String>, UserStoreError> {
+        self.change_user(username, |user| {
+            let state = match user.totp.as_mut() {
+                Some(state) if !state.confirmed => state,
+                _ => {
+                    return Err(UserStoreError::Invalid(
+                        "No two-factor enrollment is pending".to_string(),
+                    ))
+                }
+            };
+
+            let step = totp::verify_code(&state.secret, code, now, None).ok_or_else(|| {
+                UserStoreError::Invalid("Invalid authentication code".to_string())
+            })?;
+            let codes = totp::generate_recovery_codes();
+            state.confirmed = true;
+            state.last_used_step = Some(step);
+            state.recovery_codes = codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+            Ok(codes)
+        })
+    }
+
+    pub fn disable_totp(&mut self, username: &str) -> Result<UserSummary, UserStoreError> {
+        self.change_user(username, |user| {
+            user.totp = None;
+            Ok(user.summary())
+        })
+    }
+
+    /// Checks an authenticator code, falling back to a recovery code which
+    /// is consumed on use. Accounts without two-factor never match.
+    pub fn verify_two_factor(
+        &mut self,
+        username: &str,
+        code: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool, UserStoreError> {
+        let user = self
+            .users
+            .get(username)
+            .ok_or_else(|| UserStoreError::UserNotFound(username.to_string()))?;
+        let state = match user.totp.as_ref() {
+            Some(state) if state.confirmed => state,
+            _ => return Ok(false),
+        };
+
+        let mut state = state.clone();
+        if let Some(step) = totp::verify_code(&state.secret, code, now, state.last_used_step) {
+            state.last_used_step = Some(step);
+        } else {
+            let hash = totp::hash_recovery_code(code);
+            let Some(index) = state.recovery_codes.iter().position(|c| *c == hash) else {
+                return Ok(false);
+            };
+            state.recovery_codes.remove(index);
+        }
+
+        self.change_user(username, |user| {
+            user.totp = Some(state);
+            Ok(true)
+        })
+    }
+
+    // Edits a copy of the account, which replaces the loaded one only once it
+    // is on disk, so a failed write never leaves a change live in memory
+    fn change_user<T>(
+        &mut self,
+        username: &str,
+        change: impl FnOnce(&mut UserRecord) -> Result<T, UserStoreError>,
+    ) -> Result<T, UserStoreError> {
+        let mut user = self
+            .users
+            .get(username)
+            .cloned()
+            .ok_or_else(|| UserStoreError::UserNotFound(username.to_string()))?;
+        let result = change(&mut user)?;
+
+        let mut users = self.users.clone();
+        users.insert(user.username.clone(), user);
+        self.replace(users)?;
+        Ok(result)
+    }
+
+    fn replace(&mut self, users: BTreeMap<String, UserRecord>) -> Result<(), UserStoreError> {
+        self.save(&users)?;
+        self.users = users;
+        Ok(())
+    }
+
+    // Someone must always be able to manage accounts
+    fn is_last_active_admin(&self, username: &str) -> bool {
+        let mut active = self
+            .users
+            .values()
+            .filter(|user| !user.disabled && user.role == Role::Admin);
+        matches!(
+            (active.next(), active.next()),
+            (Some(only), None) if only.username == username
+        )
+    }
+
+    fn save(&self, users: &BTreeMap<String, UserRecord>) -> Result<(), UserStoreError> {
+        let file = StoreFile {
+            users: users.values().cloned().collect(),
+        };
+        write_json_atomic(&self.path, &file)?;
+        Ok(())
+    }
+}
+
+/// A password that meets the length rule, hashed and ready to store.
+pub struct NewPassword(String);
+
+impl NewPassword {
+    /// Checks and hashes `password`. Argon2id is slow by design, so servers
+    /// call this off their request threads and before locking the store.
+    pub fn hash(password: &str) -> Result<Self, UserStoreError> {
+        validate_password(password)?;
+        hash_password(password)
+            .map(Self)
+            .map_err(|_| UserStoreError::Hash)
+    }
+}
+
+/// Whether `password` opens `user`, an enabled account. A missing account
+/// takes as long as a wrong password so usernames cannot be probed by
+/// timing. Servers run this on a copy of the record rather than while
+/// holding the store.
+pub fn check_password(user: Option<&UserRecord>, password: &str) -> bool {
+    match user {
+        Some(user) => verify_password(password, &user.password_hash) && !user.disabled,
+        None => {
+            verify_dummy(password);
+            false
+        }
+    }
+}
+
+fn validate_password(password: &str) -> Result<(), UserStoreError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(UserStoreError::Invalid(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (tempfile::TempDir, UserStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UserStore::open(dir.path().join("users.json")).unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn created_users_persist_with_hashed_passwords() {
+        let (dir, mut store) = temp_store();
+        store
+            .create_user("analyst", "correct horse", Role::Analyst)
+            .unwrap();
+
+        let contents = fs::read_to_string(dir.path().join("users.json")).unwrap();
+        assert!(!contents.contains("correct horse"));
+        assert!(contents.contains("$argon2id$"));
+
+        let reopened = UserStore::open(store.path()).unwrap();
+        assert!(reopened.verify("analyst", "correct horse").is_some());
+        assert!(reopened.verify("analyst", "wrong password").is_none());
+        assert!(reopened.verify("nobody", "correct horse").is_none());
+    }
+
+    #[test]
+    fn disabled_users_cannot_log_in() {
+        let (_dir, mut store) = temp_store();
+        store
+            .create_user("admin", "admin-password", Role::Admin)
+            .unwrap();
+        store
+            .create_user("analyst", "analyst-password", Role::Analyst)
+            .unwrap();
+
+        store.set_disabled("analyst", true).unwrap();
+        assert!(store.verify("analyst", "analyst-password").is_none());
+
+        store.set_disabled("analyst", false).unwrap();
+        assert!(store.verify("analyst", "analyst-password").is_some());
+    }
+
+    #[test]
+    fn last_active_admin_cannot_be_disabled_or_demoted() {
+        let (_dir, mut store) = temp_store();
+        store
+            .create_user("admin", "admin-password", Role::Admin)
+            .unwrap();
+        store
+            .create_user("analyst", "analyst-password", Role::Analyst)
+            .unwrap();
+
+        assert!(matches!(
+            store.set_disabled("admin", true),
+            Err(UserStoreError::Invalid(_))
+        ));
+        assert!(matches!(
+            store.set_role("admin", Role::Analyst),
+            Err(UserStoreError::Invalid(_))
+        ));
+
+        store.set_role("analyst", Role::Admin).unwrap();
+        store.set_role("admin", Role::ReadOnly).unwrap();
+        assert_eq!(store.get("admin").unwrap().role, Role::ReadOnly);
+    }
+
+    #[test]
+    fn users_saved_before_roles_become_admins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        let legacy = serde_json::json!({
+            "users": [{
+                "username": "admin",
+                "password_hash": hash_password("admin-password").unwrap(),
+                "disabled": false,
+                "created_at": "2025-01-01T00:00:00Z",
+                "password_changed_at": "2025-01-01T00:00:00Z"
+            }]
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let store = UserStore::open(&path).unwrap();
+        assert_eq!(store.get("admin").unwrap().role, Role::Admin);
+    }
+
+    #[test]
+    fn reset_password_replaces_the_old_one() {
+        let (_dir, mut store) = temp_store();
+        store
+            .create_user("analyst", "first-password", Role::Analyst)
+            .unwrap();
+        store.reset_password("analyst", "second-password").unwrap();
+
+        assert!(store.verify("analyst", "first-password").is_none());
+        assert!(store.verify("analyst", "second-password").is_some());
+    }
+
+    #[test]
+    fn rejects_duplicates_and_short_passwords() {
+        let (_dir, mut store) = temp_store();
+        store
+            .create_user("analyst", "long-enough", Role::Analyst)
+            .unwrap();
+
+        assert!(matches!(
+            store.create_user("analyst", "long-enough", Role::Analyst),
+            Err(UserStoreError::UserExists(_))
+        ));
+        assert!(matches!(
+            store.create_user("other", "short", Role::ReadOnly),
+            Err(UserStoreError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn store_errors_map_to_api_codes() {
+        let cases = [
+            (UserStoreError::UserExists("a".into()), ErrorCode::Conflict),
+            (
+                UserStoreError::UserNotFound("a".into()),
+                ErrorCode::NotFound,
+            ),
+            (UserStoreError::Invalid("bad".into()), ErrorCode::Validation),
+            (UserStoreError::Hash, ErrorCode::Internal),
+        ];
+        for (error, code) in cases {
+            assert_eq!(ApiError::from(error).code, code);
+        }
+
+        let io = UserStoreError::Io(io::Error::other("disk full at /secret/path"));
+        assert!(io.is_internal());
+        assert!(!ApiError::from(io).message.contains("/secret/path"));
+    }
+
+    #[test]
+    fn two_factor_enrollment_and_verification() {
+        use chrono::TimeZone;
+        use data_encoding::BASE32_NOPAD;
+
+        let (_dir, mut store) = temp_store();
+        store
+            .create_user("analyst", "analyst-password", Role::Analyst)
+            .unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let code_for = |secret: &str, at: DateTime<Utc>| {
+            let key = BASE32_NOPAD.decode(secret.as_bytes()).unwrap();
+            format!("{:06}", totp::code_at(&key, totp::step_at(at)))
+        };
+
+        let secret = store.begin_totp_enrollment("analyst").unwrap();
+        assert!(!store.get("analyst").unwrap().two_factor_enabled());
+        assert!(store.confirm_totp("analyst", "000000x", now).is_err());
+
+        let recovery = store
+            .confirm_totp("analyst", &code_for(&secret, now), now)
+            .unwrap();
+        assert!(store.get("analyst").unwrap().summary().two_factor_enabled);
+        assert!(store.begin_totp_enrollment("analyst").is_err());
+
+        // The enrollment code cannot be replayed at login
+        assert!(!store
+            .verify_two_factor("analyst", &code_for(&secret, now), now)
+            .unwrap());
+        let later = now + chrono::Duration::seconds(60);
+        assert!(store
+            .verify_two_factor("analyst", &code_for(&secret, later), later)
+            .unwrap());
+
+        // Recovery codes work once each and survive a reload
+        let reopened_codes = UserStore::open(store.path()).unwrap();
+        assert_eq!(
+            reopened_codes
+                .get("analyst")
+                .unwrap()
+                .totp
+                .as_ref()
+                .unwrap()
+                .recovery_codes
+                .len(),
+            totp::RECOVERY_CODE_COUNT
+        );
+        assert!(store
+            .verify_two_factor("analyst", &recovery[0], later)
+            .unwrap());
+        assert!(!store
+            .verify_two_factor("analyst", &recovery[0], later)
+            .unwrap());
+
+        store.disable_totp("analyst").unwrap();
+        assert!(!store.get("analyst").unwrap().two_factor_enabled());
+        assert!(!store
+            .verify_two_factor("analyst", &recovery[1], later)
+            .unwrap());
+    }
+
+    #[test]
+    fn failed_writes_leave_users_unchanged() {
+        let (dir, mut store) = temp_store();
+        store
+            .create_user("admin", "admin-password", Role::Admin)
+            .unwrap();
+        store
+            .create_user("analyst", "analyst-password", Role::Analyst)
+            .unwrap();
+
+        // A directory in the way of the temporary file makes every save fail
+        fs::create_dir(dir.path().join("users.json.tmp")).unwrap();
+
+        assert!(store
+            .set_disabled("analyst", true)
+            .unwrap_err()
+            .is_internal());
+        assert!(store
+            .reset_password("analyst", "another-password")
+            .unwrap_err()
+            .is_internal());
+        assert!(store
+            .set_role("analyst", Role::Admin)
+            .unwrap_err()
+            .is_internal());
+        assert!(store
+            .create_user("other", "other-password", Role::ReadOnly)
+            .is_err());
+        assert!(store.begin_totp_enrollment("analyst").is_err());
+
+        let analyst = store.get("analyst").unwrap();
+        assert!(!analyst.disabled && analyst.role == Role::Analyst && analyst.totp.is_none());
+        assert!(store.verify("analyst", "analyst-password").is_some());
+        assert!(store.get("other").is_none());
+    }
+
+    #[test]
+    fn bootstrap_only_runs_on_an_empty_store() {
+        let (_dir, mut store) = temp_store();
+
+        let generated = store.bootstrap("admin", None).unwrap().unwrap();
+        assert!(store.verify("admin", &generated).is_some());
+        assert_eq!(store.get("admin").unwrap().role, Role::Admin);
+
+        assert!(store
+            .bootstrap("other", Some("whatever-pass".to_string()))
+            .unwrap()
+            .is_none());
+        assert!(store.get("other").is_none());
+    }
+}
@@ -0,0 +1,333 @@
+use chrono::{DateTime, Duration, Utc};
+use portal_types::{ApiError, ErrorCode};
+use std::collections::HashMap;
+
+/// How quickly failed logins for one key (a username or a client) are slowed
+/// down and then locked out.
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottlePolicy {
+    /// Failures allowed before any delay applies
+    pub free_attempts: u32,
+    /// Delay after the first throttled failure, doubled for each one after
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Failures after which the key is locked out entirely
+    pub lockout_threshold: u32,
+    pub lockout_duration: Duration,
+    /// Failures older than this are forgotten
+    pub reset_after: Duration,
+}
+
+impl ThrottlePolicy {
+    pub fn per_user() -> Self {
+        Self {
+            free_attempts: 3,
+            base_delay: Duration::seconds(2),
+            max_delay: Duration::minutes(1),
+            lockout_threshold: 5,
+            lockout_duration: Duration::minutes(15),
+            reset_after: Duration::hours(1),
+        }
+    }
+
+    /// Looser than per-user, since an office may share one address
+    pub fn per_client() -> Self {
+        Self {
+            free_attempts: 10,
+            base_delay: Duration::seconds(1),
+            max_delay: Duration::minutes(1),
+            lockout_threshold: 30,
+            lockout_duration: Duration::minutes(15),
+            reset_after: Duration::hours(1),
+        }
+    }
+
+    fn delay_after(&self, failures: u32) -> Duration {
+        if failures <= self.free_attempts {
+            return Duration::zero();
+        }
+        let exponent = (failures - self.free_attempts - 1).min(16);
+        (self.base_delay * 2i32.pow(exponent)).min(self.max_delay)
+    }
+}
+
+/// Why a login attempt was refused before the password was checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottleDenial {
+    pub retry_after: Duration,
+    pub locked: bool,
+}
+
+impl ThrottleDenial {
+    /// Whole seconds to wait, rounded up so clients never retry too early.
+    pub fn retry_after_secs(&self) -> u64 {
+        let millis = self.retry_after.num_milliseconds().max(0) as u64;
+        millis.div_ceil(1000)
+    }
+
+    pub fn message(&self) -> String {
+        let secs = self.retry_after_secs();
+        let wait = if secs > 90 {
+            let minutes = secs.div_ceil(60);
+            format!("{} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+        } else {
+            format!("{} second{}", secs, if secs == 1 { "" } else { "s" })
+        };
+
+        if self.locked {
+            format!(
+                "Too many failed attempts. Login is locked, try again in {}.",
+                wait
+            )
+        } else {
+            format!("Too many failed attempts. Try again in {}.", wait)
+        }
+    }
+
+    /// The stricter of two optional denials, used when a login is checked
+    /// against both the username and the client.
+    pub fn strictest(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) if (b.locked, b.retry_after) > (a.locked, a.retry_after) => Some(b),
+            (Some(a), _) => Some(a),
+            (None, b) => b,
+        }
+    }
+}
+
+impl From<ThrottleDenial> for ApiError {
+    fn from(denial: ThrottleDenial) -> Self {
+        ApiError {
+            retry_after_secs: Some(denial.retry_after_secs()),
+            ..ApiError::new(ErrorCode::LockedOut, denial.message())
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FailureRecord {
+    failures: u32,
+    last_failure: DateTime<Utc>,
+    blocked_until: DateTime<Utc>,
+    locked: bool,
+}
+
+/// Failed-login counters keyed by username or client address.
+pub struct LoginThrottle {
+    policy: ThrottlePolicy,
+    records: HashMap<String, FailureRecord>,
+}
+
+impl LoginThrottle {
+    pub fn new(policy: ThrottlePolicy) -> Self {
+        Self {
+            policy,
+            records: HashMap::new(),
+        }
+    }
+
+    /// Refuses the attempt while the key is backing off or locked out.
+    pub fn check(&self, key: &str, now: DateTime<Utc>) -> Result<(), ThrottleDenial> {
+        match self.records.get(key) {
+            Some(record) if record.blocked_until > now => Err(ThrottleDenial {
+                retry_after: record.blocked_until - now,
+                locked: record.locked,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a failed attempt, returning the resulting denial if the key is
+    /// now blocked.
+    pub fn record_failure(&mut self, key: &str, now: DateTime<Utc>) -> Option<ThrottleDenial> {
+        self.purge_stale(now);
+
+        let policy = self.policy;
+        let record = self
+            .records
+            .entry(key.to_string())
+            .or_insert(FailureRecord {
+                failures: 0,
+                last_failure: now,
+                blocked_until: now,
+                locked: false,
+            });
+
+        // A served lockout starts the key again at its first delay rather
+        // than locking it out on the next mistake
+        if record.locked && record.blocked_until <= now {
+            record.failures = policy.free_attempts;
+            record.locked = false;
+        }
+
+        record.failures += 1;
+        record.last_failure = now;
+        if record.failures >= policy.lockout_threshold {
+            record.locked = true;
+            record.blocked_until = now + policy.lockout_duration;
+        } else {
+            record.blocked_until = now + policy.delay_after(record.failures);
+        }
+
+        (record.blocked_until > now).then_some(ThrottleDenial {
+            retry_after: record.blocked_until - now,
+            locked: record.locked,
+        })
+    }
+
+    pub fn record_success(&mut self, key: &str) {
+        self.records.remove(key);
+    }
+
+    pub fn failures(&self, key: &str) -> u32 {
+        self.records.get(key).map_or(0, |record| record.failures)
+    }
+
+    fn purge_stale(&mut self, now: DateTime<Utc>) {
+        let reset_after = self.policy.reset_after;
+        self.records.retain(|_, record| {
+            record.blocked_until > now || now - record.last_failure < reset_after
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn free_attempts_are_not_delayed() {
+        let mut throttle = LoginThrottle::new(ThrottlePolicy::per_user());
+
+        for _ in 0..3 {
+            assert!(throttle.check("admin", start()).is_ok());
+            assert!(throttle.record_failure("admin", start()).is_none());
+        }
+        assert!(throttle.check("admin", start()).is_ok());
+    }
+
+    #[test]
+    fn delay_doubles_then_locks_out() {
+        let policy = ThrottlePolicy::per_user();
+        let mut throttle = LoginThrottle::new(policy);
+        let mut now = start();
+
+        for _ in 0..3 {
+            throttle.record_failure("admin", now);
+        }
+
+        let fourth = throttle.record_failure("admin", now).unwrap();
+        assert_eq!(fourth.retry_after, Duration::seconds(2));
+        assert!(!fourth.locked);
+        assert_eq!(
+            throttle.check("admin", now + Duration::seconds(1)),
+            Err(ThrottleDenial {
+                retry_after: Duration::seconds(1),
+                locked: false,
+            })
+        );
+
+        now += Duration::seconds(2);
+        assert!(throttle.check("admin", now).is_ok());
+
+        let fifth = throttle.record_failure("admin", now).unwrap();
+        assert!(fifth.locked);
+        assert_eq!(fifth.retry_after, policy.lockout_duration);
+
+        assert!(throttle
+            .check("admin", now + Duration::minutes(14))
+            .is_err());
+        assert!(throttle.check("admin", now + Duration::minutes(15)).is_ok());
+    }
+
+    #[test]
+    fn an_expired_lockout_backs_off_again() {
+        let policy = ThrottlePolicy::per_user();
+        let mut throttle = LoginThrottle::new(policy);
+        for _ in 0..policy.lockout_threshold {
+            throttle.record_failure("admin", start());
+        }
+
+        let later = start() + policy.lockout_duration;
+        assert!(throttle.check("admin", later).is_ok());
+        let next = throttle.record_failure("admin", later).unwrap();
+        assert!(!next.locked);
+        assert_eq!(next.retry_after, policy.base_delay);
+
+        // Failing on through the backoff locks it out again
+        let later = later + policy.base_delay;
+        assert!(throttle.record_failure("admin", later).unwrap().locked);
+    }
+
+    #[test]
+    fn denial_messages_round_up_the_wait() {
+        let backoff = ThrottleDenial {
+            retry_after: Duration::milliseconds(1500),
+            locked: false,
+        };
+        assert_eq!(backoff.retry_after_secs(), 2);
+        assert_eq!(
+            backoff.message(),
+            "Too many failed attempts. Try again in 2 seconds."
+        );
+
+        let locked = ThrottleDenial {
+            retry_after: Duration::minutes(15),
+            locked: true,
+        };
+        assert_eq!(
+            locked.message(),
+            "Too many failed attempts. Login is locked, try again in 15 minutes."
+        );
+        assert_eq!(
+            ThrottleDenial::strictest(Some(backoff), Some(locked)),
+            Some(locked)
+        );
+        assert_eq!(
+            ThrottleDenial::strictest(None, Some(backoff)),
+            Some(backoff)
+        );
+
+        let error = ApiError::from(locked);
+        assert_eq!(error.code, ErrorCode::LockedOut);
+        assert_eq!(error.retry_after_secs, Some(900));
+    }
+
+    #[test]
+    fn success_clears_the_counter() {
+        let mut throttle = LoginThrottle::new(ThrottlePolicy::per_user());
+        throttle.record_failure("admin", start());
+        throttle.record_failure("admin", start());
+
+        throttle.record_success("admin");
+        assert_eq!(throttle.failures("admin"), 0);
+    }
+
+    #[test]
+    fn old_failures_are_forgotten() {
+        let mut throttle = LoginThrottle::new(ThrottlePolicy::per_user());
+        throttle.record_failure("admin", start());
+        throttle.record_failure("admin", start());
+
+        let later = start() + Duration::hours(2);
+        throttle.record_failure("other", later);
+        assert_eq!(throttle.failures("admin"), 0);
+        assert_eq!(throttle.failures("other"), 1);
+    }
+
+    #[test]
+    fn keys_are_tracked_independently() {
+        let mut throttle = LoginThrottle::new(ThrottlePolicy::per_user());
+        for _ in 0..5 {
+            throttle.record_failure("admin", start());
+        }
+
+        assert!(throttle.check("admin", start()).is_err());
+        assert!(throttle.check("analyst", start()).is_ok());
+    }
+}
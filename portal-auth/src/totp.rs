@@ -0,0 +1,218 @@
+//! RFC 6238 time-based one-time passwords (HMAC-SHA1, 6 digits, 30 second
+//! steps) and single-use recovery codes. Everything here runs offline.
+
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use portal_types::TotpEnrollment;
+use qrcode::{render::svg, QrCode};
+use rand::{Rng, RngCore};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+pub const ISSUER: &str = "M&S Portal";
+pub const DIGITS: u32 = 6;
+pub const STEP_SECONDS: i64 = 30;
+/// Codes from one step either side of now are accepted to allow for clock drift
+pub const ALLOWED_SKEW_STEPS: u64 = 1;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// A new random 160-bit secret, base32 encoded as authenticator apps expect.
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; 20];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    BASE32_NOPAD.encode(&secret)
+}
+
+pub fn step_at(now: DateTime<Utc>) -> u64 {
+    (now.timestamp() / STEP_SECONDS).max(0) as u64
+}
+
+/// The code for a given time step (RFC 4226 dynamic truncation).
+pub fn code_at(secret: &[u8], step: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+/// Checks a code against the secret, returning the matched step.
+///
+/// Steps at or before `last_used_step` are rejected so a code cannot be
+/// replayed within its validity window.
+pub fn verify_code(
+    secret_base32: &str,
+    code: &str,
+    now: DateTime<Utc>,
+    last_used_step: Option<u64>,
+) -> Option<u64> {
+    let code = code.trim().replace(' ', "");
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let secret = BASE32_NOPAD.decode(secret_base32.as_bytes()).ok()?;
+
+    let current = step_at(now);
+    let first = current.saturating_sub(ALLOWED_SKEW_STEPS);
+    (first..=current + ALLOWED_SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code_at(&secret, *step) == code)
+}
+
+/// The `otpauth://` URI encoded in the enrollment QR code.
+pub fn otpauth_uri(account: &str, secret_base32: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(ISSUER),
+        percent_encode(account),
+        secret_base32,
+        percent_encode(ISSUER),
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+/// Renders a URI as an SVG QR code, so enrollment needs no network access.
+pub fn qr_svg(uri: &str) -> Option<String> {
+    let code = QrCode::new(uri.as_bytes()).ok()?;
+    Some(
+        code.render::<svg::Color>()
+            .min_dimensions(200, 200)
+            .dark_color(svg::Color("#000000"))
+            .light_color(svg::Color("#ffffff"))
+            .build(),
+    )
+}
+
+/// Everything an authenticator app needs to add the account.
+pub fn enrollment(account: &str, secret_base32: &str) -> TotpEnrollment {
+    let uri = otpauth_uri(account, secret_base32);
+    TotpEnrollment {
+        secret: secret_base32.to_string(),
+        qr_svg: qr_svg(&uri),
+        otpauth_uri: uri,
+    }
+}
+
+/// Fresh recovery codes in `xxxxx-xxxxx` form; only their hashes are stored.
+pub fn generate_recovery_codes() -> Vec<String> {
+    const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::rngs::OsRng;
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+/// Hash of a recovery code, ignoring case, spaces and dashes.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let digest = Sha256::digest(normalized.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // RFC 6238 appendix B, SHA1 rows, truncated to 6 digits
+    #[test]
+    fn matches_rfc_6238_test_vectors() {
+        let secret = b"12345678901234567890";
+        let vectors = [
+            (59, 287082),
+            (1111111109, 81804),
+            (1111111111, 50471),
+            (1234567890, 5924),
+            (2000000000, 279037),
+        ];
+
+        for (time, expected) in vectors {
+            let now = Utc.timestamp_opt(time, 0).unwrap();
+            assert_eq!(code_at(secret, step_at(now)), expected, "t = {}", time);
+        }
+    }
+
+    #[test]
+    fn verify_allows_one_step_of_skew_and_rejects_replays() {
+        let secret = BASE32_NOPAD.encode(b"12345678901234567890");
+        let now = Utc.timestamp_opt(1111111109, 0).unwrap();
+
+        assert_eq!(
+            verify_code(&secret, "081804", now, None),
+            Some(step_at(now))
+        );
+
+        // The same code 30 seconds later still falls inside the window
+        let later = now + chrono::Duration::seconds(30);
+        assert!(verify_code(&secret, "081 804", later, None).is_some());
+        assert!(verify_code(&secret, "081804", later, Some(step_at(now))).is_none());
+
+        let much_later = now + chrono::Duration::seconds(90);
+        assert!(verify_code(&secret, "081804", much_later, None).is_none());
+
+        assert!(verify_code(&secret, "12345", now, None).is_none());
+        assert!(verify_code(&secret, "abcdef", now, None).is_none());
+    }
+
+    #[test]
+    fn otpauth_uri_is_encoded_for_authenticator_apps() {
+        let uri = otpauth_uri("manetas & stevens", "JBSWY3DPEHPK3PXP");
+        assert_eq!(
+            uri,
+            "otpauth://totp/M%26S%20Portal:manetas%20%26%20stevens?secret=JBSWY3DPEHPK3PXP\
+             &issuer=M%26S%20Portal&algorithm=SHA1&digits=6&period=30"
+        );
+        assert!(qr_svg(&uri).unwrap().starts_with("<?xml"));
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_hash_loosely() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        let unique: std::collections::HashSet<_> = codes.iter().collect();
+        assert_eq!(unique.len(), codes.len());
+
+        let code = &codes[0];
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.to_uppercase())
+        );
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.replace('-', " "))
+        );
+        assert_ne!(hash_recovery_code(code), hash_recovery_code(&codes[1]));
+    }
+}
@@ -0,0 +1,77 @@
+//! File writing shared by the stores of the backend and the desktop app.
+
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `value` as pretty JSON to `path`, creating its directory. The
+/// JSON goes to a sibling `.tmp` file which is synced to disk and renamed
+/// over `path`, and the directory is synced after the rename, so a crash
+/// leaves either the old file or the new one, never a half-written one.
+pub fn write_json_atomic(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let tmp_path = tmp_path(path);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&serde_json::to_vec_pretty(value)?)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp_path, path)?;
+    sync_dir(dir)
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+// The rename only survives a crash once the directory entry is on disk.
+// Windows cannot open directories, and commits renames with the file itself.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replaces_the_file_and_leaves_no_temporary_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.json");
+
+        write_json_atomic(&path, &json!({ "version": 1 })).unwrap();
+        write_json_atomic(&path, &json!({ "version": 2 })).unwrap();
+        let written: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(written["version"], 2);
+        assert!(!dir.path().join("nested").join("state.json.tmp").exists());
+    }
+
+    #[test]
+    fn a_failed_write_keeps_the_old_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write_json_atomic(&path, &json!({ "version": 1 })).unwrap();
+
+        // A directory where the temporary file would go
+        fs::create_dir_all(dir.path().join("state.json.tmp")).unwrap();
+        assert!(write_json_atomic(&path, &json!({ "version": 2 })).is_err());
+        let kept: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(kept["version"], 1);
+    }
+}
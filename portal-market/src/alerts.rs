@@ -0,0 +1,412 @@
+use chrono::{DateTime, Duration, Utc};
+use portal_fs::write_json_atomic;
+use portal_types::alerts::default_rules;
+use portal_types::risk_premiums::history;
+use portal_types::{
+    Alert, AlertRule, AlertsResponse, ApiError, EarningsSource, FredSeries, Observation,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::fred::FredService;
+
+/// Older alerts are dropped beyond this many.
+const MAX_ALERTS: usize = 500;
+const MAX_NAME_LEN: usize = 100;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct AlertFile {
+    rules: Vec<AlertRule>,
+    /// Oldest first
+    #[serde(default)]
+    alerts: Vec<Alert>,
+    /// Rules that held at the last check. A rule alerts when it starts to
+    /// hold and not again until it has stopped.
+    #[serde(default)]
+    holding: BTreeSet<u64>,
+    #[serde(default)]
+    checked_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    last_alert_id: u64,
+}
+
+/// Alert rules and the alerts they raised, persisted as one JSON file.
+pub struct AlertStore {
+    path: PathBuf,
+    file: AlertFile,
+}
+
+impl AlertStore {
+    /// Loads the file, or starts with the default rules if there is none.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => AlertFile {
+                rules: default_rules(),
+                alerts: Vec::new(),
+                holding: BTreeSet::new(),
+                checked_at: None,
+                last_alert_id: 0,
+            },
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path, file })
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.file.rules
+    }
+
+    /// Replaces every rule. New rules (id 0) are given an id.
+    pub fn set_rules(&mut self, mut rules: Vec<AlertRule>) -> Result<&[AlertRule], ApiError> {
+        let mut ids = BTreeSet::new();
+        for rule in &mut rules {
+            rule.name = rule.name.trim().to_string();
+            if rule.name.is_empty() || rule.name.len() > MAX_NAME_LEN {
+                return Err(ApiError::validation(format!(
+                    "Rule names must be 1 to {} characters",
+                    MAX_NAME_LEN
+                )));
+            }
+            rule.condition
+                .validate()
+                .map_err(|e| ApiError::validation(format!("{}: {}", rule.name, e)))?;
+            if rule.id != 0 && !ids.insert(rule.id) {
+                return Err(ApiError::validation(format!(
+                    "Duplicate rule id {}",
+                    rule.id
+                )));
+            }
+        }
+
+        // Never reuse the id of a deleted rule, its alerts still refer to it
+        let mut next_id = self
+            .file
+            .rules
+            .iter()
+            .map(|rule| rule.id)
+            .chain(ids.iter().copied())
+            .chain(self.file.alerts.iter().map(|alert| alert.rule_id))
+            .max()
+            .unwrap_or(0);
+        for rule in rules.iter_mut().filter(|rule| rule.id == 0) {
+            next_id += 1;
+            rule.id = next_id;
+        }
+
+        let enabled: BTreeSet<u64> = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .map(|rule| rule.id)
+            .collect();
+        let mut file = AlertFile {
+            rules,
+            ..self.file.clone()
+        };
+        file.holding.retain(|id| enabled.contains(id));
+
+        if let Err(e) = self.replace(file) {
+            log::error!("Could not write alert rules: {}", e);
+            return Err(ApiError::internal("Could not save the alert rules"));
+        }
+        Ok(&self.file.rules)
+    }
+
+    pub fn alerts(&self) -> AlertsResponse {
+        AlertsResponse {
+            alerts: self.file.alerts.iter().rev().cloned().collect(),
+            unacknowledged: self
+                .file
+                .alerts
+                .iter()
+                .filter(|alert| !alert.acknowledged)
+                .count(),
+            checked_at: self.file.checked_at,
+        }
+    }
+
+    /// Acknowledges the given alerts, or all of them when `ids` is empty.
+    pub fn acknowledge(&mut self, ids: &[u64]) -> io::Result<()> {
+        let mut file = self.file.clone();
+        for alert in &mut file.alerts {
+            if ids.is_empty() || ids.contains(&alert.id) {
+                alert.acknowledged = true;
+            }
+        }
+        self.replace(file)
+    }
+
+    /// Records the outcome of checking rules, as (rule id, triggering
+    /// observation), and returns the alerts raised for rules that started to
+    /// hold.
+    pub fn record(
+        &mut self,
+        outcomes: &[(u64, Option<Observation>)],
+        now: DateTime<Utc>,
+    ) -> io::Result<Vec<Alert>> {
+        let mut file = self.file.clone();
+        let mut raised = Vec::new();
+        for &(rule_id, triggered) in outcomes {
+            let Some(rule) = file.rules.iter().find(|rule| rule.id == rule_id) else {
+                continue;
+            };
+            match triggered {
+                Some(obs) if file.holding.insert(rule_id) => {
+                    file.last_alert_id += 1;
+                    raised.push(Alert {
+                        id: file.last_alert_id,
+                        rule_id,
+                        rule_name: rule.name.clone(),
+                        message: rule.describe(),
+                        metric: rule.metric,
+                        date: obs.date,
+                        value: obs.value,
+                        triggered_at: now,
+                        acknowledged: false,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    file.holding.remove(&rule_id);
+                }
+            }
+        }
+
+        file.alerts.extend(raised.iter().cloned());
+        let excess = file.alerts.len().saturating_sub(MAX_ALERTS);
+        file.alerts.drain(..excess);
+        file.checked_at = Some(now);
+        self.replace(file)?;
+        Ok(raised)
+    }
+
+    // Changes are made to a copy, which replaces the loaded file only once it
+    // is on disk. A failed write leaves rules that started to hold not
+    // holding, so their alerts are raised again at the next check.
+    fn replace(&mut self, file: AlertFile) -> io::Result<()> {
+        write_json_atomic(&self.path, &file)?;
+        self.file = file;
+        Ok(())
+    }
+}
+
+/// Checks the alert rules against FRED data.
+pub struct AlertMonitor {
+    pub store: Mutex<AlertStore>,
+}
+
+impl AlertMonitor {
+    pub fn new(store: AlertStore) -> Self {
+        Self {
+            store: Mutex::new(store),
+        }
+    }
+
+    /// Evaluates every enabled rule at the newest data and returns the
+    /// alerts raised. A rule whose metric has no data is skipped and keeps
+    /// its state.
+    pub async fn check(
+        &self,
+        fred: &FredService,
+        earnings: Option<&(dyn EarningsSource + Sync)>,
+        now: DateTime<Utc>,
+    ) -> io::Result<Vec<Alert>> {
+        let rules: Vec<AlertRule> = self
+            .store
+            .lock()
+            .unwrap()
+            .rules()
+            .iter()
+            .filter(|rule| rule.enabled)
+            .cloned()
+            .collect();
+
+        let lookback = rules
+            .iter()
+            .map(|rule| rule.condition.lookback_days())
+            .max()
+            .unwrap_or(0);
+        let mut series: Vec<FredSeries> = rules
+            .iter()
+            .flat_map(|rule| rule.metric.series().iter().copied())
+            .collect();
+        series.sort();
+        series.dedup();
+
+        let start = now.date_naive() - Duration::days(lookback);
+        let loaded = fred.load(&series, Some(start), None, now).await;
+        let earnings = earnings.map(|source| source as &dyn EarningsSource);
+
+        let outcomes: Vec<(u64, Option<Observation>)> = rules
+            .iter()
+            .filter_map(|rule| {
+                let observations = history(rule.metric, &loaded.inputs, earnings);
+                if observations.is_empty() {
+                    log::warn!("Skipping alert rule '{}': no data", rule.name);
+                    return None;
+                }
+                Some((rule.id, rule.condition.check(&observations)))
+            })
+            .collect();
+
+        self.store.lock().unwrap().record(&outcomes, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+    use portal_types::{AlertCondition, MetricId};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 6, 18, 0, 0).unwrap()
+    }
+
+    fn obs(value: f64) -> Option<Observation> {
+        Some(Observation::new(
+            NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(),
+            value,
+        ))
+    }
+
+    fn rule(name: &str) -> AlertRule {
+        AlertRule {
+            id: 0,
+            name: name.to_string(),
+            metric: MetricId::Vix,
+            condition: AlertCondition::Above {
+                threshold: 30.0,
+                days: 1,
+            },
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn starts_with_the_default_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AlertStore::open(dir.path().join("alerts.json")).unwrap();
+        assert_eq!(store.rules(), default_rules());
+        assert!(store.alerts().alerts.is_empty());
+        assert_eq!(store.alerts().checked_at, None);
+    }
+
+    #[test]
+    fn alerts_once_per_episode() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = AlertStore::open(dir.path().join("alerts.json")).unwrap();
+
+        let raised = store.record(&[(3, obs(31.0))], now()).unwrap();
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].rule_name, "VIX above 30");
+        assert_eq!(raised[0].message, "VIX Index above 30.00 for 1 day");
+        assert_eq!(raised[0].value, 31.0);
+
+        // Still above 30: nothing new
+        assert!(store.record(&[(3, obs(33.0))], now()).unwrap().is_empty());
+        // Back below, then above again
+        assert!(store.record(&[(3, None)], now()).unwrap().is_empty());
+        assert_eq!(store.record(&[(3, obs(32.0))], now()).unwrap().len(), 1);
+
+        let alerts = store.alerts();
+        assert_eq!(alerts.unacknowledged, 2);
+        assert_eq!(alerts.checked_at, Some(now()));
+        assert!(alerts.alerts[0].id > alerts.alerts[1].id, "newest first");
+    }
+
+    #[test]
+    fn acknowledges_some_or_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+        let mut store = AlertStore::open(&path).unwrap();
+        store
+            .record(&[(1, obs(-0.2)), (3, obs(31.0))], now())
+            .unwrap();
+
+        let first = store.alerts().alerts[1].id;
+        store.acknowledge(&[first]).unwrap();
+        assert_eq!(store.alerts().unacknowledged, 1);
+        store.acknowledge(&[]).unwrap();
+        assert_eq!(store.alerts().unacknowledged, 0);
+
+        // Everything survives a restart
+        let reopened = AlertStore::open(&path).unwrap();
+        assert_eq!(reopened.alerts(), store.alerts());
+        assert!(reopened.file.holding.contains(&3));
+    }
+
+    #[test]
+    fn new_rules_get_fresh_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = AlertStore::open(dir.path().join("alerts.json")).unwrap();
+        store.record(&[(3, obs(31.0))], now()).unwrap();
+
+        // Drop every default rule and add two new ones
+        let rules = store
+            .set_rules(vec![rule("VIX spike"), rule("VIX again")])
+            .unwrap();
+        let ids: Vec<u64> = rules.iter().map(|rule| rule.id).collect();
+        assert_eq!(ids, [4, 5]);
+        assert!(store.file.holding.is_empty());
+
+        // Alerts for deleted rules stay in the history
+        assert_eq!(store.alerts().alerts[0].rule_name, "VIX above 30");
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = AlertStore::open(dir.path().join("alerts.json")).unwrap();
+
+        let error = store.set_rules(vec![rule("  ")]).unwrap_err();
+        assert!(error.message.contains("Rule names"));
+
+        let mut bad = rule("Bad window");
+        bad.condition = AlertCondition::RisesBy {
+            change: 1.0,
+            window_days: 0,
+        };
+        let error = store.set_rules(vec![bad]).unwrap_err();
+        assert!(error.message.starts_with("Bad window: "));
+
+        let mut twin = rule("Twin");
+        twin.id = 7;
+        let error = store.set_rules(vec![twin.clone(), twin]).unwrap_err();
+        assert_eq!(error.message, "Duplicate rule id 7");
+
+        assert_eq!(store.rules(), default_rules());
+    }
+
+    #[test]
+    fn failed_writes_change_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+        let mut store = AlertStore::open(&path).unwrap();
+        store.record(&[(1, obs(-0.2))], now()).unwrap();
+
+        // A directory where the temporary file would go
+        fs::create_dir(dir.path().join("alerts.json.tmp")).unwrap();
+        assert!(store.record(&[(3, obs(31.0))], now()).is_err());
+        assert!(store.acknowledge(&[]).is_err());
+        assert!(!store.file.holding.contains(&3));
+        assert_eq!(store.alerts().unacknowledged, 1);
+
+        // Once the file can be written the alert is raised after all
+        fs::remove_dir(dir.path().join("alerts.json.tmp")).unwrap();
+        assert_eq!(store.record(&[(3, obs(31.0))], now()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unreadable_files_are_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+        fs::write(&path, "not json").unwrap();
+        assert!(AlertStore::open(&path).is_err());
+    }
+}
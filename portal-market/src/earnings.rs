@@ -0,0 +1,263 @@
+use chrono::{DateTime, Utc};
+use portal_fs::write_json_atomic;
+use portal_types::{
+    ApiError, EarningsBasis, EarningsImport, EarningsSource, EarningsStatus, EarningsTable,
+    ImportEarningsRequest,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const MAINTAINED_NAME: &str = "Maintained CSV";
+/// Decades of quarterly figures fit in a few kilobytes.
+const MAX_IMPORT_BYTES: usize = 256 * 1024;
+
+/// An imported file as written to disk.
+#[derive(Serialize, Deserialize)]
+struct StoredImport {
+    #[serde(flatten)]
+    import: EarningsImport,
+    contents: String,
+}
+
+/// S&P 500 earnings for the equity risk premium: a CSV maintained by hand,
+/// unless someone has imported a file to use instead.
+pub struct EarningsStore {
+    basis: EarningsBasis,
+    csv_path: PathBuf,
+    import_path: PathBuf,
+    /// Re-read whenever the file's modification time changes, so edits
+    /// apply without a restart
+    maintained: Mutex<Option<(SystemTime, Option<EarningsTable>)>>,
+    import: Mutex<Option<(EarningsImport, EarningsTable)>>,
+}
+
+impl EarningsStore {
+    pub fn open(
+        csv_path: impl Into<PathBuf>,
+        import_path: impl Into<PathBuf>,
+        basis: EarningsBasis,
+    ) -> Self {
+        let import_path = import_path.into();
+        let import = match fs::read(&import_path) {
+            Ok(bytes) => serde_json::from_slice::<StoredImport>(&bytes)
+                .map_err(|e| e.to_string())
+                .and_then(|stored| {
+                    let table = EarningsTable::parse_csv(
+                        import_name(&stored.import),
+                        basis,
+                        &stored.contents,
+                    )?;
+                    Ok((stored.import, table))
+                })
+                .inspect_err(|e| {
+                    log::error!("Ignoring earnings import {}: {}", import_path.display(), e)
+                })
+                .ok(),
+            Err(_) => None,
+        };
+
+        Self {
+            basis,
+            csv_path: csv_path.into(),
+            import_path,
+            maintained: Mutex::new(None),
+            import: Mutex::new(import),
+        }
+    }
+
+    fn maintained(&self) -> Option<EarningsTable> {
+        let modified = fs::metadata(&self.csv_path)
+            .and_then(|meta| meta.modified())
+            .ok()?;
+        let mut cached = self.maintained.lock().unwrap();
+        if cached.as_ref().map(|(at, _)| *at) != Some(modified) {
+            let table = fs::read_to_string(&self.csv_path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| EarningsTable::parse_csv(MAINTAINED_NAME, self.basis, &text))
+                .inspect_err(|e| log::error!("Ignoring {}: {}", self.csv_path.display(), e))
+                .ok();
+            *cached = Some((modified, table));
+        }
+        cached.as_ref().and_then(|(_, table)| table.clone())
+    }
+
+    /// The imported file if there is one, otherwise the maintained CSV.
+    /// `None` when neither has a figure, leaving the assumed P/E.
+    pub fn source(&self) -> Option<EarningsTable> {
+        let imported = self
+            .import
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(_, table)| table.clone());
+        imported
+            .or_else(|| self.maintained())
+            .filter(|table| table.latest().is_some())
+    }
+
+    pub fn status(&self) -> EarningsStatus {
+        let source = self.source();
+        EarningsStatus {
+            source: source.as_ref().map(|table| table.name().to_string()),
+            basis: self.basis,
+            latest: source.as_ref().and_then(|table| table.latest()),
+            rows: source.as_ref().map_or(0, |table| table.rows().len()),
+            import: self
+                .import
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|(import, _)| import.clone()),
+        }
+    }
+
+    /// Validates and stores a file to use instead of the maintained CSV.
+    pub fn import(
+        &self,
+        request: &ImportEarningsRequest,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), ApiError> {
+        if request.contents.len() > MAX_IMPORT_BYTES {
+            return Err(ApiError::validation("The file is too large"));
+        }
+        let import = EarningsImport {
+            file_name: request.file_name.trim().to_string(),
+            imported_by: username.to_string(),
+            imported_at: now,
+        };
+        let table = EarningsTable::parse_csv(import_name(&import), self.basis, &request.contents)
+            .map_err(ApiError::validation)?;
+        if table.latest().is_none() {
+            return Err(ApiError::validation(format!(
+                "The file has no {} earnings figures",
+                self.basis.label()
+            )));
+        }
+
+        let stored = StoredImport {
+            import: import.clone(),
+            contents: request.contents.clone(),
+        };
+        self.save(&stored).map_err(|e| {
+            log::error!("Could not write earnings import: {}", e);
+            ApiError::internal("Could not save the imported file")
+        })?;
+        *self.import.lock().unwrap() = Some((import, table));
+        Ok(())
+    }
+
+    /// Goes back to the maintained CSV.
+    pub fn clear_import(&self) -> io::Result<()> {
+        match fs::remove_file(&self.import_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        *self.import.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn save(&self, stored: &StoredImport) -> io::Result<()> {
+        write_json_atomic(&self.import_path, stored)
+    }
+}
+
+fn import_name(import: &EarningsImport) -> String {
+    format!("Imported file ({})", import.file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const MAINTAINED: &str = "date,trailing_eps\n2023-12-31,190.0\n";
+
+    fn request(contents: &str) -> ImportEarningsRequest {
+        ImportEarningsRequest {
+            file_name: "eps.csv".to_string(),
+            contents: contents.to_string(),
+        }
+    }
+
+    fn store(dir: &tempfile::TempDir) -> EarningsStore {
+        EarningsStore::open(
+            dir.path().join("sp500-earnings.csv"),
+            dir.path().join("data").join("earnings-import.json"),
+            EarningsBasis::Trailing,
+        )
+    }
+
+    #[test]
+    fn uses_the_maintained_csv_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let earnings = store(&dir);
+        assert!(earnings.source().is_none());
+        assert_eq!(earnings.status().source, None);
+
+        fs::write(dir.path().join("sp500-earnings.csv"), MAINTAINED).unwrap();
+        let status = earnings.status();
+        assert_eq!(status.source.as_deref(), Some(MAINTAINED_NAME));
+        assert_eq!(status.latest.unwrap().eps, 190.0);
+        assert_eq!(status.rows, 1);
+    }
+
+    #[test]
+    fn an_import_overrides_the_maintained_csv_until_cleared() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("sp500-earnings.csv"), MAINTAINED).unwrap();
+        let earnings = store(&dir);
+
+        earnings
+            .import(
+                &request("date,trailing_eps\n2024-03-31,210.5\n"),
+                "analyst",
+                Utc::now(),
+            )
+            .unwrap();
+        let status = earnings.status();
+        assert_eq!(status.source.as_deref(), Some("Imported file (eps.csv)"));
+        assert_eq!(
+            status.latest.unwrap().as_of,
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()
+        );
+        assert_eq!(status.import.unwrap().imported_by, "analyst");
+
+        // The import survives a restart
+        let reopened = store(&dir);
+        assert_eq!(reopened.status().latest.unwrap().eps, 210.5);
+
+        reopened.clear_import().unwrap();
+        assert_eq!(reopened.status().source.as_deref(), Some(MAINTAINED_NAME));
+        assert!(store(&dir).status().import.is_none());
+    }
+
+    #[test]
+    fn rejects_files_without_figures_for_the_basis() {
+        let dir = tempfile::tempdir().unwrap();
+        let earnings = store(&dir);
+
+        let error = earnings
+            .import(
+                &request("date,forward_eps\n2024-03-31,250\n"),
+                "analyst",
+                Utc::now(),
+            )
+            .unwrap_err();
+        assert_eq!(error.message, "The file has no trailing earnings figures");
+
+        let error = earnings
+            .import(
+                &request("date,trailing_eps\nyesterday,1\n"),
+                "analyst",
+                Utc::now(),
+            )
+            .unwrap_err();
+        assert_eq!(error.message, "Line 2: expected a YYYY-MM-DD date");
+        assert!(earnings.status().import.is_none());
+    }
+}
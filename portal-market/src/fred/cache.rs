@@ -0,0 +1,248 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use portal_fs::write_json_atomic;
+use portal_types::Observation;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// A cached series is never refetched sooner than this, so a holiday or a
+/// late publication doesn't send every request upstream.
+const RECHECK_INTERVAL: Duration = Duration::hours(1);
+
+/// Observations of one series as last fetched from FRED.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedSeries {
+    /// First date requested from FRED; `None` for the full history
+    pub start: Option<NaiveDate>,
+    pub observations: Vec<Observation>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CachedSeries {
+    /// Whether the cached range reaches back to `start`.
+    pub fn covers(&self, start: Option<NaiveDate>) -> bool {
+        match (self.start, start) {
+            (None, _) => true,
+            (Some(cached), Some(requested)) => cached <= requested,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Whether FRED is likely to have published anything newer. Daily
+    /// series are expected to have last business day's value; sparser ones
+    /// (weekly, monthly) are due once a full period has passed after the
+    /// next observation date, allowing for their publication lag.
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        if now - self.fetched_at < RECHECK_INTERVAL {
+            return true;
+        }
+
+        let mut dates: Vec<NaiveDate> = self.observations.iter().map(|obs| obs.date).collect();
+        dates.sort_unstable();
+        let today = now.date_naive();
+
+        match dates.as_slice() {
+            [] => false,
+            [.., previous, latest] if (*latest - *previous).num_days() >= 7 => {
+                let period = *latest - *previous;
+                today < *latest + period + period
+            }
+            [.., latest] => *latest >= previous_business_day(today),
+        }
+    }
+
+    /// Observations between `start` and `end`, inclusive.
+    pub fn range(&self, start: Option<NaiveDate>, end: Option<NaiveDate>) -> Vec<Observation> {
+        self.observations
+            .iter()
+            .filter(|obs| start.is_none_or(|start| obs.date >= start))
+            .filter(|obs| end.is_none_or(|end| obs.date <= end))
+            .copied()
+            .collect()
+    }
+}
+
+/// The weekday before `date`, skipping weekends. Market holidays are not
+/// known here; `RECHECK_INTERVAL` keeps them from causing refetch storms.
+pub fn previous_business_day(date: NaiveDate) -> NaiveDate {
+    let mut day = date - Duration::days(1);
+    while matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+        day -= Duration::days(1);
+    }
+    day
+}
+
+/// FRED observations per series id, persisted as one JSON file.
+pub struct FredCache {
+    path: PathBuf,
+    series: BTreeMap<String, CachedSeries>,
+}
+
+impl FredCache {
+    /// Loads the cache file. A missing or unreadable file starts an empty
+    /// cache, since everything in it can be fetched again.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let series = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                log::warn!("Ignoring unreadable FRED cache {}: {}", path.display(), e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self { path, series }
+    }
+
+    pub fn get(&self, series_id: &str) -> Option<&CachedSeries> {
+        self.series.get(series_id)
+    }
+
+    /// Replaces the cached copy of a series and writes the cache file.
+    pub fn insert(&mut self, series_id: &str, cached: CachedSeries) -> io::Result<()> {
+        self.series.insert(series_id.to_string(), cached);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        write_json_atomic(&self.path, &self.series)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    // 2024-03-04 is a Monday
+    fn at(day: &str, hour: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&date(day).and_hms_opt(hour, 0, 0).unwrap())
+    }
+
+    fn cached(dates: &[&str], fetched_at: DateTime<Utc>) -> CachedSeries {
+        CachedSeries {
+            start: None,
+            observations: dates
+                .iter()
+                .map(|d| Observation::new(date(d), 1.0))
+                .collect(),
+            fetched_at,
+        }
+    }
+
+    #[test]
+    fn previous_business_day_skips_weekends() {
+        assert_eq!(
+            previous_business_day(date("2024-03-05")),
+            date("2024-03-04")
+        );
+        assert_eq!(
+            previous_business_day(date("2024-03-04")),
+            date("2024-03-01")
+        );
+        assert_eq!(
+            previous_business_day(date("2024-03-03")),
+            date("2024-03-01")
+        );
+        assert_eq!(
+            previous_business_day(date("2024-03-02")),
+            date("2024-03-01")
+        );
+    }
+
+    #[test]
+    fn daily_series_expect_last_business_day() {
+        let series = cached(&["2024-03-01", "2024-03-04"], at("2024-03-04", 23));
+        assert!(series.is_fresh(at("2024-03-05", 12)));
+        assert!(!series.is_fresh(at("2024-03-06", 12)));
+
+        // Friday's value is current all weekend and on Monday
+        let series = cached(&["2024-02-29", "2024-03-01"], at("2024-03-01", 23));
+        assert!(series.is_fresh(at("2024-03-03", 12)));
+        assert!(series.is_fresh(at("2024-03-04", 12)));
+        assert!(!series.is_fresh(at("2024-03-05", 12)));
+    }
+
+    #[test]
+    fn monthly_series_wait_for_the_next_release() {
+        let series = cached(&["2024-01-01", "2024-02-01"], at("2024-03-01", 12));
+        assert!(series.is_fresh(at("2024-03-25", 12)));
+        assert!(!series.is_fresh(at("2024-04-05", 12)));
+    }
+
+    #[test]
+    fn recent_fetches_are_not_repeated() {
+        let series = cached(&["2024-02-01"], at("2024-03-06", 12));
+        assert!(series.is_fresh(at("2024-03-06", 12) + Duration::minutes(59)));
+        assert!(!series.is_fresh(at("2024-03-06", 14)));
+
+        let empty = cached(&[], at("2024-03-06", 12));
+        assert!(empty.is_fresh(at("2024-03-06", 12)));
+        assert!(!empty.is_fresh(at("2024-03-06", 14)));
+    }
+
+    #[test]
+    fn coverage_depends_on_the_fetched_start() {
+        let mut series = cached(&["2024-03-04"], at("2024-03-04", 12));
+        assert!(series.covers(None));
+        assert!(series.covers(Some(date("2000-01-01"))));
+
+        series.start = Some(date("2024-01-01"));
+        assert!(series.covers(Some(date("2024-01-01"))));
+        assert!(series.covers(Some(date("2024-02-01"))));
+        assert!(!series.covers(Some(date("2023-12-31"))));
+        assert!(!series.covers(None));
+    }
+
+    #[test]
+    fn range_is_inclusive() {
+        let series = cached(
+            &["2024-03-01", "2024-03-04", "2024-03-05"],
+            at("2024-03-05", 12),
+        );
+        let dates = |obs: Vec<Observation>| obs.into_iter().map(|o| o.date).collect::<Vec<_>>();
+
+        assert_eq!(series.range(None, None).len(), 3);
+        assert_eq!(
+            dates(series.range(Some(date("2024-03-04")), None)),
+            [date("2024-03-04"), date("2024-03-05")]
+        );
+        assert_eq!(
+            dates(series.range(None, Some(date("2024-03-04")))),
+            [date("2024-03-01"), date("2024-03-04")]
+        );
+    }
+
+    #[test]
+    fn cache_persists_across_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join("fred.json");
+
+        let mut cache = FredCache::open(&path);
+        assert!(cache.get("DGS10").is_none());
+        let series = cached(&["2024-03-04"], at("2024-03-04", 12));
+        cache.insert("DGS10", series.clone()).unwrap();
+
+        let reopened = FredCache::open(&path);
+        assert_eq!(reopened.get("DGS10"), Some(&series));
+    }
+
+    #[test]
+    fn unreadable_cache_starts_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fred.json");
+        fs::write(&path, "not json").unwrap();
+
+        let mut cache = FredCache::open(&path);
+        assert!(cache.get("DGS10").is_none());
+        cache
+            .insert("DGS10", cached(&["2024-03-04"], at("2024-03-04", 12)))
+            .unwrap();
+        assert!(FredCache::open(&path).get("DGS10").is_some());
+    }
+}
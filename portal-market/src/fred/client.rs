@@ -0,0 +1,122 @@
+use chrono::NaiveDate;
+use portal_types::{FredObservation, Observation};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug)]
+pub enum FredError {
+    /// No API key configured
+    NotConfigured,
+    /// FRED refused the request itself, e.g. an unknown series id
+    Rejected(String),
+    /// FRED could not be reached, answered with a server error, or refused
+    /// our API key or request rate rather than the request
+    Unavailable(String),
+}
+
+impl fmt::Display for FredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FredError::NotConfigured => write!(f, "FRED API key is not configured"),
+            FredError::Rejected(message) => write!(f, "FRED rejected the request: {}", message),
+            FredError::Unavailable(message) => write!(f, "FRED is unavailable: {}", message),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ObservationsBody {
+    observations: Vec<FredObservation>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error_message: String,
+}
+
+/// Client for FRED's `series/observations` endpoint.
+pub struct FredClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl FredClient {
+    /// `base_url` is the API root, e.g. `https://api.stlouisfed.org/fred`.
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("HTTP client with default TLS settings");
+
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|key| !key.is_empty()),
+        }
+    }
+
+    /// Observations from `start` (or the beginning of the series) to the
+    /// latest, skipping missing values.
+    pub async fn observations(
+        &self,
+        series_id: &str,
+        start: Option<NaiveDate>,
+    ) -> Result<Vec<Observation>, FredError> {
+        let api_key = self.api_key.as_deref().ok_or(FredError::NotConfigured)?;
+
+        let mut query = vec![
+            ("series_id", series_id.to_string()),
+            ("api_key", api_key.to_string()),
+            ("file_type", "json".to_string()),
+        ];
+        if let Some(start) = start {
+            query.push(("observation_start", start.format("%Y-%m-%d").to_string()));
+        }
+
+        let response = self
+            .http
+            .get(format!("{}/series/observations", self.base_url))
+            .query(&query)
+            .send()
+            .await
+            // Drop the URL from the error, it carries the API key
+            .map_err(|e| FredError::Unavailable(e.without_url().to_string()))?;
+
+        let status = response.status();
+        if status.is_client_error() {
+            let message = response
+                .json::<ErrorBody>()
+                .await
+                .map(|body| body.error_message)
+                .unwrap_or_else(|_| status.to_string());
+            // FRED answers a bad or expired key with a 400 that names it
+            let refused_us = matches!(
+                status,
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+            ) || message.contains("api_key");
+            return Err(if refused_us {
+                FredError::Unavailable(message)
+            } else {
+                FredError::Rejected(message)
+            });
+        }
+        if !status.is_success() {
+            return Err(FredError::Unavailable(status.to_string()));
+        }
+
+        let body = response
+            .json::<ObservationsBody>()
+            .await
+            .map_err(|e| FredError::Unavailable(e.without_url().to_string()))?;
+        Ok(body
+            .observations
+            .iter()
+            .filter_map(FredObservation::parse)
+            .collect())
+    }
+}
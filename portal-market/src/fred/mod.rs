@@ -0,0 +1,484 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use portal_types::{
+    ApiError, ErrorCode, FredSeries, FredSeriesQuery, FredSeriesResponse, MarketInputs,
+};
+use std::sync::Mutex;
+
+mod cache;
+mod client;
+
+use cache::CachedSeries;
+pub use cache::FredCache;
+pub use client::{FredClient, FredError};
+
+/// FRED proxy: observations are served from the cache while they are
+/// current and fetched again once FRED should have published more.
+pub struct FredService {
+    pub client: FredClient,
+    pub cache: Mutex<FredCache>,
+}
+
+/// Observations of several series, with how current they are.
+pub struct LoadedSeries {
+    pub inputs: MarketInputs,
+    /// When the least recently fetched series came from FRED
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Some series are older cached copies
+    pub stale: bool,
+    /// Series that could not be loaded at all
+    pub missing: Vec<FredSeries>,
+}
+
+impl FredService {
+    pub fn new(client: FredClient, cache: FredCache) -> Self {
+        Self {
+            client,
+            cache: Mutex::new(cache),
+        }
+    }
+
+    pub async fn series(
+        &self,
+        query: &FredSeriesQuery,
+        now: DateTime<Utc>,
+    ) -> Result<FredSeriesResponse, ApiError> {
+        let series_id = query.series_id.trim().to_ascii_uppercase();
+        let valid = !series_id.is_empty()
+            && series_id.len() <= 64
+            && series_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(ApiError::validation("Invalid FRED series id"));
+        }
+
+        let start = query.observation_start;
+        let cached = self.cache.lock().unwrap().get(&series_id).cloned();
+        if let Some(cached) = cached
+            .as_ref()
+            .filter(|cached| cached.covers(start) && cached.is_fresh(now))
+        {
+            return Ok(respond(&series_id, cached, query, false));
+        }
+
+        // Keep the cached copy at least as wide as before
+        let fetch_start = match &cached {
+            Some(cached) if cached.covers(start) => cached.start,
+            _ => start,
+        };
+
+        match self.client.observations(&series_id, fetch_start).await {
+            Ok(observations) => {
+                let fetched = CachedSeries {
+                    start: fetch_start,
+                    observations,
+                    fetched_at: now,
+                };
+                let response = respond(&series_id, &fetched, query, false);
+                if let Err(e) = self.cache.lock().unwrap().insert(&series_id, fetched) {
+                    log::error!("Could not write FRED cache: {}", e);
+                }
+                Ok(response)
+            }
+            // A cached copy outlives any upstream failure
+            Err(FredError::Rejected(message)) if cached.is_none() => {
+                Err(ApiError::validation(message))
+            }
+            Err(error) => {
+                log::warn!("Could not refresh {} from FRED: {}", series_id, error);
+                match cached {
+                    Some(cached) => Ok(respond(&series_id, &cached, query, true)),
+                    None if matches!(error, FredError::NotConfigured) => Err(ApiError::new(
+                        ErrorCode::ServerUnavailable,
+                        "FRED data is not configured",
+                    )),
+                    None => Err(ApiError::new(
+                        ErrorCode::ServerUnavailable,
+                        "FRED is unavailable and this series is not cached",
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Loads each series, refreshing what is due. Series that cannot be
+    /// loaded are reported as missing rather than failing the whole load.
+    pub async fn load(
+        &self,
+        series: &[FredSeries],
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        now: DateTime<Utc>,
+    ) -> LoadedSeries {
+        let mut loaded = LoadedSeries {
+            inputs: MarketInputs::default(),
+            fetched_at: None,
+            stale: false,
+            missing: Vec::new(),
+        };
+
+        for &series in series {
+            let query = FredSeriesQuery {
+                series_id: series.id().to_string(),
+                observation_start: start,
+                observation_end: end,
+            };
+            match self.series(&query, now).await {
+                Ok(response) => {
+                    loaded.fetched_at = Some(
+                        loaded
+                            .fetched_at
+                            .map_or(response.fetched_at, |t| t.min(response.fetched_at)),
+                    );
+                    loaded.stale |= response.stale;
+                    loaded.inputs.series.insert(series, response.observations);
+                }
+                Err(e) => {
+                    log::warn!("No {} data: {}", series.id(), e);
+                    loaded.missing.push(series);
+                }
+            }
+        }
+
+        loaded
+    }
+}
+
+fn respond(
+    series_id: &str,
+    cached: &CachedSeries,
+    query: &FredSeriesQuery,
+    stale: bool,
+) -> FredSeriesResponse {
+    FredSeriesResponse {
+        series_id: series_id.to_string(),
+        observations: cached.range(query.observation_start, query.observation_end),
+        fetched_at: cached.fetched_at,
+        stale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{dev::ServerHandle, web, App, HttpRequest, HttpResponse, HttpServer};
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const API_KEY: &str = "test-key";
+
+    /// Requests the stub FRED server has seen, and whether it should fail
+    /// or rate limit them.
+    #[derive(Default)]
+    struct StubLog {
+        requests: Mutex<Vec<HashMap<String, String>>>,
+        failing: AtomicBool,
+        rate_limited: AtomicBool,
+    }
+
+    impl StubLog {
+        fn hits(&self) -> usize {
+            self.requests.lock().unwrap().len()
+        }
+
+        fn last(&self) -> HashMap<String, String> {
+            self.requests.lock().unwrap().last().cloned().unwrap()
+        }
+    }
+
+    async fn observations(req: HttpRequest, log: web::Data<StubLog>) -> HttpResponse {
+        let query = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .unwrap()
+            .into_inner();
+        log.requests.lock().unwrap().push(query.clone());
+
+        if log.failing.load(Ordering::SeqCst) {
+            return HttpResponse::ServiceUnavailable().finish();
+        }
+        if log.rate_limited.load(Ordering::SeqCst) {
+            return HttpResponse::TooManyRequests()
+                .json(json!({ "error_code": 429, "error_message": "Too Many Requests.  Exceeded Rate Limit" }));
+        }
+        if query.get("api_key").map(String::as_str) != Some(API_KEY) {
+            return HttpResponse::BadRequest()
+                .json(json!({ "error_code": 400, "error_message": "Bad Request. The value for variable api_key is not registered." }));
+        }
+        if query["series_id"] != "DGS10" {
+            return HttpResponse::BadRequest()
+                .json(json!({ "error_code": 400, "error_message": "Bad Request. The series does not exist." }));
+        }
+
+        HttpResponse::Ok().json(json!({
+            "observation_start": query.get("observation_start").cloned().unwrap_or_default(),
+            "observations": [
+                { "realtime_start": "2024-03-06", "realtime_end": "2024-03-06", "date": "2024-03-01", "value": "4.18" },
+                { "realtime_start": "2024-03-06", "realtime_end": "2024-03-06", "date": "2024-03-04", "value": "." },
+                { "realtime_start": "2024-03-06", "realtime_end": "2024-03-06", "date": "2024-03-05", "value": "4.10" }
+            ]
+        }))
+    }
+
+    struct Stub {
+        url: String,
+        log: web::Data<StubLog>,
+        handle: ServerHandle,
+    }
+
+    async fn stub_fred() -> Stub {
+        let log = web::Data::new(StubLog::default());
+        let app_log = log.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(app_log.clone())
+                .route("/fred/series/observations", web::get().to(observations))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let url = format!("http://{}/fred", server.addrs()[0]);
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+        Stub { url, log, handle }
+    }
+
+    fn state(url: &str, api_key: Option<&str>, dir: &tempfile::TempDir) -> FredService {
+        FredService::new(
+            FredClient::new(url, api_key.map(str::to_string)),
+            FredCache::open(dir.path().join("fred-cache.json")),
+        )
+    }
+
+    fn query(series_id: &str, start: Option<&str>, end: Option<&str>) -> FredSeriesQuery {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        FredSeriesQuery {
+            series_id: series_id.to_string(),
+            observation_start: start.map(date),
+            observation_end: end.map(date),
+        }
+    }
+
+    // Wednesday evening, when Tuesday's observation is the latest expected
+    fn wednesday() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 6, 18, 0, 0).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn fetches_once_then_serves_from_cache() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        let first = fred
+            .series(&query("dgs10", None, None), wednesday())
+            .await
+            .unwrap();
+        assert_eq!(first.series_id, "DGS10");
+        assert_eq!(first.observations.len(), 2, "missing values are skipped");
+        assert_eq!(first.fetched_at, wednesday());
+        assert!(!first.stale);
+
+        let sent = stub.log.last();
+        assert_eq!(sent["series_id"], "DGS10");
+        assert_eq!(sent["file_type"], "json");
+        assert!(!sent.contains_key("observation_start"));
+
+        let later = wednesday() + chrono::Duration::hours(3);
+        let second = fred
+            .series(&query("DGS10", None, None), later)
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 1);
+        assert_eq!(second.observations, first.observations);
+
+        // The cache outlives the process
+        let reopened = state(&stub.url, Some(API_KEY), &dir);
+        reopened
+            .series(&query("DGS10", None, None), later)
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 1);
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn refetches_once_newer_data_is_due() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        fred.series(&query("DGS10", None, None), wednesday())
+            .await
+            .unwrap();
+        let thursday = wednesday() + chrono::Duration::days(1);
+        let response = fred
+            .series(&query("DGS10", None, None), thursday)
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 2);
+        assert_eq!(response.fetched_at, thursday);
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn earlier_start_widens_the_cached_range() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        fred.series(&query("DGS10", Some("2024-03-04"), None), wednesday())
+            .await
+            .unwrap();
+        assert_eq!(stub.log.last()["observation_start"], "2024-03-04");
+
+        let wider = fred
+            .series(&query("DGS10", Some("2024-03-01"), None), wednesday())
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 2);
+        assert_eq!(stub.log.last()["observation_start"], "2024-03-01");
+        assert_eq!(wider.observations.len(), 2);
+
+        // Narrower ranges come from the cache, filtered
+        let narrow = fred
+            .series(
+                &query("DGS10", Some("2024-03-02"), Some("2024-03-05")),
+                wednesday(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 2);
+        assert_eq!(narrow.observations.len(), 1);
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn serves_stale_cache_when_fred_is_down() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        let fresh = fred
+            .series(&query("DGS10", None, None), wednesday())
+            .await
+            .unwrap();
+        stub.log.failing.store(true, Ordering::SeqCst);
+
+        let next_week = wednesday() + chrono::Duration::days(7);
+        let stale = fred
+            .series(&query("DGS10", None, None), next_week)
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 2);
+        assert!(stale.stale);
+        assert_eq!(stale.fetched_at, wednesday());
+        assert_eq!(stale.observations, fresh.observations);
+
+        // Nothing cached for this one
+        let error = fred
+            .series(&query("DGS2", None, None), next_week)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerUnavailable);
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn serves_stale_cache_when_fred_refuses_us() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        let fresh = fred
+            .series(&query("DGS10", None, None), wednesday())
+            .await
+            .unwrap();
+        let next_week = wednesday() + chrono::Duration::days(7);
+
+        stub.log.rate_limited.store(true, Ordering::SeqCst);
+        let limited = fred
+            .series(&query("DGS10", None, None), next_week)
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 2);
+        assert!(limited.stale);
+        assert_eq!(limited.observations, fresh.observations);
+        stub.log.rate_limited.store(false, Ordering::SeqCst);
+
+        // The same cache with a key FRED no longer accepts
+        let expired = state(&stub.url, Some("expired-key"), &dir);
+        let stale = expired
+            .series(&query("DGS10", None, None), next_week)
+            .await
+            .unwrap();
+        assert_eq!(stub.log.hits(), 3);
+        assert!(stale.stale);
+        assert_eq!(stale.observations, fresh.observations);
+
+        // With nothing cached, a refused key is an outage rather than a bad request
+        let error = expired
+            .series(&query("DGS2", None, None), next_week)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerUnavailable);
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn unknown_series_are_rejected_with_freds_message() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        let error = fred
+            .series(&query("NOPE", None, None), wednesday())
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Validation);
+        assert!(error.message.contains("series does not exist"));
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn invalid_ids_never_reach_fred() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, Some(API_KEY), &dir);
+
+        for id in ["", "DGS10&api_key=x", "../DGS10"] {
+            let error = fred
+                .series(&query(id, None, None), wednesday())
+                .await
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::Validation, "{id:?}");
+        }
+        assert_eq!(stub.log.hits(), 0);
+
+        stub.handle.stop(false).await;
+    }
+
+    #[actix_web::test]
+    async fn missing_api_key_is_reported_without_calling_fred() {
+        let stub = stub_fred().await;
+        let dir = tempfile::tempdir().unwrap();
+        let fred = state(&stub.url, None, &dir);
+
+        let error = fred
+            .series(&query("DGS10", None, None), wednesday())
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerUnavailable);
+        assert!(error.message.contains("not configured"));
+        assert_eq!(stub.log.hits(), 0);
+
+        stub.handle.stop(false).await;
+    }
+}
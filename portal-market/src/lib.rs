@@ -0,0 +1,11 @@
+//! FRED data, the S&P 500 earnings behind the equity risk premium and the
+//! alert rules evaluated over them, shared by the Actix backend and the
+//! Tauri desktop app.
+
+pub mod alerts;
+pub mod earnings;
+pub mod fred;
+
+pub use alerts::{AlertMonitor, AlertStore};
+pub use earnings::EarningsStore;
+pub use fred::{FredCache, FredClient, FredService, LoadedSeries};
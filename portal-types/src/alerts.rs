@@ -0,0 +1,413 @@
+//! Alert rules over the risk premium metrics, e.g. "10Y-2Y below 0 for 5
+//! days" or "high yield spread up 1 point within 30 days".
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::fred::Observation;
+use crate::risk_premiums::{MetricId, Unit};
+
+/// What has to hold at the newest observation for a rule to fire.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The last `days` observations were all below `threshold`
+    Below { threshold: f64, days: u32 },
+    /// The last `days` observations were all above `threshold`
+    Above { threshold: f64, days: u32 },
+    /// The value is at least `change` above its low of the last
+    /// `window_days` calendar days
+    RisesBy { change: f64, window_days: u32 },
+    /// The value is at least `change` below its high of the last
+    /// `window_days` calendar days
+    FallsBy { change: f64, window_days: u32 },
+}
+
+impl AlertCondition {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            AlertCondition::Below { threshold, days }
+            | AlertCondition::Above { threshold, days } => {
+                if !threshold.is_finite() {
+                    return Err("The threshold must be a number".to_string());
+                }
+                if !(1..=260).contains(&days) {
+                    return Err("The number of days must be between 1 and 260".to_string());
+                }
+            }
+            AlertCondition::RisesBy {
+                change,
+                window_days,
+            }
+            | AlertCondition::FallsBy {
+                change,
+                window_days,
+            } => {
+                if !(change.is_finite() && change > 0.0) {
+                    return Err("The change must be a positive number".to_string());
+                }
+                if !(1..=366).contains(&window_days) {
+                    return Err("The window must be between 1 and 366 days".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Calendar days of history needed to evaluate the condition, allowing
+    /// for weekends and holidays.
+    pub fn lookback_days(&self) -> i64 {
+        match *self {
+            AlertCondition::Below { days, .. } | AlertCondition::Above { days, .. } => {
+                i64::from(days) * 7 / 5 + 14
+            }
+            AlertCondition::RisesBy { window_days, .. }
+            | AlertCondition::FallsBy { window_days, .. } => i64::from(window_days) + 14,
+        }
+    }
+
+    /// The newest observation, if the condition holds there.
+    /// `observations` must be oldest first.
+    pub fn check(&self, observations: &[Observation]) -> Option<Observation> {
+        let latest = *observations.last()?;
+        let holds = match *self {
+            AlertCondition::Below { threshold, days } => last(observations, days)
+                .is_some_and(|recent| recent.iter().all(|obs| obs.value < threshold)),
+            AlertCondition::Above { threshold, days } => last(observations, days)
+                .is_some_and(|recent| recent.iter().all(|obs| obs.value > threshold)),
+            AlertCondition::RisesBy {
+                change,
+                window_days,
+            } => window(observations, latest.date, window_days)
+                .map(|obs| obs.value)
+                .reduce(f64::min)
+                .is_some_and(|low| latest.value - low >= change),
+            AlertCondition::FallsBy {
+                change,
+                window_days,
+            } => window(observations, latest.date, window_days)
+                .map(|obs| obs.value)
+                .reduce(f64::max)
+                .is_some_and(|high| high - latest.value >= change),
+        };
+        holds.then_some(latest)
+    }
+
+    /// e.g. "Term Premium below 0.00% for 5 days".
+    pub fn describe(&self, metric: MetricId) -> String {
+        let amount = |value: f64| match metric.unit() {
+            Unit::Percent => format!("{:.2}%", value),
+            Unit::Index => format!("{:.2}", value),
+        };
+        let change = |value: f64| match metric.unit() {
+            Unit::Percent => format!("{:.0}bp", value * 100.0),
+            Unit::Index => format!("{:.2}", value),
+        };
+        match *self {
+            AlertCondition::Below { threshold, days } => {
+                format!(
+                    "{} below {} for {} {}",
+                    metric.label(),
+                    amount(threshold),
+                    days,
+                    plural(days, "day")
+                )
+            }
+            AlertCondition::Above { threshold, days } => {
+                format!(
+                    "{} above {} for {} {}",
+                    metric.label(),
+                    amount(threshold),
+                    days,
+                    plural(days, "day")
+                )
+            }
+            AlertCondition::RisesBy {
+                change: by,
+                window_days,
+            } => format!(
+                "{} up {} within {} {}",
+                metric.label(),
+                change(by),
+                window_days,
+                plural(window_days, "day")
+            ),
+            AlertCondition::FallsBy {
+                change: by,
+                window_days,
+            } => format!(
+                "{} down {} within {} {}",
+                metric.label(),
+                change(by),
+                window_days,
+                plural(window_days, "day")
+            ),
+        }
+    }
+}
+
+fn plural(count: u32, word: &str) -> String {
+    match count {
+        1 => word.to_string(),
+        _ => format!("{}s", word),
+    }
+}
+
+fn last(observations: &[Observation], count: u32) -> Option<&[Observation]> {
+    let count = count as usize;
+    (observations.len() >= count).then(|| &observations[observations.len() - count..])
+}
+
+fn window(
+    observations: &[Observation],
+    latest: NaiveDate,
+    days: u32,
+) -> impl Iterator<Item = &Observation> {
+    let start = latest - Duration::days(i64::from(days));
+    observations.iter().filter(move |obs| obs.date >= start)
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// A condition on one metric. Rules with id 0 are new and get an id when
+/// saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    #[serde(default)]
+    pub id: u64,
+    pub name: String,
+    pub metric: MetricId,
+    pub condition: AlertCondition,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+impl AlertRule {
+    pub fn describe(&self) -> String {
+        self.condition.describe(self.metric)
+    }
+}
+
+/// The curve, credit and volatility rules new installations start with.
+pub fn default_rules() -> Vec<AlertRule> {
+    vec![
+        AlertRule {
+            id: 1,
+            name: "Yield curve inverted".to_string(),
+            metric: MetricId::TermPremium,
+            condition: AlertCondition::Below {
+                threshold: 0.0,
+                days: 5,
+            },
+            enabled: true,
+        },
+        AlertRule {
+            id: 2,
+            name: "High yield spread widening".to_string(),
+            metric: MetricId::HighYieldSpread,
+            condition: AlertCondition::RisesBy {
+                change: 1.0,
+                window_days: 30,
+            },
+            enabled: true,
+        },
+        AlertRule {
+            id: 3,
+            name: "VIX above 30".to_string(),
+            metric: MetricId::Vix,
+            condition: AlertCondition::Above {
+                threshold: 30.0,
+                days: 1,
+            },
+            enabled: true,
+        },
+    ]
+}
+
+/// A rule that started to hold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: u64,
+    pub rule_id: u64,
+    pub rule_name: String,
+    /// The rule's condition, described
+    pub message: String,
+    pub metric: MetricId,
+    /// The observation that triggered it
+    pub date: NaiveDate,
+    pub value: f64,
+    pub triggered_at: DateTime<Utc>,
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+/// `GET /api/alerts`: newest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertsResponse {
+    pub alerts: Vec<Alert>,
+    pub unacknowledged: usize,
+    /// When the rules were last checked
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AcknowledgeAlertsRequest {
+    /// Empty to acknowledge every alert
+    #[serde(default)]
+    pub ids: Vec<u64>,
+}
+
+/// Replaces every rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveAlertRulesRequest {
+    pub rules: Vec<AlertRule>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Weekday};
+    use serde_json::json;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    // One observation per weekday from 2024-03-04 (a Monday)
+    fn weekdays(values: &[f64]) -> Vec<Observation> {
+        let mut day = date("2024-03-04");
+        values
+            .iter()
+            .map(|&value| {
+                let obs = Observation::new(day, value);
+                day += Duration::days(if day.weekday() == Weekday::Fri { 3 } else { 1 });
+                obs
+            })
+            .collect()
+    }
+
+    #[test]
+    fn below_needs_every_recent_observation() {
+        let condition = AlertCondition::Below {
+            threshold: 0.0,
+            days: 5,
+        };
+        let inverted = weekdays(&[0.2, -0.1, -0.2, -0.1, -0.3, -0.2]);
+        assert_eq!(condition.check(&inverted), inverted.last().copied());
+
+        let brief = weekdays(&[0.2, 0.1, -0.2, -0.1, -0.3, -0.2]);
+        assert_eq!(condition.check(&brief), None);
+
+        // Too little history to tell
+        assert_eq!(condition.check(&inverted[2..]), None);
+        assert_eq!(condition.check(&[]), None);
+    }
+
+    #[test]
+    fn above_is_strict() {
+        let condition = AlertCondition::Above {
+            threshold: 30.0,
+            days: 1,
+        };
+        assert!(condition.check(&weekdays(&[20.0, 31.5])).is_some());
+        assert!(condition.check(&weekdays(&[35.0, 30.0])).is_none());
+    }
+
+    #[test]
+    fn rises_by_compares_with_the_low_of_the_window() {
+        let condition = AlertCondition::RisesBy {
+            change: 1.0,
+            window_days: 30,
+        };
+        let observations = vec![
+            Observation::new(date("2024-01-02"), 2.5),
+            Observation::new(date("2024-02-01"), 3.2),
+            Observation::new(date("2024-02-15"), 3.6),
+            Observation::new(date("2024-03-01"), 4.2),
+        ];
+        // The January low is outside the window; 4.2 - 3.2 = 1.0
+        assert!(condition.check(&observations).is_some());
+
+        let smaller = AlertCondition::RisesBy {
+            change: 1.5,
+            window_days: 30,
+        };
+        assert!(smaller.check(&observations).is_none());
+        let longer = AlertCondition::RisesBy {
+            change: 1.5,
+            window_days: 60,
+        };
+        assert!(longer.check(&observations).is_some());
+    }
+
+    #[test]
+    fn falls_by_compares_with_the_high_of_the_window() {
+        let condition = AlertCondition::FallsBy {
+            change: 5.0,
+            window_days: 10,
+        };
+        assert!(condition.check(&weekdays(&[30.0, 27.0, 24.0])).is_some());
+        assert!(condition.check(&weekdays(&[30.0, 27.0, 26.0])).is_none());
+    }
+
+    #[test]
+    fn describes_conditions_in_the_metric_unit() {
+        let rules = default_rules();
+        assert_eq!(rules[0].describe(), "Term Premium below 0.00% for 5 days");
+        assert_eq!(
+            rules[1].describe(),
+            "High Yield Spread up 100bp within 30 days"
+        );
+        assert_eq!(rules[2].describe(), "VIX Index above 30.00 for 1 day");
+    }
+
+    #[test]
+    fn validates_parameters() {
+        assert!(default_rules()
+            .iter()
+            .all(|rule| rule.condition.validate().is_ok()));
+        let invalid = [
+            AlertCondition::Below {
+                threshold: f64::NAN,
+                days: 5,
+            },
+            AlertCondition::Above {
+                threshold: 30.0,
+                days: 0,
+            },
+            AlertCondition::RisesBy {
+                change: -1.0,
+                window_days: 30,
+            },
+            AlertCondition::FallsBy {
+                change: 1.0,
+                window_days: 0,
+            },
+        ];
+        for condition in invalid {
+            assert!(condition.validate().is_err(), "{condition:?}");
+        }
+    }
+
+    #[test]
+    fn rules_serialize_with_a_condition_kind() {
+        let rule = &default_rules()[0];
+        let value = serde_json::to_value(rule).unwrap();
+        assert_eq!(
+            value["condition"],
+            json!({ "kind": "below", "threshold": 0.0, "days": 5 })
+        );
+        assert_eq!(value["metric"], "term_premium");
+
+        let new: AlertRule = serde_json::from_value(json!({
+            "name": "VIX calm",
+            "metric": "vix",
+            "condition": { "kind": "below", "threshold": 12.0, "days": 3 }
+        }))
+        .unwrap();
+        assert_eq!(new.id, 0);
+        assert!(new.enabled);
+    }
+}
@@ -0,0 +1,598 @@
+//! Risk and performance figures from a series of monthly returns, laid out
+//! as rows of the funds' `risk_metrics` table.
+//!
+//! Returns are in percent, as everywhere else. Annual figures are
+//! annualised from monthly ones: means by 12, deviations by √12.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::MonthBalance;
+use crate::funds::{FundStat, StatUnit, StatValue, YearReturns};
+
+/// Confidence level of the value-at-risk figures: the worst 5% of months.
+pub const VAR_LEVEL: f64 = 0.95;
+
+/// Monthly returns in percent, oldest month first, one per month.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReturnSeries {
+    months: Vec<(NaiveDate, f64)>,
+}
+
+impl ReturnSeries {
+    pub fn new(mut months: Vec<(NaiveDate, f64)>) -> Self {
+        months.sort_by_key(|(month, _)| *month);
+        months.dedup_by_key(|(month, _)| *month);
+        Self { months }
+    }
+
+    /// The months of a fund's `monthly_returns`. Months with no figure are
+    /// left out.
+    pub fn from_years(years: &[YearReturns]) -> Self {
+        let months = years
+            .iter()
+            .flat_map(|year| {
+                year.months.iter().enumerate().filter_map(move |(m, pct)| {
+                    let month = NaiveDate::from_ymd_opt(year.year, m as u32 + 1, 1)?;
+                    Some((month, (*pct)?))
+                })
+            })
+            .collect();
+        Self::new(months)
+    }
+
+    /// The months of a backtest.
+    pub fn from_balances(months: &[MonthBalance]) -> Self {
+        Self::new(
+            months
+                .iter()
+                .map(|month| (month.month, month.return_pct))
+                .collect(),
+        )
+    }
+
+    pub fn months(&self) -> &[(NaiveDate, f64)] {
+        &self.months
+    }
+
+    pub fn first_month(&self) -> Option<NaiveDate> {
+        self.months.first().map(|(month, _)| *month)
+    }
+
+    pub fn last_month(&self) -> Option<NaiveDate> {
+        self.months.last().map(|(month, _)| *month)
+    }
+
+    /// Only the months both series have, so the two can be compared.
+    pub fn overlap(&self, other: &ReturnSeries) -> (ReturnSeries, ReturnSeries) {
+        let shared = |series: &ReturnSeries, other: &ReturnSeries| {
+            let months = series
+                .months
+                .iter()
+                .filter(|(month, _)| {
+                    other
+                        .months
+                        .binary_search_by_key(month, |(month, _)| *month)
+                        .is_ok()
+                })
+                .copied()
+                .collect();
+            ReturnSeries { months }
+        };
+        (shared(self, other), shared(other, self))
+    }
+
+    fn returns(&self) -> impl Iterator<Item = f64> + '_ {
+        self.months.iter().map(|(_, pct)| *pct)
+    }
+}
+
+/// Figures for one series on its own. Percentages are in percent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskMetrics {
+    pub months: usize,
+    /// Average monthly return
+    pub arithmetic_mean: f64,
+    /// Compound annual growth rate
+    pub cagr: f64,
+    /// Annualised standard deviation of monthly returns
+    pub volatility: f64,
+    /// Annualised deviation of returns below the risk-free rate
+    pub downside_deviation: f64,
+    /// `None` when there is no volatility to divide by
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    /// Deepest fall from a high, negative
+    pub max_drawdown: f64,
+    /// CAGR over the maximum drawdown; `None` without a drawdown
+    pub calmar: Option<f64>,
+    /// Calendar year and its compounded return
+    pub best_year: (i32, f64),
+    pub worst_year: (i32, f64),
+    /// Monthly historical value at risk at [`VAR_LEVEL`], a return
+    pub value_at_risk: f64,
+    /// Average of the months at or below the value at risk
+    pub conditional_value_at_risk: f64,
+}
+
+impl RiskMetrics {
+    /// `risk_free` is an annual rate in percent. `None` for an empty series.
+    pub fn compute(series: &ReturnSeries, risk_free: f64) -> Option<Self> {
+        let returns: Vec<f64> = series.returns().collect();
+        let n = returns.len();
+        if n == 0 {
+            return None;
+        }
+
+        let arithmetic_mean = mean(&returns);
+        let growth: f64 = returns.iter().map(|pct| 1.0 + pct / 100.0).product();
+        let cagr = (growth.powf(12.0 / n as f64) - 1.0) * 100.0;
+        let volatility = std_dev(&returns) * 12f64.sqrt();
+
+        let monthly_risk_free = monthly_rate(risk_free);
+        let excess = (arithmetic_mean - monthly_risk_free) * 12.0;
+        let downside = returns
+            .iter()
+            .map(|pct| (pct - monthly_risk_free).min(0.0).powi(2))
+            .sum::<f64>()
+            / n as f64;
+        let downside_deviation = downside.sqrt() * 12f64.sqrt();
+
+        let mut balance = 1.0;
+        let mut peak = 1.0;
+        let mut max_drawdown: f64 = 0.0;
+        for pct in &returns {
+            balance *= 1.0 + pct / 100.0;
+            peak = f64::max(peak, balance);
+            max_drawdown = max_drawdown.min((balance / peak - 1.0) * 100.0);
+        }
+
+        let mut years: Vec<(i32, f64)> = Vec::new();
+        for (month, pct) in &series.months {
+            match years.last_mut() {
+                Some((year, growth)) if *year == month.year() => *growth *= 1.0 + pct / 100.0,
+                _ => years.push((month.year(), 1.0 + pct / 100.0)),
+            }
+        }
+        let years: Vec<(i32, f64)> = years
+            .into_iter()
+            .map(|(year, growth)| (year, (growth - 1.0) * 100.0))
+            .collect();
+        let best_year = *years.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let worst_year = *years.iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        // The worst ceil(5%) of months, at least one
+        let mut sorted = returns.clone();
+        sorted.sort_by(f64::total_cmp);
+        let tail = (((1.0 - VAR_LEVEL) * n as f64).ceil() as usize).clamp(1, n);
+        let value_at_risk = sorted[tail - 1];
+        let conditional_value_at_risk = mean(&sorted[..tail]);
+
+        Some(Self {
+            months: n,
+            arithmetic_mean,
+            cagr,
+            volatility,
+            downside_deviation,
+            sharpe: ratio(excess, volatility),
+            sortino: ratio(excess, downside_deviation),
+            max_drawdown,
+            calmar: ratio(cagr, -max_drawdown),
+            best_year,
+            worst_year,
+            value_at_risk,
+            conditional_value_at_risk,
+        })
+    }
+}
+
+/// Figures for a series against a benchmark over the months both have.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelativeMetrics {
+    pub months: usize,
+    pub beta: Option<f64>,
+    /// Annualised Jensen's alpha, in percent
+    pub alpha: Option<f64>,
+    pub correlation: Option<f64>,
+    /// Percent of the benchmark's average rise captured in months it rose
+    pub upside_capture: Option<f64>,
+    /// Percent of the benchmark's average fall taken in months it fell
+    pub downside_capture: Option<f64>,
+}
+
+impl RelativeMetrics {
+    /// `risk_free` is an annual rate in percent.
+    pub fn compute(series: &ReturnSeries, benchmark: &ReturnSeries, risk_free: f64) -> Self {
+        let (series, benchmark) = series.overlap(benchmark);
+        let fund: Vec<f64> = series.returns().collect();
+        let bench: Vec<f64> = benchmark.returns().collect();
+
+        let (fund_mean, bench_mean) = (mean(&fund), mean(&bench));
+        let covariance = covariance(&fund, &bench);
+        let bench_variance = covariance_of(&bench);
+        let beta = ratio(covariance, bench_variance);
+        let monthly_risk_free = monthly_rate(risk_free);
+        let alpha = beta.map(|beta| {
+            ((fund_mean - monthly_risk_free) - beta * (bench_mean - monthly_risk_free)) * 12.0
+        });
+        let correlation = ratio(covariance, (covariance_of(&fund) * bench_variance).sqrt());
+
+        let capture = |rising: bool| {
+            let (fund, bench): (Vec<f64>, Vec<f64>) = fund
+                .iter()
+                .zip(&bench)
+                .filter(|(_, bench)| if rising { **bench > 0.0 } else { **bench < 0.0 })
+                .unzip();
+            if bench.is_empty() {
+                return None;
+            }
+            ratio(geometric_mean(&fund), geometric_mean(&bench)).map(|ratio| ratio * 100.0)
+        };
+
+        Self {
+            months: fund.len(),
+            beta,
+            alpha,
+            correlation,
+            upside_capture: capture(true),
+            downside_capture: capture(false),
+        }
+    }
+}
+
+/// Correlation of monthly returns over the months both series have.
+pub fn correlation(a: &ReturnSeries, b: &ReturnSeries) -> Option<f64> {
+    let (a, b) = a.overlap(b);
+    let a: Vec<f64> = a.returns().collect();
+    let b: Vec<f64> = b.returns().collect();
+    ratio(
+        covariance(&a, &b),
+        (covariance_of(&a) * covariance_of(&b)).sqrt(),
+    )
+}
+
+/// The `risk_metrics` table for a series, against a benchmark over the
+/// months they share when there is one. Without a benchmark the relative
+/// rows (beta, alpha, correlation, capture) are left out and the benchmark
+/// column is empty. `risk_free` is an annual rate in percent.
+pub fn risk_table(
+    series: &ReturnSeries,
+    benchmark: Option<&ReturnSeries>,
+    risk_free: f64,
+) -> Result<Vec<FundStat>, String> {
+    let row = |metric: &str, fund: StatValue, benchmark: StatValue| FundStat {
+        metric: metric.to_string(),
+        fund,
+        benchmark,
+    };
+
+    let Some(benchmark) = benchmark else {
+        let fund = RiskMetrics::compute(series, risk_free)
+            .ok_or_else(|| "The fund has no monthly returns".to_string())?;
+        return Ok(own_rows(&fund)
+            .into_iter()
+            .map(|(metric, fund)| {
+                let empty = figure(None, fund.unit);
+                row(metric, fund, empty)
+            })
+            .collect());
+    };
+
+    let (series, benchmark) = series.overlap(benchmark);
+    let (Some(fund), Some(bench)) = (
+        RiskMetrics::compute(&series, risk_free),
+        RiskMetrics::compute(&benchmark, risk_free),
+    ) else {
+        return Err("The fund and benchmark have no months in common".to_string());
+    };
+    let relative = RelativeMetrics::compute(&series, &benchmark, risk_free);
+    let itself = RelativeMetrics::compute(&benchmark, &benchmark, risk_free);
+
+    let mut table: Vec<FundStat> = own_rows(&fund)
+        .into_iter()
+        .zip(own_rows(&bench))
+        .map(|((metric, fund), (_, bench))| row(metric, fund, bench))
+        .collect();
+    table.extend([
+        row(
+            "Beta",
+            figure(relative.beta, StatUnit::Ratio),
+            figure(itself.beta, StatUnit::Ratio),
+        ),
+        row(
+            "Alpha (annualized)",
+            figure(relative.alpha, StatUnit::Percent),
+            figure(itself.alpha, StatUnit::Percent),
+        ),
+        row(
+            "Benchmark Correlation",
+            figure(relative.correlation, StatUnit::Ratio),
+            figure(itself.correlation, StatUnit::Ratio),
+        ),
+        row(
+            "Upside Capture Ratio",
+            figure(relative.upside_capture, StatUnit::Percent),
+            figure(itself.upside_capture, StatUnit::Percent),
+        ),
+        row(
+            "Downside Capture Ratio",
+            figure(relative.downside_capture, StatUnit::Percent),
+            figure(itself.downside_capture, StatUnit::Percent),
+        ),
+    ]);
+    Ok(table)
+}
+
+// Ratios with nothing to divide by stay undefined rather than reading 0
+fn figure(value: Option<f64>, unit: StatUnit) -> StatValue {
+    StatValue {
+        value,
+        unit,
+        note: None,
+    }
+}
+
+/// The rows a series' own returns give, in table order.
+fn own_rows(metrics: &RiskMetrics) -> Vec<(&'static str, StatValue)> {
+    let percent = |value: f64| figure(Some(value), StatUnit::Percent);
+    let year = |(year, pct): (i32, f64)| StatValue {
+        note: Some(year.to_string()),
+        ..percent(pct)
+    };
+
+    vec![
+        (
+            "Arithmetic Mean (monthly)",
+            percent(metrics.arithmetic_mean),
+        ),
+        ("Annualized Return (CAGR)", percent(metrics.cagr)),
+        ("Volatility", percent(metrics.volatility)),
+        ("Downside Deviation", percent(metrics.downside_deviation)),
+        ("Sharpe Ratio", figure(metrics.sharpe, StatUnit::Ratio)),
+        ("Sortino Ratio", figure(metrics.sortino, StatUnit::Ratio)),
+        ("Maximum Drawdown", percent(metrics.max_drawdown)),
+        ("Calmar Ratio", figure(metrics.calmar, StatUnit::Ratio)),
+        ("Best Year", year(metrics.best_year)),
+        ("Worst Year", year(metrics.worst_year)),
+        ("Historical VaR (5%)", percent(metrics.value_at_risk)),
+        (
+            "Conditional VaR (5%)",
+            percent(metrics.conditional_value_at_risk),
+        ),
+    ]
+}
+
+/// Asks for a fund's risk table, optionally against a ticker from the
+/// returns dataset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskMetricsQuery {
+    #[serde(default)]
+    pub benchmark: Option<String>,
+    /// Annual percent
+    #[serde(default)]
+    pub risk_free: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskMetricsResponse {
+    /// `None` for the fund on its own
+    pub benchmark: Option<String>,
+    pub risk_free: f64,
+    /// The months the table covers, those both series have with a benchmark
+    pub first_month: Option<NaiveDate>,
+    pub last_month: Option<NaiveDate>,
+    pub metrics: Vec<FundStat>,
+}
+
+/// The monthly rate that compounds to `annual` percent over a year.
+fn monthly_rate(annual: f64) -> f64 {
+    ((1.0 + annual / 100.0).powf(1.0 / 12.0) - 1.0) * 100.0
+}
+
+fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    (denominator.abs() > f64::EPSILON).then(|| numerator / denominator)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+fn geometric_mean(returns: &[f64]) -> f64 {
+    let growth: f64 = returns.iter().map(|pct| 1.0 + pct / 100.0).product();
+    (growth.powf(1.0 / returns.len() as f64) - 1.0) * 100.0
+}
+
+/// Sample covariance.
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() < 2 {
+        return 0.0;
+    }
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - mean_a) * (b - mean_b))
+        .sum::<f64>()
+        / (a.len() - 1) as f64
+}
+
+fn covariance_of(values: &[f64]) -> f64 {
+    covariance(values, values)
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    covariance_of(values).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn series(start_year: i32, returns: &[f64]) -> ReturnSeries {
+        let start = NaiveDate::from_ymd_opt(start_year, 1, 1).unwrap();
+        ReturnSeries::new(
+            returns
+                .iter()
+                .enumerate()
+                .map(|(i, pct)| {
+                    let month = start
+                        .checked_add_months(chrono::Months::new(i as u32))
+                        .unwrap();
+                    (month, *pct)
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn reads_fund_years() {
+        let mut months = [None; 12];
+        months[0] = Some(1.0);
+        months[2] = Some(-2.0);
+        let years = [YearReturns {
+            year: 2024,
+            months,
+            total: None,
+            inflation: None,
+            balance: None,
+        }];
+        let series = ReturnSeries::from_years(&years);
+        assert_eq!(series.months().len(), 2);
+        assert_eq!(series.last_month(), NaiveDate::from_ymd_opt(2024, 3, 1));
+    }
+
+    #[test]
+    fn computes_growth_and_risk() {
+        // A year of 1% months, then a year of alternating +10%/-10%
+        let mut returns = vec![1.0; 12];
+        returns.extend((0..12).map(|i| if i % 2 == 0 { 10.0 } else { -10.0 }));
+        let metrics = RiskMetrics::compute(&series(2020, &returns), 0.0).unwrap();
+
+        assert_eq!(metrics.months, 24);
+        assert!(close(metrics.arithmetic_mean, 0.5));
+        let growth = 1.01f64.powi(12) * 0.99f64.powi(6);
+        assert!(close(metrics.cagr, (growth.sqrt() - 1.0) * 100.0));
+        assert_eq!(metrics.best_year.0, 2020);
+        assert!(close(metrics.best_year.1, (1.01f64.powi(12) - 1.0) * 100.0));
+        assert_eq!(metrics.worst_year.0, 2021);
+        assert!(close(
+            metrics.max_drawdown,
+            (0.99f64.powi(5) * 0.9 - 1.0) * 100.0
+        ));
+        assert!(close(
+            metrics.calmar.unwrap(),
+            metrics.cagr / -metrics.max_drawdown
+        ));
+
+        // The worst 5% of 24 months is the two worst
+        assert!(close(metrics.value_at_risk, -10.0));
+        assert!(close(metrics.conditional_value_at_risk, -10.0));
+        assert!(metrics.sharpe.unwrap() > 0.0);
+        assert!(metrics.sortino.unwrap() > metrics.sharpe.unwrap());
+    }
+
+    #[test]
+    fn flat_returns_have_no_ratios() {
+        let metrics = RiskMetrics::compute(&series(2020, &[0.5; 12]), 0.0).unwrap();
+        assert!(close(metrics.volatility, 0.0));
+        assert_eq!(metrics.sharpe, None);
+        assert_eq!(metrics.sortino, None);
+        assert!(close(metrics.max_drawdown, 0.0));
+        assert_eq!(metrics.calmar, None);
+
+        assert_eq!(RiskMetrics::compute(&ReturnSeries::default(), 0.0), None);
+    }
+
+    #[test]
+    fn sharpe_uses_the_risk_free_rate() {
+        let returns: Vec<f64> = (0..24)
+            .map(|i| if i % 3 == 0 { -2.0 } else { 2.0 })
+            .collect();
+        let without = RiskMetrics::compute(&series(2020, &returns), 0.0).unwrap();
+        let with = RiskMetrics::compute(&series(2020, &returns), 5.0).unwrap();
+        assert!(with.sharpe.unwrap() < without.sharpe.unwrap());
+        assert!(close(monthly_rate(12.682503013196977), 1.0));
+    }
+
+    #[test]
+    fn compares_with_a_benchmark() {
+        let bench = series(2020, &[2.0, -1.0, 3.0, -2.0, 1.0, -3.0]);
+        let levered = series(2020, &[4.0, -2.0, 6.0, -4.0, 2.0, -6.0]);
+
+        let relative = RelativeMetrics::compute(&levered, &bench, 0.0);
+        assert!(close(relative.beta.unwrap(), 2.0));
+        assert!(close(relative.correlation.unwrap(), 1.0));
+        // Doubling each month's return roughly doubles the geometric means
+        assert!((relative.upside_capture.unwrap() - 200.0).abs() < 1.0);
+        assert!((relative.downside_capture.unwrap() - 200.0).abs() < 1.0);
+
+        let itself = RelativeMetrics::compute(&bench, &bench, 0.0);
+        assert!(close(correlation(&levered, &bench).unwrap(), 1.0));
+        assert!(close(itself.beta.unwrap(), 1.0));
+        assert!(close(itself.alpha.unwrap(), 0.0));
+        assert!(close(itself.upside_capture.unwrap(), 100.0));
+    }
+
+    #[test]
+    fn builds_the_risk_table_over_shared_months() {
+        // The fund starts two months after the benchmark
+        let bench = series(2020, &[5.0, 5.0, 2.0, -1.0, 3.0, -2.0]);
+        let fund = ReturnSeries::new(
+            series(2020, &[0.0, 0.0, 1.0, -0.5, 1.5, -1.0]).months()[2..].to_vec(),
+        );
+
+        let table = risk_table(&fund, Some(&bench), 0.0).unwrap();
+        let row = |metric: &str| table.iter().find(|row| row.metric == metric).unwrap();
+
+        let mean = row("Arithmetic Mean (monthly)");
+        assert!(close(mean.fund.value.unwrap(), 0.25));
+        assert!(close(mean.benchmark.value.unwrap(), 0.5));
+        assert_eq!(mean.fund.format(), "0.25%");
+        assert!(close(row("Beta").fund.value.unwrap(), 0.5));
+        assert!(close(row("Beta").benchmark.value.unwrap(), 1.0));
+        assert_eq!(row("Benchmark Correlation").benchmark.format(), "1.00");
+        assert_eq!(row("Best Year").fund.note.as_deref(), Some("2020"));
+
+        assert!(risk_table(&fund, Some(&series(2030, &[1.0])), 0.0).is_err());
+
+        // On its own the fund keeps all of its months
+        let alone = risk_table(&fund, None, 0.0).unwrap();
+        let alone_row = |metric: &str| alone.iter().find(|row| row.metric == metric);
+        assert_eq!(alone.len(), 12);
+        assert!(close(
+            alone_row("Arithmetic Mean (monthly)")
+                .unwrap()
+                .fund
+                .value
+                .unwrap(),
+            0.25
+        ));
+        assert_eq!(
+            alone_row("Volatility").unwrap().benchmark.unit,
+            StatUnit::Percent
+        );
+        assert_eq!(alone_row("Volatility").unwrap().benchmark.format(), "—");
+        assert!(alone_row("Beta").is_none());
+        assert!(risk_table(&ReturnSeries::default(), None, 0.0).is_err());
+    }
+
+    #[test]
+    fn undefined_ratios_are_left_empty() {
+        let flat = series(2020, &[0.5; 12]);
+        let bench = series(2020, &[1.0; 12]);
+
+        let table = risk_table(&flat, Some(&bench), 0.0).unwrap();
+        let row = |metric: &str| table.iter().find(|row| row.metric == metric).unwrap();
+
+        for metric in ["Sharpe Ratio", "Sortino Ratio", "Calmar Ratio", "Beta"] {
+            assert_eq!(row(metric).fund.value, None, "{}", metric);
+            assert_eq!(row(metric).fund.format(), "—");
+        }
+        assert_eq!(row("Alpha (annualized)").fund.format(), "—");
+        assert_eq!(row("Downside Capture Ratio").fund.value, None);
+        assert!(close(row("Volatility").fund.value.unwrap(), 0.0));
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Login,
+    Logout,
+    AccessDenied,
+    UserCreated,
+    UserDisabled,
+    UserEnabled,
+    RoleChanged,
+    PasswordReset,
+    TwoFactorEnabled,
+    TwoFactorDisabled,
+    FundCreated,
+    FundUpdated,
+    FundDeleted,
+    FundRestored,
+}
+
+impl AuditEvent {
+    pub fn label(self) -> &'static str {
+        match self {
+            AuditEvent::Login => "Login",
+            AuditEvent::Logout => "Logout",
+            AuditEvent::AccessDenied => "Access denied",
+            AuditEvent::UserCreated => "User created",
+            AuditEvent::UserDisabled => "User disabled",
+            AuditEvent::UserEnabled => "User enabled",
+            AuditEvent::RoleChanged => "Role changed",
+            AuditEvent::PasswordReset => "Password reset",
+            AuditEvent::TwoFactorEnabled => "Two-factor enabled",
+            AuditEvent::TwoFactorDisabled => "Two-factor disabled",
+            AuditEvent::FundCreated => "Fund created",
+            AuditEvent::FundUpdated => "Fund updated",
+            AuditEvent::FundDeleted => "Fund deleted",
+            AuditEvent::FundRestored => "Fund restored",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    /// Refused by login throttling before credentials were checked
+    Throttled,
+}
+
+impl AuditOutcome {
+    pub fn label(self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "Success",
+            AuditOutcome::Failure => "Failure",
+            AuditOutcome::Throttled => "Throttled",
+        }
+    }
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// The account acting, or the username attempted for failed logins
+    pub user: String,
+    pub event: AuditEvent,
+    pub outcome: AuditOutcome,
+    /// Address or window the request came from, where known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// The affected account, new role, or failure reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(user: impl Into<String>, event: AuditEvent, outcome: AuditOutcome) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            user: user.into(),
+            event,
+            outcome,
+            client: None,
+            detail: None,
+        }
+    }
+}
+
+/// Filters for reading the audit log. Bounds are inclusive; results come
+/// back newest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn entry_round_trip() {
+        let entry = AuditEntry {
+            timestamp: Utc.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap(),
+            client: Some("10.0.0.5".to_string()),
+            ..AuditEntry::new("admin", AuditEvent::Login, AuditOutcome::Throttled)
+        };
+
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "timestamp": "2025-03-03T09:00:00Z",
+                "user": "admin",
+                "event": "login",
+                "outcome": "throttled",
+                "client": "10.0.0.5"
+            })
+        );
+        assert_eq!(serde_json::from_value::<AuditEntry>(value).unwrap(), entry);
+    }
+
+    #[test]
+    fn empty_query_serializes_to_nothing() {
+        assert_eq!(
+            serde_json::to_value(AuditQuery::default()).unwrap(),
+            json!({})
+        );
+
+        let query: AuditQuery =
+            serde_json::from_value(json!({ "user": "analyst", "limit": 50 })).unwrap();
+        assert_eq!(query.user.as_deref(), Some("analyst"));
+        assert_eq!(query.limit, Some(50));
+        assert!(query.from.is_none());
+    }
+}
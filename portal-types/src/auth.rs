@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+use crate::roles::Role;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// Body of a successful login step or logout. Failures are `ApiError`s.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoginResponse {
+    pub message: String,
+    /// Only returned over IPC; the HTTP server keeps the token in a cookie
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+    /// Set when the password was right but a second factor is still needed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub two_factor_token: Option<String>,
+}
+
+impl LoginResponse {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            session_token: None,
+            two_factor_token: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub username: String,
+    pub logged_in: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// Seconds until the session times out, counting from this response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in_secs: Option<u64>,
+}
+
+impl UserInfo {
+    pub fn logged_out() -> Self {
+        Self {
+            username: String::new(),
+            logged_in: false,
+            role: None,
+            expires_in_secs: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn login_request_round_trip() {
+        let request = LoginRequest {
+            username: "admin".to_string(),
+            password: "admin123".to_string(),
+        };
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value,
+            json!({ "username": "admin", "password": "admin123" })
+        );
+        assert_eq!(
+            serde_json::from_value::<LoginRequest>(value).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn login_response_round_trip() {
+        let response = LoginResponse::new("Logged out successfully");
+
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value, json!({ "message": "Logged out successfully" }));
+        assert_eq!(
+            serde_json::from_value::<LoginResponse>(value).unwrap(),
+            response
+        );
+
+        let response = LoginResponse {
+            session_token: Some("abc123".to_string()),
+            ..LoginResponse::new("Login successful")
+        };
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            value,
+            json!({ "message": "Login successful", "session_token": "abc123" })
+        );
+        assert_eq!(
+            serde_json::from_value::<LoginResponse>(value).unwrap(),
+            response
+        );
+    }
+
+    #[test]
+    fn two_factor_challenge_round_trip() {
+        let response = LoginResponse {
+            two_factor_token: Some("challenge".to_string()),
+            ..LoginResponse::new("Enter your authentication code")
+        };
+
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "message": "Enter your authentication code",
+                "two_factor_token": "challenge"
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<LoginResponse>(value).unwrap(),
+            response
+        );
+    }
+
+    #[test]
+    fn user_info_round_trip() {
+        let user = UserInfo {
+            username: "user1".to_string(),
+            logged_in: true,
+            role: Some(Role::Analyst),
+            expires_in_secs: Some(1800),
+        };
+
+        let value = serde_json::to_value(&user).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "username": "user1",
+                "logged_in": true,
+                "role": "analyst",
+                "expires_in_secs": 1800
+            })
+        );
+        assert_eq!(serde_json::from_value::<UserInfo>(value).unwrap(), user);
+    }
+
+    #[test]
+    fn user_info_accepts_logged_out_payloads() {
+        let value = json!({ "username": "", "logged_in": false });
+        assert_eq!(
+            serde_json::from_value::<UserInfo>(value).unwrap(),
+            UserInfo::logged_out()
+        );
+    }
+}
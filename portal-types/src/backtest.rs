@@ -0,0 +1,625 @@
+//! Backtests a fund's allocations over monthly returns per ticker, giving
+//! balances, calendar-year returns and drawdowns in the same shape as the
+//! fund files.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::funds::{format_month, Drawdown, Holding, YearReturns};
+
+/// Drawdowns reported, deepest first, as in the fund files.
+pub const MAX_DRAWDOWNS: usize = 10;
+
+/// When holdings are brought back to their target allocations, at the end
+/// of the month.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rebalance {
+    Never,
+    Monthly,
+    Quarterly,
+    #[default]
+    Annually,
+}
+
+impl Rebalance {
+    pub const ALL: [Rebalance; 4] = [
+        Rebalance::Never,
+        Rebalance::Monthly,
+        Rebalance::Quarterly,
+        Rebalance::Annually,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Rebalance::Never => "Never",
+            Rebalance::Monthly => "Monthly",
+            Rebalance::Quarterly => "Quarterly",
+            Rebalance::Annually => "Annually",
+        }
+    }
+
+    fn due(self, month: NaiveDate) -> bool {
+        match self {
+            Rebalance::Never => false,
+            Rebalance::Monthly => true,
+            Rebalance::Quarterly => month.month().is_multiple_of(3),
+            Rebalance::Annually => month.month() == 12,
+        }
+    }
+}
+
+/// One month of returns in percent, by ticker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReturnsRow {
+    /// The first of the month
+    pub month: NaiveDate,
+    pub returns: BTreeMap<String, f64>,
+    /// Change in consumer prices over the month
+    pub inflation: Option<f64>,
+}
+
+/// Monthly returns per ticker, oldest month first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReturnsTable {
+    rows: Vec<ReturnsRow>,
+}
+
+impl ReturnsTable {
+    pub fn new(mut rows: Vec<ReturnsRow>) -> Self {
+        rows.sort_by_key(|row| row.month);
+        rows.dedup_by_key(|row| row.month);
+        Self { rows }
+    }
+
+    /// Reads CSV text with a `month` column (YYYY-MM), an optional
+    /// `inflation` column and one column of returns per ticker, all in
+    /// percent. Empty cells are months without a figure. Blank lines and
+    /// lines starting with `#` are skipped.
+    pub fn parse_csv(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or("The file has no header row")?;
+        let columns: Vec<String> = header
+            .split(',')
+            .map(|column| column.trim().to_string())
+            .collect();
+        let month_column = columns
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case("month"))
+            .ok_or("The header has no month column")?;
+
+        let mut rows = Vec::new();
+        for (number, line) in lines {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            let month = cells
+                .get(month_column)
+                .and_then(|cell| parse_month(cell))
+                .ok_or_else(|| format!("Line {}: expected a YYYY-MM month", number))?;
+
+            let mut row = ReturnsRow {
+                month,
+                returns: BTreeMap::new(),
+                inflation: None,
+            };
+            for (i, column) in columns.iter().enumerate() {
+                let Some(cell) = cells.get(i).filter(|cell| !cell.is_empty()) else {
+                    continue;
+                };
+                if i == month_column {
+                    continue;
+                }
+                let value = cell
+                    .trim_end_matches('%')
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite() && *value > -100.0)
+                    .ok_or_else(|| format!("Line {}: '{}' is not a return", number, cell))?;
+                if column.eq_ignore_ascii_case("inflation") {
+                    row.inflation = Some(value);
+                } else {
+                    row.returns.insert(column.to_ascii_uppercase(), value);
+                }
+            }
+            rows.push(row);
+        }
+
+        Ok(Self::new(rows))
+    }
+
+    pub fn rows(&self) -> &[ReturnsRow] {
+        &self.rows
+    }
+
+    /// Every ticker with at least one month of returns.
+    pub fn tickers(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self
+            .rows
+            .iter()
+            .flat_map(|row| row.returns.keys().cloned())
+            .collect();
+        tickers.sort();
+        tickers.dedup();
+        tickers
+    }
+}
+
+/// What the returns dataset covers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReturnsSummary {
+    pub tickers: Vec<String>,
+    pub first_month: Option<NaiveDate>,
+    pub last_month: Option<NaiveDate>,
+}
+
+impl From<&ReturnsTable> for ReturnsSummary {
+    fn from(table: &ReturnsTable) -> Self {
+        Self {
+            tickers: table.tickers(),
+            first_month: table.rows.first().map(|row| row.month),
+            last_month: table.rows.last().map(|row| row.month),
+        }
+    }
+}
+
+fn parse_month(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", text.trim()), "%Y-%m-%d").ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestRequest {
+    pub holdings: Vec<Holding>,
+    #[serde(default)]
+    pub rebalance: Rebalance,
+    pub starting_balance: f64,
+    /// First month to include; defaults to the first month every holding
+    /// has a return
+    #[serde(default)]
+    pub start: Option<NaiveDate>,
+    /// Last month to include; defaults to the last month every holding has
+    /// a return
+    #[serde(default)]
+    pub end: Option<NaiveDate>,
+}
+
+/// The portfolio at the end of one month.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonthBalance {
+    pub month: NaiveDate,
+    /// Percent
+    pub return_pct: f64,
+    pub balance: f64,
+    /// In starting-month money; `None` once a month has no inflation figure
+    pub real_balance: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub starting_balance: f64,
+    pub rebalance: Rebalance,
+    pub months: Vec<MonthBalance>,
+    /// Calendar years, as in a fund's `monthly_returns`
+    pub years: Vec<YearReturns>,
+    /// Deepest first, as in a fund's `drawdowns`
+    pub drawdowns: Vec<Drawdown>,
+}
+
+impl BacktestResult {
+    pub fn end_balance(&self) -> f64 {
+        self.months
+            .last()
+            .map_or(self.starting_balance, |month| month.balance)
+    }
+}
+
+/// Runs the holdings over `table`. Every holding needs a return for every
+/// month in the range.
+pub fn backtest(table: &ReturnsTable, request: &BacktestRequest) -> Result<BacktestResult, String> {
+    if !(request.starting_balance.is_finite() && request.starting_balance > 0.0) {
+        return Err("The starting balance must be a positive amount".to_string());
+    }
+    if request.holdings.is_empty() {
+        return Err("Add at least one holding".to_string());
+    }
+    if let Some(holding) = request
+        .holdings
+        .iter()
+        .find(|holding| !(holding.allocation.is_finite() && holding.allocation > 0.0))
+    {
+        return Err(format!("{} needs a positive allocation", holding.ticker));
+    }
+    let total: f64 = request
+        .holdings
+        .iter()
+        .map(|holding| holding.allocation)
+        .sum();
+    if (total - 100.0).abs() > 0.1 {
+        return Err(format!("Allocations add up to {:.2}%, not 100%", total));
+    }
+
+    let tickers: Vec<String> = request
+        .holdings
+        .iter()
+        .map(|holding| holding.ticker.trim().to_ascii_uppercase())
+        .collect();
+    let complete = |row: &ReturnsRow| {
+        tickers
+            .iter()
+            .all(|ticker| row.returns.contains_key(ticker))
+    };
+    let start = match request.start {
+        Some(start) => start.with_day(1).unwrap_or(start),
+        None => table
+            .rows
+            .iter()
+            .find(|row| complete(row))
+            .map(|row| row.month)
+            .ok_or("No month has returns for every holding")?,
+    };
+    let end = match request.end {
+        Some(end) => end,
+        None => table
+            .rows
+            .iter()
+            .rev()
+            .find(|row| complete(row))
+            .map_or(start, |row| row.month),
+    };
+    let rows: Vec<&ReturnsRow> = table
+        .rows
+        .iter()
+        .filter(|row| row.month >= start && row.month <= end)
+        .collect();
+    if rows.is_empty() {
+        return Err("There are no returns in that range".to_string());
+    }
+
+    let weights: Vec<f64> = request
+        .holdings
+        .iter()
+        .map(|holding| holding.allocation / total)
+        .collect();
+    let mut positions: Vec<f64> = weights
+        .iter()
+        .map(|weight| request.starting_balance * weight)
+        .collect();
+    let mut balance = request.starting_balance;
+    let mut prices = Some(1.0);
+    let mut months = Vec::with_capacity(rows.len());
+
+    for (i, row) in rows.iter().enumerate() {
+        if let Some(previous) = i.checked_sub(1).map(|i| rows[i]) {
+            if next_month(previous.month) != row.month {
+                return Err(format!(
+                    "No returns for {}",
+                    format_month(next_month(previous.month))
+                ));
+            }
+        }
+        for (position, ticker) in positions.iter_mut().zip(&tickers) {
+            let rate = row
+                .returns
+                .get(ticker)
+                .ok_or_else(|| format!("No {} return for {}", ticker, format_month(row.month)))?;
+            *position *= 1.0 + rate / 100.0;
+        }
+
+        let previous = balance;
+        balance = positions.iter().sum();
+        prices = prices
+            .zip(row.inflation)
+            .map(|(prices, inflation)| prices * (1.0 + inflation / 100.0));
+        months.push(MonthBalance {
+            month: row.month,
+            return_pct: (balance / previous - 1.0) * 100.0,
+            balance,
+            real_balance: prices.map(|prices| balance / prices),
+        });
+
+        if request.rebalance.due(row.month) {
+            for (position, weight) in positions.iter_mut().zip(&weights) {
+                *position = balance * weight;
+            }
+        }
+    }
+
+    let inflation: BTreeMap<NaiveDate, Option<f64>> =
+        rows.iter().map(|row| (row.month, row.inflation)).collect();
+    Ok(BacktestResult {
+        starting_balance: request.starting_balance,
+        rebalance: request.rebalance,
+        years: years(&months, &inflation),
+        drawdowns: drawdowns(request.starting_balance, &months),
+        months,
+    })
+}
+
+/// Groups months into calendar years, compounding returns and inflation.
+pub fn years(
+    months: &[MonthBalance],
+    inflation: &BTreeMap<NaiveDate, Option<f64>>,
+) -> Vec<YearReturns> {
+    let mut years: Vec<YearReturns> = Vec::new();
+    for month in months {
+        let year = month.month.year();
+        if years.last().is_none_or(|last| last.year != year) {
+            years.push(YearReturns {
+                year,
+                months: [None; 12],
+                total: None,
+                inflation: None,
+                balance: None,
+            });
+        }
+        let current = years.last_mut().expect("a year was just pushed");
+        current.months[month.month.month0() as usize] = Some(month.return_pct);
+        current.balance = Some(month.balance);
+    }
+
+    for year in &mut years {
+        year.total = Some(year.compounded());
+        let monthly: Option<Vec<f64>> = months
+            .iter()
+            .filter(|month| month.month.year() == year.year)
+            .map(|month| inflation.get(&month.month).copied().flatten())
+            .collect();
+        year.inflation = monthly.map(|monthly| {
+            let growth: f64 = monthly.iter().map(|rate| 1.0 + rate / 100.0).product();
+            (growth - 1.0) * 100.0
+        });
+    }
+    years
+}
+
+/// Falls below a previous high, deepest first. A drawdown starts in the
+/// first month below the high and recovers in the month the high is regained.
+pub fn drawdowns(starting_balance: f64, months: &[MonthBalance]) -> Vec<Drawdown> {
+    let mut found = Vec::new();
+    let mut peak = starting_balance;
+    let mut current: Option<Drawdown> = None;
+
+    for month in months {
+        if month.balance >= peak {
+            if let Some(mut drawdown) = current.take() {
+                drawdown.recovered = Some(month.month);
+                found.push(drawdown);
+            }
+            peak = month.balance;
+            continue;
+        }
+
+        let depth = (month.balance / peak - 1.0) * 100.0;
+        let drawdown = current.get_or_insert(Drawdown {
+            rank: 0,
+            start: month.month,
+            bottom: month.month,
+            recovered: None,
+            depth,
+        });
+        if depth < drawdown.depth {
+            drawdown.depth = depth;
+            drawdown.bottom = month.month;
+        }
+    }
+    found.extend(current);
+
+    found.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+    found.truncate(MAX_DRAWDOWNS);
+    for (rank, drawdown) in found.iter_mut().enumerate() {
+        drawdown.rank = rank as u32 + 1;
+    }
+    found
+}
+
+fn next_month(month: NaiveDate) -> NaiveDate {
+    month
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    fn holding(ticker: &str, allocation: f64) -> Holding {
+        Holding {
+            ticker: ticker.to_string(),
+            name: ticker.to_string(),
+            allocation,
+            color: None,
+        }
+    }
+
+    fn request(holdings: Vec<Holding>, rebalance: Rebalance) -> BacktestRequest {
+        BacktestRequest {
+            holdings,
+            rebalance,
+            starting_balance: 10_000.0,
+            start: None,
+            end: None,
+        }
+    }
+
+    const CSV: &str = "\
+# Returns in percent
+month,inflation,VTI,TLT
+2023-11,0.5,10,0
+2023-12,0.5,10,0
+2024-01,,-50,0
+2024-02,0,100,
+";
+
+    #[test]
+    fn parses_returns() {
+        let table = ReturnsTable::parse_csv(CSV).unwrap();
+        assert_eq!(table.rows().len(), 4);
+        assert_eq!(table.tickers(), ["TLT", "VTI"]);
+        assert_eq!(table.rows()[0].month, month(2023, 11));
+        assert_eq!(table.rows()[0].inflation, Some(0.5));
+        assert_eq!(table.rows()[2].inflation, None);
+        assert!(!table.rows()[3].returns.contains_key("TLT"));
+
+        assert_eq!(
+            ReturnsTable::parse_csv("month,VTI\n2024-13,1").unwrap_err(),
+            "Line 2: expected a YYYY-MM month"
+        );
+        assert_eq!(
+            ReturnsTable::parse_csv("month,VTI\n2024-01,-100").unwrap_err(),
+            "Line 2: '-100' is not a return"
+        );
+        assert!(ReturnsTable::parse_csv("VTI\n1").is_err());
+    }
+
+    #[test]
+    fn runs_over_months_every_holding_has() {
+        let table = ReturnsTable::parse_csv(CSV).unwrap();
+        let result = backtest(
+            &table,
+            &request(vec![holding("VTI", 100.0)], Rebalance::Never),
+        )
+        .unwrap();
+
+        let balances: Vec<f64> = result.months.iter().map(|month| month.balance).collect();
+        assert_eq!(balances.len(), 4);
+        assert!((result.end_balance() - 12_100.0).abs() < 1e-6);
+        assert!((result.months[0].real_balance.unwrap() - 11_000.0 / 1.005).abs() < 1e-6);
+        assert_eq!(result.months[2].real_balance, None);
+
+        // TLT has no February return, so the mix stops in January
+        let mixed = backtest(
+            &table,
+            &request(
+                vec![holding("VTI", 50.0), holding("tlt", 50.0)],
+                Rebalance::Never,
+            ),
+        )
+        .unwrap();
+        assert_eq!(mixed.months.last().unwrap().month, month(2024, 1));
+    }
+
+    #[test]
+    fn rebalancing_changes_the_outcome() {
+        // VTI doubles then halves; TLT is flat
+        let table =
+            ReturnsTable::parse_csv("month,VTI,TLT\n2024-01,100,0\n2024-02,-50,0\n").unwrap();
+        let holdings = vec![holding("VTI", 50.0), holding("TLT", 50.0)];
+
+        let never = backtest(&table, &request(holdings.clone(), Rebalance::Never)).unwrap();
+        assert!((never.end_balance() - 10_000.0).abs() < 1e-6);
+
+        // Rebalanced to 7,500/7,500 after January, then VTI halves
+        let monthly = backtest(&table, &request(holdings, Rebalance::Monthly)).unwrap();
+        assert!((monthly.end_balance() - 11_250.0).abs() < 1e-6);
+        assert!((monthly.months[1].return_pct + 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rebalances_on_schedule() {
+        assert!(Rebalance::Quarterly.due(month(2024, 6)));
+        assert!(!Rebalance::Quarterly.due(month(2024, 5)));
+        assert!(Rebalance::Annually.due(month(2024, 12)));
+        assert!(!Rebalance::Annually.due(month(2024, 11)));
+        assert!(!Rebalance::Never.due(month(2024, 12)));
+    }
+
+    #[test]
+    fn groups_calendar_years() {
+        let table = ReturnsTable::parse_csv(CSV).unwrap();
+        let result = backtest(
+            &table,
+            &request(vec![holding("VTI", 100.0)], Rebalance::Never),
+        )
+        .unwrap();
+
+        assert_eq!(result.years.len(), 2);
+        let first = &result.years[0];
+        assert_eq!(first.year, 2023);
+        assert!((first.months[10].unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(first.months[0], None);
+        assert!((first.total.unwrap() - 21.0).abs() < 1e-9);
+        assert!((first.inflation.unwrap() - 1.0025).abs() < 1e-9);
+        assert!((first.balance.unwrap() - 12_100.0).abs() < 1e-6);
+        // January has no inflation figure
+        assert_eq!(result.years[1].inflation, None);
+    }
+
+    #[test]
+    fn finds_drawdowns() {
+        let balances = [9_000.0, 8_000.0, 10_500.0, 10_000.0, 11_000.0, 9_900.0];
+        let months: Vec<MonthBalance> = balances
+            .iter()
+            .enumerate()
+            .map(|(i, balance)| MonthBalance {
+                month: month(2024, i as u32 + 1),
+                return_pct: 0.0,
+                balance: *balance,
+                real_balance: None,
+            })
+            .collect();
+
+        let found = drawdowns(10_000.0, &months);
+        assert_eq!(found.len(), 3);
+        let deepest = &found[0];
+        assert_eq!(deepest.rank, 1);
+        assert_eq!(deepest.start, month(2024, 1));
+        assert_eq!(deepest.bottom, month(2024, 2));
+        assert_eq!(deepest.recovered, Some(month(2024, 3)));
+        assert!((deepest.depth + 20.0).abs() < 1e-9);
+        // Still under water at the end
+        assert_eq!(found[1].start, month(2024, 6));
+        assert_eq!(found[1].recovered, None);
+        assert_eq!(found[2].rank, 3);
+        assert_eq!(found[2].bottom, month(2024, 4));
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let table = ReturnsTable::parse_csv(CSV).unwrap();
+        let error = |request: BacktestRequest| backtest(&table, &request).unwrap_err();
+
+        assert_eq!(
+            error(request(vec![holding("VTI", 60.0)], Rebalance::Never)),
+            "Allocations add up to 60.00%, not 100%"
+        );
+        assert_eq!(
+            error(request(vec![holding("GLD", 100.0)], Rebalance::Never)),
+            "No month has returns for every holding"
+        );
+        assert_eq!(
+            error(BacktestRequest {
+                end: Some(month(2024, 2)),
+                ..request(
+                    vec![holding("VTI", 50.0), holding("TLT", 50.0)],
+                    Rebalance::Never
+                )
+            }),
+            "No TLT return for Feb 2024"
+        );
+        assert_eq!(
+            error(BacktestRequest {
+                starting_balance: 0.0,
+                ..request(vec![holding("VTI", 100.0)], Rebalance::Never)
+            }),
+            "The starting balance must be a positive amount"
+        );
+
+        let gappy = ReturnsTable::parse_csv("month,VTI\n2024-01,1\n2024-03,1\n").unwrap();
+        assert_eq!(
+            backtest(
+                &gappy,
+                &request(vec![holding("VTI", 100.0)], Rebalance::Never)
+            )
+            .unwrap_err(),
+            "No returns for Feb 2024"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Two to five funds, and optionally a benchmark, side by side over the
+//! months they all have: growth of $10,000, risk figures and how their
+//! monthly returns move together.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::analytics::{correlation, RelativeMetrics, ReturnSeries, RiskMetrics};
+
+pub const MIN_COMPARED: usize = 2;
+pub const MAX_COMPARED: usize = 5;
+
+/// Every growth curve starts from this balance.
+pub const GROWTH_START: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FundComparisonQuery {
+    /// Fund numbers separated by commas, e.g. "7,12"
+    pub funds: String,
+    /// A ticker from the backtest returns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<String>,
+    /// Annual percent
+    #[serde(default)]
+    pub risk_free: f64,
+}
+
+impl FundComparisonQuery {
+    /// The fund numbers in the order given, without repeats.
+    pub fn fund_numbers(&self) -> Result<Vec<u32>, String> {
+        let mut numbers: Vec<u32> = Vec::new();
+        for part in self
+            .funds
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            let number = part
+                .parse()
+                .map_err(|_| format!("'{}' is not a fund number", part))?;
+            if !numbers.contains(&number) {
+                numbers.push(number);
+            }
+        }
+        if !(MIN_COMPARED..=MAX_COMPARED).contains(&numbers.len()) {
+            return Err(format!(
+                "Choose between {} and {} funds to compare",
+                MIN_COMPARED, MAX_COMPARED
+            ));
+        }
+        Ok(numbers)
+    }
+}
+
+/// One series to compare, by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonInput {
+    pub label: String,
+    /// `None` for the benchmark
+    pub fund: Option<u32>,
+    pub returns: ReturnSeries,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GrowthPoint {
+    pub month: NaiveDate,
+    /// At the end of the month
+    pub balance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparedSeries {
+    pub label: String,
+    /// `None` for the benchmark
+    pub fund: Option<u32>,
+    /// [`GROWTH_START`] compounded month by month
+    pub growth: Vec<GrowthPoint>,
+    pub metrics: RiskMetrics,
+    /// Against the benchmark, when there is one
+    pub relative: Option<RelativeMetrics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundComparison {
+    pub first_month: NaiveDate,
+    pub last_month: NaiveDate,
+    pub risk_free: f64,
+    /// The funds in the order asked for, then the benchmark
+    pub series: Vec<ComparedSeries>,
+    /// Correlation of monthly returns between each pair, in the order of
+    /// `series`
+    pub correlations: Vec<Vec<Option<f64>>>,
+}
+
+/// Compares `funds` and `benchmark` over the months every one of them has.
+/// `risk_free` is an annual rate in percent.
+pub fn compare(
+    funds: Vec<ComparisonInput>,
+    benchmark: Option<ComparisonInput>,
+    risk_free: f64,
+) -> Result<FundComparison, String> {
+    let inputs: Vec<ComparisonInput> = funds.into_iter().chain(benchmark).collect();
+    if let Some(empty) = inputs
+        .iter()
+        .find(|input| input.returns.months().is_empty())
+    {
+        return Err(format!("{} has no monthly returns", empty.label));
+    }
+
+    let mut shared: Option<BTreeSet<NaiveDate>> = None;
+    for input in &inputs {
+        let months: BTreeSet<NaiveDate> = input
+            .returns
+            .months()
+            .iter()
+            .map(|(month, _)| *month)
+            .collect();
+        shared = Some(match shared {
+            Some(shared) => shared.intersection(&months).copied().collect(),
+            None => months,
+        });
+    }
+    let shared = shared.unwrap_or_default();
+    let (Some(&first_month), Some(&last_month)) = (shared.first(), shared.last()) else {
+        return Err("The funds have no months in common".to_string());
+    };
+
+    let aligned: Vec<ReturnSeries> = inputs
+        .iter()
+        .map(|input| {
+            ReturnSeries::new(
+                input
+                    .returns
+                    .months()
+                    .iter()
+                    .filter(|(month, _)| shared.contains(month))
+                    .copied()
+                    .collect(),
+            )
+        })
+        .collect();
+    let benchmark = inputs
+        .iter()
+        .position(|input| input.fund.is_none())
+        .map(|i| &aligned[i]);
+
+    let mut series = Vec::with_capacity(inputs.len());
+    for (input, returns) in inputs.iter().zip(&aligned) {
+        let mut balance = GROWTH_START;
+        let growth = returns
+            .months()
+            .iter()
+            .map(|(month, pct)| {
+                balance *= 1.0 + pct / 100.0;
+                GrowthPoint {
+                    month: *month,
+                    balance,
+                }
+            })
+            .collect();
+        series.push(ComparedSeries {
+            label: input.label.clone(),
+            fund: input.fund,
+            growth,
+            metrics: RiskMetrics::compute(returns, risk_free)
+                .ok_or("The funds have no months in common")?,
+            relative: benchmark
+                .map(|benchmark| RelativeMetrics::compute(returns, benchmark, risk_free)),
+        });
+    }
+
+    let correlations = aligned
+        .iter()
+        .map(|a| aligned.iter().map(|b| correlation(a, b)).collect())
+        .collect();
+
+    Ok(FundComparison {
+        first_month,
+        last_month,
+        risk_free,
+        series,
+        correlations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    fn input(label: &str, fund: Option<u32>, start: NaiveDate, returns: &[f64]) -> ComparisonInput {
+        let months = returns
+            .iter()
+            .enumerate()
+            .map(|(i, pct)| {
+                let month = start
+                    .checked_add_months(chrono::Months::new(i as u32))
+                    .unwrap();
+                (month, *pct)
+            })
+            .collect();
+        ComparisonInput {
+            label: label.to_string(),
+            fund,
+            returns: ReturnSeries::new(months),
+        }
+    }
+
+    #[test]
+    fn parses_fund_numbers() {
+        let query = |funds: &str| FundComparisonQuery {
+            funds: funds.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(query("7, 12,7").fund_numbers().unwrap(), [7, 12]);
+        assert_eq!(
+            query("7,x").fund_numbers().unwrap_err(),
+            "'x' is not a fund number"
+        );
+        assert!(query("7").fund_numbers().is_err());
+        assert!(query("1,2,3,4,5,6").fund_numbers().is_err());
+    }
+
+    #[test]
+    fn aligns_on_shared_months() {
+        // Fund 12 starts two months later and the benchmark ends a month early
+        let funds = vec![
+            input(
+                "Fund 7",
+                Some(7),
+                month(2024, 1),
+                &[1.0, 2.0, 10.0, -5.0, 3.0],
+            ),
+            input("Fund 12", Some(12), month(2024, 3), &[5.0, -2.5, 1.5]),
+        ];
+        let benchmark = input("VTI", None, month(2024, 1), &[0.0, 0.0, 4.0, -2.0]);
+
+        let comparison = compare(funds, Some(benchmark), 0.0).unwrap();
+        assert_eq!(comparison.first_month, month(2024, 3));
+        assert_eq!(comparison.last_month, month(2024, 4));
+        assert_eq!(comparison.series.len(), 3);
+
+        let fund = &comparison.series[0];
+        assert_eq!(fund.growth.len(), 2);
+        assert!((fund.growth[0].balance - 11_000.0).abs() < 1e-9);
+        assert!((fund.growth[1].balance - 10_450.0).abs() < 1e-9);
+        assert_eq!(fund.metrics.months, 2);
+        let beta = fund.relative.as_ref().unwrap().beta.unwrap();
+        assert!((beta - 2.5).abs() < 1e-9);
+        assert!(comparison.series[2].fund.is_none());
+
+        // Every series moves the same way over those two months
+        assert_eq!(comparison.correlations.len(), 3);
+        for row in &comparison.correlations {
+            for value in row {
+                assert!((value.unwrap() - 1.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn needs_shared_months() {
+        let funds = vec![
+            input("Fund 7", Some(7), month(2020, 1), &[1.0]),
+            input("Fund 8", Some(8), month(2021, 1), &[1.0]),
+        ];
+        assert_eq!(
+            compare(funds.clone(), None, 0.0).unwrap_err(),
+            "The funds have no months in common"
+        );
+
+        let empty = input("Fund 11", Some(11), month(2020, 1), &[]);
+        assert_eq!(
+            compare(vec![funds[0].clone(), empty], None, 0.0).unwrap_err(),
+            "Fund 11 has no monthly returns"
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! S&P 500 earnings for the equity risk premium.
+//!
+//! The risk premium engine only sees an `EarningsSource`; where the figures
+//! come from (the maintained CSV, an imported file) is up to the caller.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Which earnings-per-share figure the earnings yield is computed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EarningsBasis {
+    /// Reported earnings over the last twelve months
+    #[default]
+    Trailing,
+    /// Analysts' estimate for the next twelve months
+    Forward,
+}
+
+impl EarningsBasis {
+    pub fn label(self) -> &'static str {
+        match self {
+            EarningsBasis::Trailing => "trailing",
+            EarningsBasis::Forward => "forward",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "trailing" => Some(EarningsBasis::Trailing),
+            "forward" => Some(EarningsBasis::Forward),
+            _ => None,
+        }
+    }
+}
+
+/// Twelve-month S&P 500 earnings per share and where the figure came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Earnings {
+    pub as_of: NaiveDate,
+    pub eps: f64,
+    #[serde(default)]
+    pub basis: EarningsBasis,
+    /// Name of the `EarningsSource`, shown next to the figure
+    #[serde(default)]
+    pub source: String,
+}
+
+/// Anything that can say what S&P 500 earnings were known at a date.
+pub trait EarningsSource {
+    /// Shown with the earnings yield, e.g. "Maintained CSV"
+    fn name(&self) -> &str;
+
+    /// The newest figure dated on or before `date`.
+    fn earnings_at(&self, date: NaiveDate) -> Option<Earnings>;
+
+    fn latest(&self) -> Option<Earnings> {
+        self.earnings_at(NaiveDate::MAX)
+    }
+}
+
+/// One quarter of earnings; either figure may be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EarningsRow {
+    pub date: NaiveDate,
+    pub trailing_eps: Option<f64>,
+    pub forward_eps: Option<f64>,
+}
+
+impl EarningsRow {
+    pub fn eps(&self, basis: EarningsBasis) -> Option<f64> {
+        match basis {
+            EarningsBasis::Trailing => self.trailing_eps,
+            EarningsBasis::Forward => self.forward_eps,
+        }
+    }
+}
+
+/// Earnings read from CSV text with a `date` column and `trailing_eps`
+/// and/or `forward_eps` columns. Blank lines and lines starting with `#`
+/// are skipped, and an empty cell means the figure isn't known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarningsTable {
+    name: String,
+    basis: EarningsBasis,
+    /// Oldest first
+    rows: Vec<EarningsRow>,
+}
+
+impl EarningsTable {
+    pub fn new(name: impl Into<String>, basis: EarningsBasis, mut rows: Vec<EarningsRow>) -> Self {
+        rows.sort_by_key(|row| row.date);
+        Self {
+            name: name.into(),
+            basis,
+            rows,
+        }
+    }
+
+    /// Fails with the line number of the first problem.
+    pub fn parse_csv(
+        name: impl Into<String>,
+        basis: EarningsBasis,
+        text: &str,
+    ) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or("The file has no header row")?;
+        let columns: Vec<String> = header
+            .split(',')
+            .map(|column| column.trim().to_ascii_lowercase())
+            .collect();
+        let find = |name: &str| columns.iter().position(|column| column == name);
+        let date_column = find("date").ok_or("The header has no date column")?;
+        let trailing_column = find("trailing_eps");
+        let forward_column = find("forward_eps");
+        if trailing_column.is_none() && forward_column.is_none() {
+            return Err("The header needs a trailing_eps or forward_eps column".to_string());
+        }
+
+        let mut rows = Vec::new();
+        for (number, line) in lines {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            let date = cells
+                .get(date_column)
+                .and_then(|cell| NaiveDate::parse_from_str(cell, "%Y-%m-%d").ok())
+                .ok_or_else(|| format!("Line {}: expected a YYYY-MM-DD date", number))?;
+            let eps = |column: Option<usize>| -> Result<Option<f64>, String> {
+                match column
+                    .and_then(|i| cells.get(i))
+                    .filter(|cell| !cell.is_empty())
+                {
+                    None => Ok(None),
+                    Some(cell) => cell
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|eps| eps.is_finite() && *eps > 0.0)
+                        .map(Some)
+                        .ok_or_else(|| {
+                            format!("Line {}: '{}' is not a positive number", number, cell)
+                        }),
+                }
+            };
+            rows.push(EarningsRow {
+                date,
+                trailing_eps: eps(trailing_column)?,
+                forward_eps: eps(forward_column)?,
+            });
+        }
+
+        Ok(Self::new(name, basis, rows))
+    }
+
+    pub fn basis(&self) -> EarningsBasis {
+        self.basis
+    }
+
+    pub fn rows(&self) -> &[EarningsRow] {
+        &self.rows
+    }
+}
+
+impl EarningsSource for EarningsTable {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn earnings_at(&self, date: NaiveDate) -> Option<Earnings> {
+        self.rows
+            .iter()
+            .rev()
+            .filter(|row| row.date <= date)
+            .find_map(|row| {
+                Some(Earnings {
+                    as_of: row.date,
+                    eps: row.eps(self.basis)?,
+                    basis: self.basis,
+                    source: self.name.clone(),
+                })
+            })
+    }
+}
+
+/// `GET /api/earnings`: the source the equity risk premium is using.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EarningsStatus {
+    /// `None` when no source has any figures and the assumed P/E is used
+    pub source: Option<String>,
+    pub basis: EarningsBasis,
+    pub latest: Option<Earnings>,
+    pub rows: usize,
+    /// Present while an imported file overrides the maintained CSV
+    #[serde(default)]
+    pub import: Option<EarningsImport>,
+}
+
+/// Who imported the file that overrides the maintained CSV, and when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EarningsImport {
+    pub file_name: String,
+    pub imported_by: String,
+    pub imported_at: DateTime<Utc>,
+}
+
+/// `POST /api/earnings/import`: a local CSV file, read by the browser.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportEarningsRequest {
+    pub file_name: String,
+    pub contents: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    const CSV: &str = "\
+# S&P 500 earnings per share
+date,trailing_eps,forward_eps
+
+2023-09-30,185.00,
+2023-12-31,192.50,243.00
+2023-06-30, 181.00 ,230.00
+";
+
+    #[test]
+    fn parses_rows_in_date_order() {
+        let table =
+            EarningsTable::parse_csv("Maintained CSV", EarningsBasis::Trailing, CSV).unwrap();
+        let dates: Vec<NaiveDate> = table.rows().iter().map(|row| row.date).collect();
+        assert_eq!(
+            dates,
+            [date("2023-06-30"), date("2023-09-30"), date("2023-12-31")]
+        );
+        assert_eq!(table.rows()[0].trailing_eps, Some(181.0));
+        assert_eq!(table.rows()[1].forward_eps, None);
+    }
+
+    #[test]
+    fn earnings_at_uses_the_newest_figure_known_at_the_date() {
+        let table =
+            EarningsTable::parse_csv("Maintained CSV", EarningsBasis::Trailing, CSV).unwrap();
+        let earnings = table.earnings_at(date("2023-11-15")).unwrap();
+        assert_eq!(earnings.as_of, date("2023-09-30"));
+        assert_eq!(earnings.eps, 185.0);
+        assert_eq!(earnings.source, "Maintained CSV");
+        assert_eq!(earnings.basis, EarningsBasis::Trailing);
+
+        assert_eq!(table.earnings_at(date("2023-01-01")), None);
+        assert_eq!(table.latest().unwrap().eps, 192.5);
+    }
+
+    #[test]
+    fn forward_basis_skips_rows_without_an_estimate() {
+        let table = EarningsTable::parse_csv("Import", EarningsBasis::Forward, CSV).unwrap();
+        let earnings = table.earnings_at(date("2023-11-15")).unwrap();
+        assert_eq!(earnings.as_of, date("2023-06-30"));
+        assert_eq!(earnings.eps, 230.0);
+    }
+
+    #[test]
+    fn columns_can_be_in_any_order() {
+        let csv = "forward_eps,date\n250,2024-03-31\n";
+        let table = EarningsTable::parse_csv("Import", EarningsBasis::Forward, csv).unwrap();
+        assert_eq!(table.latest().unwrap().eps, 250.0);
+        assert_eq!(
+            EarningsTable::parse_csv("Import", EarningsBasis::Trailing, csv)
+                .unwrap()
+                .latest(),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_the_first_bad_line() {
+        let parse = |csv: &str| EarningsTable::parse_csv("Import", EarningsBasis::Trailing, csv);
+
+        assert_eq!(parse("").unwrap_err(), "The file has no header row");
+        assert_eq!(
+            parse("day,trailing_eps\n").unwrap_err(),
+            "The header has no date column"
+        );
+        assert_eq!(
+            parse("date,eps\n").unwrap_err(),
+            "The header needs a trailing_eps or forward_eps column"
+        );
+        assert_eq!(
+            parse("date,trailing_eps\n2023-12-31,190\n12/31/2023,190\n").unwrap_err(),
+            "Line 3: expected a YYYY-MM-DD date"
+        );
+        assert_eq!(
+            parse("date,trailing_eps\n2023-12-31,-4\n").unwrap_err(),
+            "Line 2: '-4' is not a positive number"
+        );
+    }
+
+    #[test]
+    fn basis_labels_round_trip() {
+        for basis in [EarningsBasis::Trailing, EarningsBasis::Forward] {
+            assert_eq!(EarningsBasis::from_label(basis.label()), Some(basis));
+        }
+        assert_eq!(
+            EarningsBasis::from_label(" Forward "),
+            Some(EarningsBasis::Forward)
+        );
+        assert_eq!(EarningsBasis::from_label("operating"), None);
+    }
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Stable, machine-readable reason for a failed request. The serialized
+/// names are part of the API and must not change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidCredentials,
+    InvalidTwoFactorCode,
+    /// The pending second login step timed out
+    TwoFactorExpired,
+    /// Throttled after repeated failures; see `retry_after_secs`
+    LockedOut,
+    /// Not logged in, or the session timed out
+    SessionExpired,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Validation,
+    /// The server could not be reached; produced by clients, never sent
+    ServerUnavailable,
+    Internal,
+}
+
+impl ErrorCode {
+    /// The HTTP status the server answers with for this code.
+    pub fn http_status(self) -> u16 {
+        match self {
+            ErrorCode::InvalidCredentials
+            | ErrorCode::InvalidTwoFactorCode
+            | ErrorCode::TwoFactorExpired
+            | ErrorCode::SessionExpired => 401,
+            ErrorCode::Forbidden => 403,
+            ErrorCode::NotFound => 404,
+            ErrorCode::Conflict => 409,
+            ErrorCode::Validation => 400,
+            ErrorCode::LockedOut => 429,
+            ErrorCode::ServerUnavailable => 503,
+            ErrorCode::Internal => 500,
+        }
+    }
+}
+
+/// The body of every failed request, over HTTP and IPC alike.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    /// Human-readable text, safe to show as-is
+    pub message: String,
+    /// Seconds until another attempt will be accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retry_after_secs: None,
+        }
+    }
+
+    pub fn invalid_credentials() -> Self {
+        Self::new(
+            ErrorCode::InvalidCredentials,
+            "Invalid username or password",
+        )
+    }
+
+    pub fn invalid_two_factor_code() -> Self {
+        Self::new(
+            ErrorCode::InvalidTwoFactorCode,
+            "Invalid authentication code",
+        )
+    }
+
+    pub fn two_factor_expired() -> Self {
+        Self::new(
+            ErrorCode::TwoFactorExpired,
+            "Your sign-in attempt expired, please log in again",
+        )
+    }
+
+    pub fn session_expired() -> Self {
+        Self::new(ErrorCode::SessionExpired, "Not logged in")
+    }
+
+    pub fn forbidden() -> Self {
+        Self::new(
+            ErrorCode::Forbidden,
+            "You do not have permission to do that",
+        )
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Validation, message)
+    }
+
+    pub fn server_unavailable() -> Self {
+        Self::new(
+            ErrorCode::ServerUnavailable,
+            "Cannot reach the server. Please try again.",
+        )
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn codes_serialize_as_stable_snake_case() {
+        let codes = [
+            (ErrorCode::InvalidCredentials, "invalid_credentials"),
+            (ErrorCode::InvalidTwoFactorCode, "invalid_two_factor_code"),
+            (ErrorCode::TwoFactorExpired, "two_factor_expired"),
+            (ErrorCode::LockedOut, "locked_out"),
+            (ErrorCode::SessionExpired, "session_expired"),
+            (ErrorCode::Forbidden, "forbidden"),
+            (ErrorCode::NotFound, "not_found"),
+            (ErrorCode::Conflict, "conflict"),
+            (ErrorCode::Validation, "validation"),
+            (ErrorCode::ServerUnavailable, "server_unavailable"),
+            (ErrorCode::Internal, "internal"),
+        ];
+
+        for (code, name) in codes {
+            assert_eq!(serde_json::to_value(code).unwrap(), json!(name));
+            assert_eq!(
+                serde_json::from_value::<ErrorCode>(json!(name)).unwrap(),
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn error_round_trip() {
+        let error = ApiError {
+            retry_after_secs: Some(900),
+            ..ApiError::new(ErrorCode::LockedOut, "Too many failed attempts")
+        };
+
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "code": "locked_out",
+                "message": "Too many failed attempts",
+                "retry_after_secs": 900
+            })
+        );
+        assert_eq!(serde_json::from_value::<ApiError>(value).unwrap(), error);
+
+        let value = serde_json::to_value(ApiError::invalid_credentials()).unwrap();
+        assert_eq!(
+            value,
+            json!({ "code": "invalid_credentials", "message": "Invalid username or password" })
+        );
+    }
+
+    #[test]
+    fn client_errors_map_to_4xx() {
+        assert_eq!(ErrorCode::SessionExpired.http_status(), 401);
+        assert_eq!(ErrorCode::LockedOut.http_status(), 429);
+        assert_eq!(ErrorCode::Validation.http_status(), 400);
+        assert_eq!(ErrorCode::Internal.http_status(), 500);
+    }
+}
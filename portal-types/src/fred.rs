@@ -0,0 +1,204 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// FRED series the market pages are computed from. Serialized as the FRED
+/// series id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum FredSeries {
+    #[serde(rename = "DGS10")]
+    Treasury10y,
+    #[serde(rename = "DGS2")]
+    Treasury2y,
+    #[serde(rename = "DFII10")]
+    RealYield10y,
+    #[serde(rename = "T10YIE")]
+    BreakevenInflation10y,
+    #[serde(rename = "BAMLC0A0CM")]
+    CorporateSpread,
+    #[serde(rename = "BAMLC0A0CMEY")]
+    CorporateYield,
+    #[serde(rename = "BAMLH0A0HYM2")]
+    HighYieldSpread,
+    #[serde(rename = "VIXCLS")]
+    Vix,
+    #[serde(rename = "TEDRATE")]
+    TedSpread,
+    #[serde(rename = "FEDFUNDS")]
+    FedFunds,
+    #[serde(rename = "SP500")]
+    Sp500,
+}
+
+impl FredSeries {
+    pub const ALL: [FredSeries; 11] = [
+        FredSeries::Treasury10y,
+        FredSeries::Treasury2y,
+        FredSeries::RealYield10y,
+        FredSeries::BreakevenInflation10y,
+        FredSeries::CorporateSpread,
+        FredSeries::CorporateYield,
+        FredSeries::HighYieldSpread,
+        FredSeries::Vix,
+        FredSeries::TedSpread,
+        FredSeries::FedFunds,
+        FredSeries::Sp500,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            FredSeries::Treasury10y => "DGS10",
+            FredSeries::Treasury2y => "DGS2",
+            FredSeries::RealYield10y => "DFII10",
+            FredSeries::BreakevenInflation10y => "T10YIE",
+            FredSeries::CorporateSpread => "BAMLC0A0CM",
+            FredSeries::CorporateYield => "BAMLC0A0CMEY",
+            FredSeries::HighYieldSpread => "BAMLH0A0HYM2",
+            FredSeries::Vix => "VIXCLS",
+            FredSeries::TedSpread => "TEDRATE",
+            FredSeries::FedFunds => "FEDFUNDS",
+            FredSeries::Sp500 => "SP500",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|series| series.id() == id)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FredSeries::Treasury10y => "10-Year Treasury Yield",
+            FredSeries::Treasury2y => "2-Year Treasury Yield",
+            FredSeries::RealYield10y => "10-Year TIPS Yield",
+            FredSeries::BreakevenInflation10y => "10-Year Breakeven Inflation",
+            FredSeries::CorporateSpread => "ICE BofA US Corporate Index OAS",
+            FredSeries::CorporateYield => "ICE BofA US Corporate Index Effective Yield",
+            FredSeries::HighYieldSpread => "ICE BofA US High Yield Index OAS",
+            FredSeries::Vix => "CBOE Volatility Index",
+            FredSeries::TedSpread => "TED Spread",
+            FredSeries::FedFunds => "Federal Funds Effective Rate",
+            FredSeries::Sp500 => "S&P 500",
+        }
+    }
+}
+
+/// One dated value of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+impl Observation {
+    pub fn new(date: NaiveDate, value: f64) -> Self {
+        Self { date, value }
+    }
+}
+
+/// An observation as FRED's `series/observations` endpoint returns it:
+/// both fields are strings, and a missing value is `"."`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FredObservation {
+    pub date: String,
+    pub value: String,
+}
+
+impl FredObservation {
+    /// `None` for missing values and anything that doesn't parse.
+    pub fn parse(&self) -> Option<Observation> {
+        let date = NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()?;
+        let value = self
+            .value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())?;
+        Some(Observation::new(date, value))
+    }
+}
+
+/// Query for `GET /api/fred/series`. Any FRED series id is accepted, not
+/// only the ones in `FredSeries`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FredSeriesQuery {
+    pub series_id: String,
+    #[serde(default)]
+    pub observation_start: Option<NaiveDate>,
+    #[serde(default)]
+    pub observation_end: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FredSeriesResponse {
+    pub series_id: String,
+    /// Oldest first, as FRED returns them
+    pub observations: Vec<Observation>,
+    /// When the server last fetched the series from FRED
+    pub fetched_at: DateTime<Utc>,
+    /// FRED could not be reached and this is an older cached copy
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// The most recent observation, regardless of input order.
+pub fn latest(observations: &[Observation]) -> Option<Observation> {
+    observations.iter().copied().max_by_key(|obs| obs.date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn series_serialize_as_fred_ids() {
+        for series in FredSeries::ALL {
+            assert_eq!(serde_json::to_value(series).unwrap(), json!(series.id()));
+            assert_eq!(FredSeries::from_id(series.id()), Some(series));
+        }
+        assert_eq!(FredSeries::from_id("NOPE"), None);
+    }
+
+    #[test]
+    fn parses_fred_observations() {
+        let obs = FredObservation {
+            date: "2024-03-01".into(),
+            value: "4.18".into(),
+        };
+        assert_eq!(
+            obs.parse(),
+            Some(Observation::new(date("2024-03-01"), 4.18))
+        );
+    }
+
+    #[test]
+    fn missing_values_are_skipped() {
+        for value in [".", "", "NaN", "abc"] {
+            let obs = FredObservation {
+                date: "2024-03-01".into(),
+                value: value.into(),
+            };
+            assert_eq!(obs.parse(), None, "{value:?}");
+        }
+
+        let bad_date = FredObservation {
+            date: "03/01/2024".into(),
+            value: "4.18".into(),
+        };
+        assert_eq!(bad_date.parse(), None);
+    }
+
+    #[test]
+    fn latest_picks_the_newest_date() {
+        let observations = [
+            Observation::new(date("2024-03-04"), 2.0),
+            Observation::new(date("2024-03-05"), 3.0),
+            Observation::new(date("2024-03-01"), 1.0),
+        ];
+        assert_eq!(latest(&observations), Some(observations[1]));
+        assert_eq!(latest(&[]), None);
+    }
+}
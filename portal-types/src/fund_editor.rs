@@ -0,0 +1,470 @@
+//! Editing a fund's definition: its name, starting balance, holdings and
+//! strategy text. Returns, drawdowns and the performance tables come from
+//! the fund file and are left as they are.
+//!
+//! Every save is kept as a numbered version with who made it and when, so
+//! any two versions can be compared and an earlier one restored.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::funds::{format_currency, Fund, Holding, Strategy, PERCENT_TOLERANCE};
+
+pub const MAX_NAME_CHARS: usize = 100;
+pub const MAX_HOLDINGS: usize = 30;
+pub const MAX_TICKER_CHARS: usize = 10;
+
+/// The part of a fund that is edited by hand.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FundDefinition {
+    pub name: String,
+    pub starting_balance: Option<f64>,
+    pub holdings: Vec<Holding>,
+    pub strategy: Strategy,
+}
+
+impl Fund {
+    pub fn definition(&self) -> FundDefinition {
+        FundDefinition {
+            name: self.name.clone(),
+            starting_balance: self.starting_balance,
+            holdings: self.holdings.clone(),
+            strategy: self.strategy.clone(),
+        }
+    }
+}
+
+impl FundDefinition {
+    /// Trims the text, drops blank list entries and blank colours, and
+    /// writes tickers in upper case and colours in lower case.
+    pub fn normalized(mut self) -> Self {
+        let list = |items: Vec<String>| -> Vec<String> {
+            items
+                .into_iter()
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+
+        self.name = self.name.trim().to_string();
+        for holding in &mut self.holdings {
+            holding.ticker = holding.ticker.trim().to_ascii_uppercase();
+            holding.name = holding.name.trim().to_string();
+            holding.color = holding
+                .color
+                .take()
+                .map(|color| color.trim().to_ascii_lowercase())
+                .filter(|color| !color.is_empty());
+        }
+        self.strategy = Strategy {
+            assets: list(self.strategy.assets),
+            overview: self.strategy.overview.trim().to_string(),
+            why_it_works: list(self.strategy.why_it_works),
+            best_suited: list(self.strategy.best_suited),
+            effective_during: list(self.strategy.effective_during),
+        };
+        self
+    }
+
+    /// Everything that stops the definition being saved. Unlike
+    /// [`Fund::validate`], these are errors: allocations must add up to
+    /// 100% and every holding must be complete.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push("The fund needs a name".to_string());
+        } else if self.name.chars().count() > MAX_NAME_CHARS {
+            errors.push(format!(
+                "The name is longer than {} characters",
+                MAX_NAME_CHARS
+            ));
+        }
+        if let Some(balance) = self.starting_balance {
+            if !(balance.is_finite() && balance > 0.0) {
+                errors.push("The starting balance must be more than $0".to_string());
+            }
+        }
+
+        if self.holdings.is_empty() {
+            errors.push("The fund needs at least one holding".to_string());
+        } else if self.holdings.len() > MAX_HOLDINGS {
+            errors.push(format!("A fund can have at most {} holdings", MAX_HOLDINGS));
+        }
+        for (i, holding) in self.holdings.iter().enumerate() {
+            let ticker = holding.ticker.trim();
+            if ticker.is_empty() {
+                errors.push(format!("Holding {} needs a ticker", i + 1));
+                continue;
+            }
+            if ticker.len() > MAX_TICKER_CHARS
+                || !ticker
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '^'))
+            {
+                errors.push(format!("'{}' is not a ticker", ticker));
+            }
+            if holding.name.trim().is_empty() {
+                errors.push(format!("{} needs a name", ticker));
+            }
+            if !(holding.allocation.is_finite()
+                && holding.allocation > 0.0
+                && holding.allocation <= 100.0)
+            {
+                errors.push(format!(
+                    "{} allocation must be more than 0% and at most 100%",
+                    ticker
+                ));
+            }
+            if let Some(color) = holding.color.as_deref().filter(|color| !is_color(color)) {
+                errors.push(format!(
+                    "{} colour '{}' is not a colour such as #4285f4",
+                    ticker, color
+                ));
+            }
+            if self.holdings[..i]
+                .iter()
+                .any(|other| other.ticker.trim().eq_ignore_ascii_case(ticker))
+            {
+                errors.push(format!("{} is listed twice", ticker));
+            }
+        }
+        if !self.holdings.is_empty() {
+            let total: f64 = self.holdings.iter().map(|holding| holding.allocation).sum();
+            if (total - 100.0).abs() > PERCENT_TOLERANCE {
+                errors.push(format!("Allocations add up to {:.2}%, not 100%", total));
+            }
+        }
+
+        errors
+    }
+}
+
+/// "#rrggbb", as the fund files and colour pickers write them.
+fn is_color(text: &str) -> bool {
+    text.strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FundChange {
+    /// The fund as its file stood before an edit, recorded when the fund
+    /// had no versions or its file had been changed by hand since the
+    /// latest one
+    FromFile,
+    Created,
+    Updated,
+    Restored {
+        version: u32,
+    },
+    Deleted,
+}
+
+impl FundChange {
+    pub fn label(self) -> String {
+        match self {
+            FundChange::FromFile => "Read from the file".to_string(),
+            FundChange::Created => "Created".to_string(),
+            FundChange::Updated => "Updated".to_string(),
+            FundChange::Restored { version } => format!("Restored version {}", version),
+            FundChange::Deleted => "Deleted".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundVersion {
+    /// Counts up from 1 for each fund
+    pub version: u32,
+    pub change: FundChange,
+    pub saved_by: String,
+    pub saved_at: DateTime<Utc>,
+    /// The definition after the change; for a deletion, the one deleted
+    pub definition: FundDefinition,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveFundRequest {
+    pub definition: FundDefinition,
+    /// The latest version when editing started, so a save cannot silently
+    /// replace someone else's; `None` for a fund with no versions yet
+    #[serde(default)]
+    pub base_version: Option<u32>,
+}
+
+/// The latest version when the admin chose to delete or restore a fund,
+/// checked the same way as a save's `base_version`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundVersionRequest {
+    #[serde(default)]
+    pub base_version: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundVersions {
+    pub number: u32,
+    /// What the fund file holds now; `None` once the fund is deleted
+    pub current: Option<FundDefinition>,
+    /// Oldest first
+    pub versions: Vec<FundVersion>,
+}
+
+impl FundVersions {
+    /// What a [`SaveFundRequest`] or [`FundVersionRequest`] should give as
+    /// its `base_version`.
+    pub fn latest_version(&self) -> Option<u32> {
+        self.versions.last().map(|version| version.version)
+    }
+}
+
+/// A fund whose latest version is a deletion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeletedFund {
+    pub number: u32,
+    pub name: String,
+    pub deleted_by: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundDiffQuery {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// One field that differs between two versions. A holding or list entry
+/// that was added has no `before`; one that was removed has no `after`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundDiff {
+    pub number: u32,
+    pub from: u32,
+    pub to: u32,
+    pub changes: Vec<FieldChange>,
+}
+
+/// What changed from `before` to `after`. Holdings are matched by ticker
+/// and strategy entries by their text, so reordering is not a change.
+pub fn diff(before: &FundDefinition, after: &FundDefinition) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut field = |field: String, before: Option<String>, after: Option<String>| {
+        if before != after {
+            changes.push(FieldChange {
+                field,
+                before,
+                after,
+            });
+        }
+    };
+
+    field(
+        "Name".to_string(),
+        Some(before.name.clone()),
+        Some(after.name.clone()),
+    );
+    field(
+        "Starting balance".to_string(),
+        before.starting_balance.map(format_currency),
+        after.starting_balance.map(format_currency),
+    );
+
+    let find = |holdings: &[Holding], ticker: &str| -> Option<Holding> {
+        holdings
+            .iter()
+            .find(|holding| holding.ticker == ticker)
+            .cloned()
+    };
+    for old in &before.holdings {
+        match find(&after.holdings, &old.ticker) {
+            None => field(format!("Holding {}", old.ticker), Some(describe(old)), None),
+            Some(new) => {
+                field(
+                    format!("{} name", old.ticker),
+                    Some(old.name.clone()),
+                    Some(new.name.clone()),
+                );
+                field(
+                    format!("{} allocation", old.ticker),
+                    Some(format!("{:.2}%", old.allocation)),
+                    Some(format!("{:.2}%", new.allocation)),
+                );
+                field(
+                    format!("{} colour", old.ticker),
+                    old.color.clone(),
+                    new.color,
+                );
+            }
+        }
+    }
+    for new in &after.holdings {
+        if find(&before.holdings, &new.ticker).is_none() {
+            field(format!("Holding {}", new.ticker), None, Some(describe(new)));
+        }
+    }
+
+    field(
+        "Overview".to_string(),
+        Some(before.strategy.overview.clone()),
+        Some(after.strategy.overview.clone()),
+    );
+    let lists = [
+        ("Assets", &before.strategy.assets, &after.strategy.assets),
+        (
+            "Why it works",
+            &before.strategy.why_it_works,
+            &after.strategy.why_it_works,
+        ),
+        (
+            "Best suited for",
+            &before.strategy.best_suited,
+            &after.strategy.best_suited,
+        ),
+        (
+            "Effective during",
+            &before.strategy.effective_during,
+            &after.strategy.effective_during,
+        ),
+    ];
+    for (label, old, new) in lists {
+        for item in old.iter().filter(|item| !new.contains(item)) {
+            field(label.to_string(), Some(item.clone()), None);
+        }
+        for item in new.iter().filter(|item| !old.contains(item)) {
+            field(label.to_string(), None, Some(item.clone()));
+        }
+    }
+
+    changes
+}
+
+fn describe(holding: &Holding) -> String {
+    format!("{}, {:.2}%", holding.name, holding.allocation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(ticker: &str, allocation: f64) -> Holding {
+        Holding {
+            ticker: ticker.to_string(),
+            name: format!("{} ETF", ticker),
+            allocation,
+            color: Some("#4285f4".to_string()),
+        }
+    }
+
+    fn definition() -> FundDefinition {
+        FundDefinition {
+            name: "ALL WEATHER FUND 16".to_string(),
+            starting_balance: Some(10_000.0),
+            holdings: vec![holding("VTI", 60.0), holding("TLT", 40.0)],
+            strategy: Strategy {
+                overview: "Stocks and bonds".to_string(),
+                why_it_works: vec!["Bonds rally when stocks fall".to_string()],
+                ..Strategy::default()
+            },
+        }
+    }
+
+    #[test]
+    fn normalizes_before_validating() {
+        let mut messy = definition();
+        messy.name = "  ALL WEATHER FUND 16 ".to_string();
+        messy.holdings[0].ticker = " vti".to_string();
+        messy.holdings[0].color = Some(" #4285F4 ".to_string());
+        messy.holdings[1].color = Some(String::new());
+        messy.strategy.why_it_works.push("  ".to_string());
+
+        let clean = messy.normalized();
+        assert_eq!(clean.name, "ALL WEATHER FUND 16");
+        assert_eq!(clean.holdings[0].ticker, "VTI");
+        assert_eq!(clean.holdings[0].color.as_deref(), Some("#4285f4"));
+        assert_eq!(clean.holdings[1].color, None);
+        assert_eq!(clean.strategy.why_it_works.len(), 1);
+        assert!(clean.validate().is_empty(), "{:?}", clean.validate());
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let mut bad = definition();
+        bad.name = String::new();
+        bad.starting_balance = Some(0.0);
+        bad.holdings = vec![
+            holding("VTI", 60.0),
+            holding("vti", 50.0),
+            holding("", 10.0),
+            Holding {
+                name: String::new(),
+                color: Some("blue".to_string()),
+                ..holding("GLD", -5.0)
+            },
+            holding("BAD TICKER", 5.0),
+        ];
+
+        assert_eq!(
+            bad.validate(),
+            [
+                "The fund needs a name",
+                "The starting balance must be more than $0",
+                "vti is listed twice",
+                "Holding 3 needs a ticker",
+                "GLD needs a name",
+                "GLD allocation must be more than 0% and at most 100%",
+                "GLD colour 'blue' is not a colour such as #4285f4",
+                "'BAD TICKER' is not a ticker",
+                "Allocations add up to 120.00%, not 100%",
+            ]
+        );
+
+        let empty = FundDefinition {
+            holdings: Vec::new(),
+            ..definition()
+        };
+        assert_eq!(empty.validate(), ["The fund needs at least one holding"]);
+    }
+
+    #[test]
+    fn diffs_fields_holdings_and_lists() {
+        let before = definition();
+        let mut after = definition();
+        after.name = "ALL WEATHER FUND 16B".to_string();
+        after.holdings[0].allocation = 50.0;
+        after.holdings.remove(1);
+        after.holdings.push(holding("GLD", 50.0));
+        after.strategy.why_it_works = vec!["Gold hedges inflation".to_string()];
+
+        let change = |field: &str, before: Option<&str>, after: Option<&str>| FieldChange {
+            field: field.to_string(),
+            before: before.map(str::to_string),
+            after: after.map(str::to_string),
+        };
+        assert_eq!(
+            diff(&before, &after),
+            [
+                change(
+                    "Name",
+                    Some("ALL WEATHER FUND 16"),
+                    Some("ALL WEATHER FUND 16B")
+                ),
+                change("VTI allocation", Some("60.00%"), Some("50.00%")),
+                change("Holding TLT", Some("TLT ETF, 40.00%"), None),
+                change("Holding GLD", None, Some("GLD ETF, 50.00%")),
+                change("Why it works", Some("Bonds rally when stocks fall"), None),
+                change("Why it works", None, Some("Gold hedges inflation")),
+            ]
+        );
+
+        // Reordering holdings changes nothing
+        let mut reordered = definition();
+        reordered.holdings.reverse();
+        assert!(diff(&before, &reordered).is_empty());
+    }
+}
@@ -0,0 +1,770 @@
+//! All Weather funds: the JSON files in `MS-Portal-Website/data/funds` and
+//! the typed form served by the API.
+//!
+//! The files hold display strings ("$100,000", "-0.56%"); `Fund` holds the
+//! numbers. Percentages stay in percent, so "-0.56%" becomes -0.56.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+pub const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Allocations and compounded totals are compared to this many percentage
+/// points, allowing for the files' two decimal places.
+pub(crate) const PERCENT_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub ticker: String,
+    pub name: String,
+    /// Percent of the portfolio
+    pub allocation: f64,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatUnit {
+    Currency,
+    Percent,
+    Ratio,
+}
+
+/// A figure from the performance or risk tables, e.g. "23.1% (2016)".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatValue {
+    /// `None` when the figure is undefined, e.g. a Sharpe ratio without volatility
+    pub value: Option<f64>,
+    pub unit: StatUnit,
+    /// Trailing text in parentheses, such as the year of the best year
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl StatValue {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (figure, note) = match text.split_once('(') {
+            Some((figure, rest)) => (
+                figure.trim(),
+                Some(rest.trim_end_matches(')').trim().to_string()),
+            ),
+            None => (text, None),
+        };
+
+        let (digits, unit) = if let Some(digits) = figure.strip_suffix('%') {
+            (digits.to_string(), StatUnit::Percent)
+        } else if figure.contains('$') {
+            (figure.replacen('$', "", 1), StatUnit::Currency)
+        } else {
+            (figure.to_string(), StatUnit::Ratio)
+        };
+        let value = parse_number(&digits).ok_or_else(|| format!("'{}' is not a number", text))?;
+
+        Ok(Self {
+            value: Some(value),
+            unit,
+            note,
+        })
+    }
+
+    /// The figure the way the files write it, or "—" when it is undefined.
+    pub fn format(&self) -> String {
+        let figure = match (self.value, self.unit) {
+            (None, _) => "—".to_string(),
+            (Some(value), StatUnit::Currency) => format_currency(value),
+            (Some(value), StatUnit::Percent) => format!("{:.2}%", value),
+            (Some(value), StatUnit::Ratio) => format!("{:.2}", value),
+        };
+        match &self.note {
+            Some(note) => format!("{} ({})", figure, note),
+            None => figure,
+        }
+    }
+}
+
+/// One row of the performance or risk tables.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundStat {
+    pub metric: String,
+    pub fund: StatValue,
+    pub benchmark: StatValue,
+}
+
+/// One calendar year of returns, all in percent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct YearReturns {
+    pub year: i32,
+    /// January first; `None` for months with no figure
+    pub months: [Option<f64>; 12],
+    pub total: Option<f64>,
+    pub inflation: Option<f64>,
+    /// Balance at the end of the year
+    pub balance: Option<f64>,
+}
+
+impl YearReturns {
+    /// The months compounded, in percent.
+    pub fn compounded(&self) -> f64 {
+        let growth: f64 = self
+            .months
+            .iter()
+            .flatten()
+            .map(|month| 1.0 + month / 100.0)
+            .product();
+        (growth - 1.0) * 100.0
+    }
+}
+
+/// A fall from a peak, by month. Dates are the first of the month.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Drawdown {
+    pub rank: u32,
+    /// First month below the previous high
+    pub start: NaiveDate,
+    /// Month of the lowest balance
+    pub bottom: NaiveDate,
+    /// Month the previous peak was regained, if it has been
+    pub recovered: Option<NaiveDate>,
+    /// Percent, negative
+    pub depth: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Strategy {
+    pub assets: Vec<String>,
+    pub overview: String,
+    pub why_it_works: Vec<String>,
+    pub best_suited: Vec<String>,
+    pub effective_during: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fund {
+    pub number: u32,
+    pub name: String,
+    pub starting_balance: Option<f64>,
+    pub holdings: Vec<Holding>,
+    pub performance: Vec<FundStat>,
+    pub risk_metrics: Vec<FundStat>,
+    /// Oldest year first
+    pub monthly_returns: Vec<YearReturns>,
+    pub drawdowns: Vec<Drawdown>,
+    pub strategy: Strategy,
+}
+
+impl Fund {
+    /// Converts a fund file. `number` comes from the file name and must
+    /// agree with the number inside the file, if it has one.
+    pub fn from_file(number: u32, file: FundFile) -> Result<Self, String> {
+        if let Some(inner) = file.fund_number.filter(|inner| *inner != number) {
+            return Err(format!("The file is for fund {}", inner));
+        }
+
+        let holdings = file
+            .portfolio
+            .map(|portfolio| portfolio.holdings)
+            .unwrap_or(file.holdings)
+            .into_iter()
+            .map(|holding| {
+                let allocation = holding
+                    .allocation
+                    .value()
+                    .ok_or_else(|| format!("{}: allocation is not a number", holding.ticker))?;
+                Ok(Holding {
+                    ticker: holding.ticker,
+                    name: holding.name,
+                    allocation,
+                    color: holding.color,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let stats = |rows: Vec<FileStat>| -> Result<Vec<FundStat>, String> {
+            rows.into_iter()
+                .map(|row| {
+                    let value = |text: &str| {
+                        StatValue::parse(text).map_err(|e| format!("{}: {}", row.metric, e))
+                    };
+                    Ok(FundStat {
+                        fund: value(&row.fund)?,
+                        benchmark: value(&row.benchmark)?,
+                        metric: row.metric,
+                    })
+                })
+                .collect()
+        };
+
+        let monthly_returns = file
+            .monthly_returns
+            .into_iter()
+            .map(FileYear::parse)
+            .collect::<Result<_, String>>()?;
+        let drawdowns = file
+            .drawdowns
+            .into_iter()
+            .map(FileDrawdown::parse)
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            number,
+            name: file.fund_name.trim().to_string(),
+            starting_balance: file.starting_balance,
+            holdings,
+            performance: stats(file.performance)?,
+            risk_metrics: stats(file.risk_metrics)?,
+            monthly_returns,
+            drawdowns,
+            strategy: Strategy {
+                assets: file.strategy.assets_list,
+                overview: file.strategy.overview,
+                why_it_works: file.strategy.why_works,
+                best_suited: file.strategy.best_suited,
+                effective_during: file.strategy.effective_during,
+            },
+        })
+    }
+
+    /// Problems that do not stop the fund being shown, e.g. allocations
+    /// that do not add up to 100%.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if self.holdings.is_empty() {
+            issues.push("The fund has no holdings".to_string());
+        } else {
+            let total: f64 = self.holdings.iter().map(|holding| holding.allocation).sum();
+            if (total - 100.0).abs() > PERCENT_TOLERANCE {
+                issues.push(format!("Allocations add up to {:.2}%, not 100%", total));
+            }
+        }
+        for (i, holding) in self.holdings.iter().enumerate() {
+            if holding.allocation <= 0.0 {
+                issues.push(format!("{} has no allocation", holding.ticker));
+            }
+            if self.holdings[..i]
+                .iter()
+                .any(|other| other.ticker == holding.ticker)
+            {
+                issues.push(format!("{} is listed twice", holding.ticker));
+            }
+        }
+
+        for (i, year) in self.monthly_returns.iter().enumerate() {
+            if let Some(previous) = i.checked_sub(1).map(|i| &self.monthly_returns[i]) {
+                if year.year != previous.year + 1 {
+                    issues.push(format!("{} follows {}", year.year, previous.year));
+                }
+            }
+
+            // Only the first year may start late and only the latest year
+            // may stop early, so gaps are the months missing in between
+            let is_first = i == 0;
+            let is_latest = i + 1 == self.monthly_returns.len();
+            let started = year.months.iter().position(Option::is_some).unwrap_or(0);
+            let reported = year
+                .months
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |last| last + 1);
+            let missing: Vec<&str> = year
+                .months
+                .iter()
+                .zip(MONTH_NAMES)
+                .enumerate()
+                .filter(|(m, (month, _))| {
+                    month.is_none() && !(is_first && *m < started) && !(is_latest && *m >= reported)
+                })
+                .map(|(_, (_, name))| name)
+                .collect();
+            if !missing.is_empty() {
+                issues.push(format!("{} is missing {}", year.year, missing.join(", ")));
+            }
+
+            if let Some(total) = year.total {
+                let compounded = year.compounded();
+                if (total - compounded).abs() > PERCENT_TOLERANCE {
+                    issues.push(format!(
+                        "{} total is {:.2}% but its months compound to {:.2}%",
+                        year.year, total, compounded
+                    ));
+                }
+            }
+        }
+
+        for drawdown in &self.drawdowns {
+            if drawdown.bottom < drawdown.start {
+                issues.push(format!(
+                    "Drawdown {} bottoms before it starts",
+                    drawdown.rank
+                ));
+            }
+            if drawdown
+                .recovered
+                .is_some_and(|recovered| recovered < drawdown.bottom)
+            {
+                issues.push(format!(
+                    "Drawdown {} recovers before it bottoms",
+                    drawdown.rank
+                ));
+            }
+        }
+
+        issues
+    }
+
+    pub fn summary(&self) -> FundSummary {
+        FundSummary {
+            number: self.number,
+            name: self.name.clone(),
+            holdings: self.holdings.len(),
+            first_year: self.monthly_returns.first().map(|year| year.year),
+            last_year: self.monthly_returns.last().map(|year| year.year),
+            issues: self.validate(),
+        }
+    }
+}
+
+/// A fund in the list of funds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundSummary {
+    pub number: u32,
+    pub name: String,
+    pub holdings: usize,
+    pub first_year: Option<i32>,
+    pub last_year: Option<i32>,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundResponse {
+    pub fund: Fund,
+    pub issues: Vec<String>,
+}
+
+/// A fund file as written, with camelCase keys and display strings. Older
+/// files name the fund `name` and list holdings at the top level.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundFile {
+    #[serde(default)]
+    pub fund_number: Option<u32>,
+    #[serde(alias = "name")]
+    pub fund_name: String,
+    #[serde(default)]
+    pub starting_balance: Option<f64>,
+    #[serde(default)]
+    pub portfolio: Option<FilePortfolio>,
+    #[serde(default)]
+    pub holdings: Vec<FileHolding>,
+    #[serde(default)]
+    pub performance: Vec<FileStat>,
+    #[serde(default)]
+    pub risk_metrics: Vec<FileStat>,
+    #[serde(default)]
+    pub monthly_returns: Vec<FileYear>,
+    #[serde(default)]
+    pub drawdowns: Vec<FileDrawdown>,
+    #[serde(default)]
+    pub strategy: FileStrategy,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilePortfolio {
+    pub holdings: Vec<FileHolding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHolding {
+    pub ticker: String,
+    pub name: String,
+    pub allocation: FileNumber,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// A number written either as a JSON number or as text such as "9.50%".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FileNumber {
+    Number(f64),
+    Text(String),
+}
+
+impl FileNumber {
+    pub fn value(&self) -> Option<f64> {
+        match self {
+            FileNumber::Number(value) => Some(*value).filter(|value| value.is_finite()),
+            FileNumber::Text(text) => parse_number(text.trim().trim_end_matches('%')),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStat {
+    pub metric: String,
+    pub fund: String,
+    pub benchmark: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileYear {
+    pub year: String,
+    #[serde(default)]
+    pub jan: Option<String>,
+    #[serde(default)]
+    pub feb: Option<String>,
+    #[serde(default)]
+    pub mar: Option<String>,
+    #[serde(default)]
+    pub apr: Option<String>,
+    #[serde(default)]
+    pub may: Option<String>,
+    #[serde(default)]
+    pub jun: Option<String>,
+    #[serde(default)]
+    pub jul: Option<String>,
+    #[serde(default)]
+    pub aug: Option<String>,
+    #[serde(default)]
+    pub sep: Option<String>,
+    #[serde(default)]
+    pub oct: Option<String>,
+    #[serde(default)]
+    pub nov: Option<String>,
+    #[serde(default)]
+    pub dec: Option<String>,
+    #[serde(default)]
+    pub total: Option<String>,
+    #[serde(default)]
+    pub inflation: Option<String>,
+    #[serde(default)]
+    pub balance: Option<String>,
+}
+
+impl FileYear {
+    fn parse(self) -> Result<YearReturns, String> {
+        let year: i32 = self
+            .year
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a year", self.year))?;
+        // Blank cells are months with no figure yet
+        let value = |label: &str, cell: &Option<String>| -> Result<Option<f64>, String> {
+            match cell
+                .as_deref()
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+            {
+                None => Ok(None),
+                Some(cell) => {
+                    parse_number(cell.trim_end_matches('%').replacen('$', "", 1).as_str())
+                        .map(Some)
+                        .ok_or_else(|| format!("{} {}: '{}' is not a number", label, year, cell))
+                }
+            }
+        };
+
+        let cells = [
+            &self.jan, &self.feb, &self.mar, &self.apr, &self.may, &self.jun, &self.jul, &self.aug,
+            &self.sep, &self.oct, &self.nov, &self.dec,
+        ];
+        let mut months = [None; 12];
+        for ((month, cell), name) in months.iter_mut().zip(cells).zip(MONTH_NAMES) {
+            *month = value(name, cell)?;
+        }
+
+        Ok(YearReturns {
+            year,
+            months,
+            total: value("Total", &self.total)?,
+            inflation: value("Inflation", &self.inflation)?,
+            balance: value("Balance", &self.balance)?,
+        })
+    }
+}
+
+/// Newer files give `start`, `end` and `recoveryBy`; older ones `peak`,
+/// `valley` and `recovery`. Durations are worked out from the months.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDrawdown {
+    pub rank: u32,
+    #[serde(alias = "peak")]
+    pub start: String,
+    #[serde(alias = "valley")]
+    pub end: String,
+    #[serde(default, alias = "recovery")]
+    pub recovery_by: Option<String>,
+    pub drawdown: String,
+}
+
+impl FileDrawdown {
+    fn parse(self) -> Result<Drawdown, String> {
+        let context = |e: String| format!("Drawdown {}: {}", self.rank, e);
+        let recovered = match self.recovery_by.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(text) if text.eq_ignore_ascii_case("ongoing") => None,
+            Some(text) => Some(parse_month(text).map_err(context)?),
+        };
+
+        Ok(Drawdown {
+            rank: self.rank,
+            start: parse_month(&self.start).map_err(context)?,
+            bottom: parse_month(&self.end).map_err(context)?,
+            recovered,
+            depth: parse_number(self.drawdown.trim().trim_end_matches('%'))
+                .ok_or_else(|| context(format!("'{}' is not a percentage", self.drawdown)))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStrategy {
+    #[serde(default)]
+    pub assets_list: Vec<String>,
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default)]
+    pub why_works: Vec<String>,
+    #[serde(default)]
+    pub best_suited: Vec<String>,
+    #[serde(default)]
+    pub effective_during: Vec<String>,
+}
+
+/// "-1,234.5" style numbers.
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim()
+        .replace(',', "")
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// "Jun 2008" as the first of the month.
+pub fn parse_month(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("1 {}", text.trim()), "%d %b %Y")
+        .map_err(|_| format!("'{}' is not a month such as Jun 2008", text))
+}
+
+/// The first of the month as "Jun 2008".
+pub fn format_month(date: NaiveDate) -> String {
+    format!("{} {}", MONTH_NAMES[date.month0() as usize], date.year())
+}
+
+/// "$100,000" style whole dollars.
+pub fn format_currency(value: f64) -> String {
+    let digits = format!("{:.0}", value.abs());
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    format!("{}${}", sign, grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    fn year(year: i32, months: &[f64]) -> YearReturns {
+        let mut all = [None; 12];
+        for (slot, value) in all.iter_mut().zip(months) {
+            *slot = Some(*value);
+        }
+        YearReturns {
+            year,
+            months: all,
+            total: None,
+            inflation: None,
+            balance: None,
+        }
+    }
+
+    fn fund(holdings: &[(&str, f64)], monthly_returns: Vec<YearReturns>) -> Fund {
+        Fund {
+            number: 1,
+            name: "ALL WEATHER FUND 1".to_string(),
+            starting_balance: Some(10_000.0),
+            holdings: holdings
+                .iter()
+                .map(|(ticker, allocation)| Holding {
+                    ticker: ticker.to_string(),
+                    name: ticker.to_string(),
+                    allocation: *allocation,
+                    color: None,
+                })
+                .collect(),
+            performance: Vec::new(),
+            risk_metrics: Vec::new(),
+            monthly_returns,
+            drawdowns: Vec::new(),
+            strategy: Strategy::default(),
+        }
+    }
+
+    #[test]
+    fn parses_display_strings() {
+        let currency = StatValue::parse("$100,000").unwrap();
+        assert_eq!(currency.value, Some(100_000.0));
+        assert_eq!(currency.unit, StatUnit::Currency);
+
+        let percent = StatValue::parse("-0.56%").unwrap();
+        assert_eq!(percent.value, Some(-0.56));
+        assert_eq!(percent.unit, StatUnit::Percent);
+
+        let ratio = StatValue::parse("0.36").unwrap();
+        assert_eq!(ratio.unit, StatUnit::Ratio);
+
+        let best = StatValue::parse("23.1% (2016)").unwrap();
+        assert_eq!(best.value, Some(23.1));
+        assert_eq!(best.note.as_deref(), Some("2016"));
+        assert_eq!(best.format(), "23.10% (2016)");
+
+        assert!(StatValue::parse("n/a").is_err());
+    }
+
+    #[test]
+    fn formats_currency_and_months() {
+        assert_eq!(format_currency(100_000.0), "$100,000");
+        assert_eq!(format_currency(-1_234.4), "-$1,234");
+        assert_eq!(format_currency(999.0), "$999");
+        assert_eq!(parse_month("Jun 2008"), Ok(date(2008, 6)));
+        assert_eq!(format_month(date(2008, 6)), "Jun 2008");
+        assert!(parse_month("2008-06").is_err());
+    }
+
+    #[test]
+    fn reads_both_file_layouts() {
+        let file = FundFile {
+            fund_number: None,
+            fund_name: " ALL WEATHER FUND 11 ".to_string(),
+            holdings: vec![FileHolding {
+                ticker: "VTI".to_string(),
+                name: "Vanguard Total Stock Market ETF".to_string(),
+                allocation: FileNumber::Text("14.50%".to_string()),
+                color: None,
+            }],
+            monthly_returns: vec![FileYear {
+                year: "2008".to_string(),
+                jan: Some("-0.56%".to_string()),
+                feb: Some("".to_string()),
+                balance: Some("$86,955".to_string()),
+                ..FileYear::default()
+            }],
+            drawdowns: vec![FileDrawdown {
+                rank: 1,
+                start: "Dec 2021".to_string(),
+                end: "Sep 2022".to_string(),
+                recovery_by: Some("Mar 2024".to_string()),
+                drawdown: "-14.7%".to_string(),
+            }],
+            ..FundFile::default()
+        };
+
+        let fund = Fund::from_file(11, file.clone()).unwrap();
+        assert_eq!(fund.name, "ALL WEATHER FUND 11");
+        assert_eq!(fund.holdings[0].allocation, 14.5);
+        assert_eq!(fund.monthly_returns[0].months[0], Some(-0.56));
+        assert_eq!(fund.monthly_returns[0].months[1], None);
+        assert_eq!(fund.monthly_returns[0].balance, Some(86_955.0));
+        assert_eq!(
+            fund.drawdowns[0],
+            Drawdown {
+                rank: 1,
+                start: date(2021, 12),
+                bottom: date(2022, 9),
+                recovered: Some(date(2024, 3)),
+                depth: -14.7,
+            }
+        );
+
+        let wrong = FundFile {
+            fund_number: Some(12),
+            ..file
+        };
+        assert_eq!(
+            Fund::from_file(11, wrong).unwrap_err(),
+            "The file is for fund 12"
+        );
+    }
+
+    #[test]
+    fn rejects_unreadable_values() {
+        let file = FundFile {
+            fund_name: "Fund".to_string(),
+            monthly_returns: vec![FileYear {
+                year: "2008".to_string(),
+                mar: Some("abc".to_string()),
+                ..FileYear::default()
+            }],
+            ..FundFile::default()
+        };
+        assert_eq!(
+            Fund::from_file(1, file).unwrap_err(),
+            "Mar 2008: 'abc' is not a number"
+        );
+    }
+
+    #[test]
+    fn valid_funds_have_no_issues() {
+        // The fund started in July
+        let mut first = year(2022, &[]);
+        first.months[6..].fill(Some(0.5));
+        let mut latest = year(2024, &[1.0, 2.0]);
+        latest.total = Some(3.02);
+        let fund = fund(
+            &[("VTI", 60.0), ("TLT", 40.0)],
+            vec![first, year(2023, &[0.5; 12]), latest],
+        );
+        assert!(fund.validate().is_empty(), "{:?}", fund.validate());
+    }
+
+    #[test]
+    fn reports_allocations_and_gaps() {
+        let mut gappy = year(2008, &[1.0; 12]);
+        gappy.months[2] = None;
+        gappy.total = Some(20.0);
+        let fund = fund(
+            &[("VTI", 10.0), ("VTI", 0.0)],
+            vec![gappy, year(2010, &[1.0; 3])],
+        );
+
+        assert_eq!(
+            fund.validate(),
+            [
+                "Allocations add up to 10.00%, not 100%",
+                "VTI has no allocation",
+                "VTI is listed twice",
+                "2008 is missing Mar",
+                "2008 total is 20.00% but its months compound to 11.57%",
+                "2010 follows 2008",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_drawdowns_out_of_order() {
+        let mut fund = fund(&[("VTI", 100.0)], Vec::new());
+        fund.drawdowns.push(Drawdown {
+            rank: 4,
+            start: date(2020, 7),
+            bottom: date(2020, 3),
+            recovered: Some(date(2020, 8)),
+            depth: -6.7,
+        });
+        assert_eq!(fund.validate(), ["Drawdown 4 bottoms before it starts"]);
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn health_response_round_trip() {
+        let health = HealthResponse {
+            status: "healthy".to_string(),
+            version: "0.1.0".to_string(),
+        };
+
+        let value = serde_json::to_value(&health).unwrap();
+        assert_eq!(value, json!({ "status": "healthy", "version": "0.1.0" }));
+        assert_eq!(
+            serde_json::from_value::<HealthResponse>(value).unwrap(),
+            health
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! A fund run at leverage: borrowing to hold more than the balance and
+//! paying the fed funds rate on the loan, rebalanced back to the target
+//! leverage every month or every day.
+//!
+//! Fund returns are monthly. Daily rebalancing spreads each month's return
+//! evenly over [`TRADING_DAYS_PER_MONTH`] days, so it shows the effect of
+//! resetting the loan daily but not of swings within the month, which the
+//! fund files do not record.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::analytics::{ReturnSeries, RiskMetrics};
+use crate::backtest::{self, MonthBalance};
+use crate::fred::Observation;
+use crate::funds::{format_month, Drawdown, YearReturns};
+
+/// The levels offered in the dashboard.
+pub const LEVERAGE_OPTIONS: [f64; 3] = [1.5, 2.0, 3.0];
+pub const MAX_LEVERAGE: f64 = 5.0;
+pub const TRADING_DAYS_PER_MONTH: u32 = 21;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeverageRebalance {
+    Daily,
+    #[default]
+    Monthly,
+}
+
+impl LeverageRebalance {
+    pub const ALL: [LeverageRebalance; 2] = [LeverageRebalance::Daily, LeverageRebalance::Monthly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LeverageRebalance::Daily => "Daily",
+            LeverageRebalance::Monthly => "Monthly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeverageQuery {
+    /// Exposure per dollar of balance, e.g. 2 for 2x
+    pub leverage: f64,
+    #[serde(default)]
+    pub rebalance: LeverageRebalance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LeveragedMonth {
+    pub month: NaiveDate,
+    /// Percent
+    pub fund_return: f64,
+    /// Annual percent paid on the loan
+    pub borrow_rate: f64,
+    /// Percent
+    pub leveraged_return: f64,
+    pub fund_balance: f64,
+    pub leveraged_balance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeverageResult {
+    pub leverage: f64,
+    pub rebalance: LeverageRebalance,
+    pub starting_balance: f64,
+    pub months: Vec<LeveragedMonth>,
+    /// Leveraged calendar years, as in a fund's `monthly_returns`
+    pub years: Vec<YearReturns>,
+    /// Leveraged drawdowns, deepest first
+    pub drawdowns: Vec<Drawdown>,
+    pub fund: RiskMetrics,
+    pub leveraged: RiskMetrics,
+    /// Annual percent, averaged over the months
+    pub average_borrow_rate: f64,
+    /// What multiplying the fund's CAGR suggests: leverage × CAGR less
+    /// the borrowing on the extra exposure
+    pub expected_cagr: f64,
+    /// How far the leveraged CAGR falls short of `expected_cagr`, in
+    /// percentage points, from compounding more volatile returns
+    pub volatility_drag: f64,
+    /// The month losses took the whole balance, ending the simulation
+    pub wiped_out: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeverageResponse {
+    pub result: LeverageResult,
+    /// FRED could not be reached and the borrowing rates are an older
+    /// cached copy
+    pub stale: bool,
+}
+
+/// Averages the fed funds observations of each month, in annual percent.
+pub fn monthly_rates(observations: &[Observation]) -> BTreeMap<NaiveDate, f64> {
+    let mut sums: BTreeMap<NaiveDate, (f64, u32)> = BTreeMap::new();
+    for obs in observations {
+        let Some(month) = obs.date.with_day(1) else {
+            continue;
+        };
+        let (sum, count) = sums.entry(month).or_default();
+        *sum += obs.value;
+        *count += 1;
+    }
+    sums.into_iter()
+        .map(|(month, (sum, count))| (month, sum / count as f64))
+        .collect()
+}
+
+/// Runs `series` at `query.leverage`. A month without a borrowing rate uses
+/// the latest earlier one, so a rate not yet published for the newest
+/// month does not stop the run.
+pub fn simulate(
+    series: &ReturnSeries,
+    borrow_rates: &BTreeMap<NaiveDate, f64>,
+    query: &LeverageQuery,
+    starting_balance: f64,
+) -> Result<LeverageResult, String> {
+    let leverage = query.leverage;
+    if !(leverage.is_finite() && (1.0..=MAX_LEVERAGE).contains(&leverage)) {
+        return Err(format!("Leverage must be between 1 and {}", MAX_LEVERAGE));
+    }
+    if series.months().is_empty() {
+        return Err("The fund has no monthly returns".to_string());
+    }
+
+    let mut months = Vec::with_capacity(series.months().len());
+    let mut fund_balance = starting_balance;
+    let mut leveraged_balance = starting_balance;
+    let mut wiped_out = None;
+    for &(month, fund_return) in series.months() {
+        let borrow_rate = *borrow_rates
+            .range(..=month)
+            .next_back()
+            .ok_or_else(|| format!("No fed funds rate for {}", format_month(month)))?
+            .1;
+        let growth = leveraged_growth(fund_return, borrow_rate, leverage, query.rebalance);
+
+        fund_balance *= 1.0 + fund_return / 100.0;
+        leveraged_balance = (leveraged_balance * growth).max(0.0);
+        months.push(LeveragedMonth {
+            month,
+            fund_return,
+            borrow_rate,
+            leveraged_return: (growth.max(0.0) - 1.0) * 100.0,
+            fund_balance,
+            leveraged_balance,
+        });
+        if leveraged_balance <= 0.0 {
+            wiped_out = Some(month);
+            break;
+        }
+    }
+
+    let fund_series = ReturnSeries::new(
+        months
+            .iter()
+            .map(|month| (month.month, month.fund_return))
+            .collect(),
+    );
+    let leveraged_series = ReturnSeries::new(
+        months
+            .iter()
+            .map(|month| (month.month, month.leveraged_return))
+            .collect(),
+    );
+    // Sharpe and Sortino are measured against the same cash rate as the loan
+    let average_borrow_rate =
+        months.iter().map(|month| month.borrow_rate).sum::<f64>() / months.len() as f64;
+    let fund = RiskMetrics::compute(&fund_series, average_borrow_rate)
+        .ok_or("The fund has no monthly returns")?;
+    let leveraged = RiskMetrics::compute(&leveraged_series, average_borrow_rate)
+        .ok_or("The fund has no monthly returns")?;
+    let expected_cagr = leverage * fund.cagr - (leverage - 1.0) * average_borrow_rate;
+
+    let balances: Vec<MonthBalance> = months
+        .iter()
+        .map(|month| MonthBalance {
+            month: month.month,
+            return_pct: month.leveraged_return,
+            balance: month.leveraged_balance,
+            real_balance: None,
+        })
+        .collect();
+
+    Ok(LeverageResult {
+        leverage,
+        rebalance: query.rebalance,
+        starting_balance,
+        years: backtest::years(&balances, &BTreeMap::new()),
+        drawdowns: backtest::drawdowns(starting_balance, &balances),
+        months,
+        volatility_drag: expected_cagr - leveraged.cagr,
+        expected_cagr,
+        average_borrow_rate,
+        fund,
+        leveraged,
+        wiped_out,
+    })
+}
+
+/// Growth factor of the leveraged balance over one month. Zero or less
+/// means the balance is gone.
+fn leveraged_growth(
+    fund_return: f64,
+    borrow_rate: f64,
+    leverage: f64,
+    rebalance: LeverageRebalance,
+) -> f64 {
+    let borrowed = leverage - 1.0;
+    match rebalance {
+        LeverageRebalance::Monthly => {
+            1.0 + leverage * fund_return / 100.0 - borrowed * borrow_rate / 100.0 / 12.0
+        }
+        LeverageRebalance::Daily => {
+            let days = TRADING_DAYS_PER_MONTH as f64;
+            let daily_return = (1.0 + fund_return / 100.0).powf(1.0 / days) - 1.0;
+            let daily_cost = borrow_rate / 100.0 / 12.0 / days;
+            let day = 1.0 + leverage * daily_return - borrowed * daily_cost;
+            if day <= 0.0 {
+                0.0
+            } else {
+                day.powi(TRADING_DAYS_PER_MONTH as i32)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    fn series(returns: &[f64]) -> ReturnSeries {
+        ReturnSeries::new(
+            returns
+                .iter()
+                .enumerate()
+                .map(|(i, pct)| (month(2024, i as u32 + 1), *pct))
+                .collect(),
+        )
+    }
+
+    fn query(leverage: f64, rebalance: LeverageRebalance) -> LeverageQuery {
+        LeverageQuery {
+            leverage,
+            rebalance,
+        }
+    }
+
+    #[test]
+    fn averages_rates_by_month() {
+        let obs = |date: &str, value: f64| Observation {
+            date: date.parse().unwrap(),
+            value,
+        };
+        let rates = monthly_rates(&[
+            obs("2024-01-02", 5.0),
+            obs("2024-01-03", 6.0),
+            obs("2024-02-01", 4.0),
+        ]);
+        assert_eq!(rates.len(), 2);
+        assert!((rates[&month(2024, 1)] - 5.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn leverages_monthly_returns_less_borrowing() {
+        let rates = BTreeMap::from([(month(2024, 1), 12.0)]);
+        let result = simulate(
+            &series(&[5.0, -5.0]),
+            &rates,
+            &query(2.0, LeverageRebalance::Monthly),
+            10_000.0,
+        )
+        .unwrap();
+
+        // 2 × 5% less 1% borrowing; February reuses January's rate
+        assert!((result.months[0].leveraged_return - 9.0).abs() < 1e-9);
+        assert!((result.months[1].leveraged_return + 11.0).abs() < 1e-9);
+        assert!((result.months[1].leveraged_balance - 10_000.0 * 1.09 * 0.89).abs() < 1e-6);
+        assert!((result.months[1].fund_balance - 10_000.0 * 1.05 * 0.95).abs() < 1e-6);
+        assert!((result.average_borrow_rate - 12.0).abs() < 1e-9);
+        assert_eq!(result.years.len(), 1);
+        assert_eq!(result.drawdowns.len(), 1);
+        assert_eq!(result.wiped_out, None);
+        // Volatile returns compound to less than leverage suggests
+        assert!(result.volatility_drag > 0.0);
+    }
+
+    #[test]
+    fn one_times_leverage_is_the_fund() {
+        let rates = BTreeMap::from([(month(2024, 1), 5.0)]);
+        for rebalance in LeverageRebalance::ALL {
+            let result = simulate(
+                &series(&[3.0, -2.0, 1.0]),
+                &rates,
+                &query(1.0, rebalance),
+                10_000.0,
+            )
+            .unwrap();
+            for month in &result.months {
+                assert!((month.leveraged_return - month.fund_return).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn daily_rebalancing_compounds_within_the_month() {
+        let rates = BTreeMap::from([(month(2024, 1), 0.0)]);
+        let run = |rebalance| {
+            simulate(&series(&[10.0]), &rates, &query(2.0, rebalance), 10_000.0)
+                .unwrap()
+                .months[0]
+                .leveraged_return
+        };
+        // A steady rise gains more when the exposure is topped up daily
+        assert!((run(LeverageRebalance::Monthly) - 20.0).abs() < 1e-9);
+        assert!(run(LeverageRebalance::Daily) > 20.0);
+    }
+
+    #[test]
+    fn stops_when_wiped_out() {
+        let rates = BTreeMap::from([(month(2024, 1), 0.0)]);
+        let result = simulate(
+            &series(&[1.0, -40.0, 5.0]),
+            &rates,
+            &query(3.0, LeverageRebalance::Monthly),
+            10_000.0,
+        )
+        .unwrap();
+        assert_eq!(result.wiped_out, Some(month(2024, 2)));
+        assert_eq!(result.months.len(), 2);
+        assert_eq!(result.months[1].leveraged_balance, 0.0);
+        assert!((result.months[1].leveraged_return + 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let rates = BTreeMap::from([(month(2024, 3), 5.0)]);
+        let run = |leverage| {
+            simulate(
+                &series(&[1.0]),
+                &rates,
+                &query(leverage, LeverageRebalance::Monthly),
+                10_000.0,
+            )
+            .unwrap_err()
+        };
+        assert_eq!(run(0.5), "Leverage must be between 1 and 5");
+        assert_eq!(run(f64::NAN), "Leverage must be between 1 and 5");
+        // The only rate is from after January
+        assert_eq!(run(2.0), "No fed funds rate for Jan 2024");
+    }
+}
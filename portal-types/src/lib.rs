@@ -0,0 +1,68 @@
+//! Request and response types shared by the Yew frontend, the Tauri desktop
+//! app and the Actix backend.
+
+pub mod alerts;
+pub mod analytics;
+pub mod audit;
+pub mod auth;
+pub mod backtest;
+pub mod comparison;
+pub mod earnings;
+pub mod error;
+pub mod fred;
+pub mod fund_editor;
+pub mod funds;
+pub mod health;
+pub mod leverage;
+pub mod risk_premiums;
+pub mod roles;
+pub mod two_factor;
+pub mod users;
+
+pub use alerts::{
+    AcknowledgeAlertsRequest, Alert, AlertCondition, AlertRule, AlertsResponse,
+    SaveAlertRulesRequest,
+};
+pub use analytics::{
+    RelativeMetrics, ReturnSeries, RiskMetrics, RiskMetricsQuery, RiskMetricsResponse,
+};
+pub use audit::{AuditEntry, AuditEvent, AuditOutcome, AuditQuery};
+pub use auth::{LoginRequest, LoginResponse, UserInfo};
+pub use backtest::{
+    BacktestRequest, BacktestResult, MonthBalance, Rebalance, ReturnsRow, ReturnsSummary,
+    ReturnsTable,
+};
+pub use comparison::{
+    ComparedSeries, ComparisonInput, FundComparison, FundComparisonQuery, GrowthPoint,
+};
+pub use earnings::{
+    Earnings, EarningsBasis, EarningsImport, EarningsRow, EarningsSource, EarningsStatus,
+    EarningsTable, ImportEarningsRequest,
+};
+pub use error::{ApiError, ErrorCode};
+pub use fred::{FredObservation, FredSeries, FredSeriesQuery, FredSeriesResponse, Observation};
+pub use fund_editor::{
+    DeletedFund, FieldChange, FundChange, FundDefinition, FundDiff, FundDiffQuery, FundVersion,
+    FundVersionRequest, FundVersions, SaveFundRequest,
+};
+pub use funds::{
+    Drawdown, Fund, FundFile, FundResponse, FundStat, FundSummary, Holding, StatUnit, StatValue,
+    Strategy, YearReturns,
+};
+pub use health::HealthResponse;
+pub use leverage::{
+    LeverageQuery, LeverageRebalance, LeverageResponse, LeverageResult, LeveragedMonth,
+};
+pub use risk_premiums::{
+    Band, MarketInputs, Metric, MetricHistoryQuery, MetricHistoryResponse, MetricId, MetricInput,
+    RiskPremiumReport, RiskPremiumsResponse, Signal, Unit,
+};
+pub use roles::Role;
+pub use two_factor::{
+    ConfirmTwoFactorRequest, DisableTwoFactorRequest, RecoveryCodes, TotpEnrollment,
+    TwoFactorLoginRequest,
+};
+pub use users::{
+    CreateUserRequest, ResetPasswordRequest, SetUserDisabledRequest, SetUserRoleRequest,
+    UserSummary,
+};
@@ -0,0 +1,1035 @@
+//! The Risk Premiums / Discount Rate page, computed from FRED observations.
+//!
+//! Every metric carries the formula it was computed with, the inputs that
+//! went into it and the date of its oldest input, so the server and the
+//! frontend can show the same numbers and explain them.
+//!
+//! `BAMLC0A0CM` is an option-adjusted spread over Treasuries, so it is the
+//! credit risk premium as published; the corporate bond yield comes from
+//! the matching effective yield series, `BAMLC0A0CMEY`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::earnings::{Earnings, EarningsSource};
+use crate::fred::{latest, FredSeries, Observation};
+
+/// S&P 500 P/E assumed when no earnings figure is available.
+pub const ASSUMED_PE: f64 = 19.0;
+
+/// Everything the metrics are computed from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketInputs {
+    #[serde(default)]
+    pub series: BTreeMap<FredSeries, Vec<Observation>>,
+    #[serde(default)]
+    pub earnings: Option<Earnings>,
+}
+
+impl MarketInputs {
+    pub fn with_series(mut self, series: FredSeries, observations: Vec<Observation>) -> Self {
+        self.series.insert(series, observations);
+        self
+    }
+
+    pub fn with_earnings(mut self, earnings: Earnings) -> Self {
+        self.earnings = Some(earnings);
+        self
+    }
+
+    pub fn latest(&self, series: FredSeries) -> Option<Observation> {
+        self.series
+            .get(&series)
+            .and_then(|observations| latest(observations))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricId {
+    Treasury10y,
+    Treasury2y,
+    FedFunds,
+    EarningsYield,
+    EquityRiskPremium,
+    TermPremium,
+    CreditRiskPremium,
+    HighYieldSpread,
+    LiquidityPremium,
+    RealYield10y,
+    BreakevenInflation,
+    DiscountRate,
+    Vix,
+    CorporateBondYield,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Percent,
+    Index,
+}
+
+impl MetricId {
+    /// Every metric, in the order the page shows them.
+    pub const ALL: [MetricId; 14] = [
+        MetricId::Treasury10y,
+        MetricId::Treasury2y,
+        MetricId::FedFunds,
+        MetricId::EarningsYield,
+        MetricId::EquityRiskPremium,
+        MetricId::TermPremium,
+        MetricId::CreditRiskPremium,
+        MetricId::HighYieldSpread,
+        MetricId::LiquidityPremium,
+        MetricId::RealYield10y,
+        MetricId::BreakevenInflation,
+        MetricId::DiscountRate,
+        MetricId::Vix,
+        MetricId::CorporateBondYield,
+    ];
+
+    /// The serialized name, as used in query strings.
+    pub fn id(self) -> &'static str {
+        match self {
+            MetricId::Treasury10y => "treasury10y",
+            MetricId::Treasury2y => "treasury2y",
+            MetricId::FedFunds => "fed_funds",
+            MetricId::EarningsYield => "earnings_yield",
+            MetricId::EquityRiskPremium => "equity_risk_premium",
+            MetricId::TermPremium => "term_premium",
+            MetricId::CreditRiskPremium => "credit_risk_premium",
+            MetricId::HighYieldSpread => "high_yield_spread",
+            MetricId::LiquidityPremium => "liquidity_premium",
+            MetricId::RealYield10y => "real_yield10y",
+            MetricId::BreakevenInflation => "breakeven_inflation",
+            MetricId::DiscountRate => "discount_rate",
+            MetricId::Vix => "vix",
+            MetricId::CorporateBondYield => "corporate_bond_yield",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MetricId::Treasury10y => "10-Year Treasury Yield",
+            MetricId::Treasury2y => "2-Year Treasury Yield",
+            MetricId::FedFunds => "Fed Funds Rate",
+            MetricId::EarningsYield => "S&P 500 Earnings Yield",
+            MetricId::EquityRiskPremium => "Equity Risk Premium",
+            MetricId::TermPremium => "Term Premium",
+            MetricId::CreditRiskPremium => "Credit Risk Premium",
+            MetricId::HighYieldSpread => "High Yield Spread",
+            MetricId::LiquidityPremium => "Liquidity Premium",
+            MetricId::RealYield10y => "Real Yield (10Y TIPS)",
+            MetricId::BreakevenInflation => "Breakeven Inflation",
+            MetricId::DiscountRate => "Estimated Discount Rate",
+            MetricId::Vix => "VIX Index",
+            MetricId::CorporateBondYield => "Corporate Bond Yield",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            MetricId::Treasury10y => "Risk-free rate benchmark",
+            MetricId::Treasury2y => "Short-term risk-free rate",
+            MetricId::FedFunds => "Federal Reserve policy rate",
+            MetricId::EarningsYield => "Earnings per share as a percentage of the index level",
+            MetricId::EquityRiskPremium => "Earnings yield minus the 10-year Treasury",
+            MetricId::TermPremium => "Yield curve slope: 10Y minus 2Y",
+            MetricId::CreditRiskPremium => "Investment-grade corporate spread over Treasuries",
+            MetricId::HighYieldSpread => "High yield spread over Treasuries",
+            MetricId::LiquidityPremium => "TED spread, or VIX / 10 where it is unavailable",
+            MetricId::RealYield10y => "Inflation-protected 10-year yield",
+            MetricId::BreakevenInflation => "Market-implied 10-year inflation",
+            MetricId::DiscountRate => "Cost of equity: 10Y plus the equity risk premium",
+            MetricId::Vix => "Expected S&P 500 volatility",
+            MetricId::CorporateBondYield => "Investment-grade corporate effective yield",
+        }
+    }
+
+    pub fn unit(self) -> Unit {
+        match self {
+            MetricId::Vix => Unit::Index,
+            _ => Unit::Percent,
+        }
+    }
+
+    /// FRED series the metric is computed from.
+    pub fn series(self) -> &'static [FredSeries] {
+        match self {
+            MetricId::Treasury10y => &[FredSeries::Treasury10y],
+            MetricId::Treasury2y => &[FredSeries::Treasury2y],
+            MetricId::FedFunds => &[FredSeries::FedFunds],
+            MetricId::EarningsYield => &[FredSeries::Sp500],
+            MetricId::EquityRiskPremium | MetricId::DiscountRate => {
+                &[FredSeries::Treasury10y, FredSeries::Sp500]
+            }
+            MetricId::TermPremium => &[FredSeries::Treasury10y, FredSeries::Treasury2y],
+            MetricId::CreditRiskPremium => &[FredSeries::CorporateSpread],
+            MetricId::HighYieldSpread => &[FredSeries::HighYieldSpread],
+            MetricId::LiquidityPremium => &[FredSeries::TedSpread, FredSeries::Vix],
+            MetricId::RealYield10y => &[FredSeries::RealYield10y],
+            MetricId::BreakevenInflation => &[FredSeries::BreakevenInflation10y],
+            MetricId::Vix => &[FredSeries::Vix],
+            MetricId::CorporateBondYield => &[FredSeries::CorporateYield],
+        }
+    }
+
+    /// Whether the metric depends on S&P 500 earnings.
+    pub fn uses_earnings(self) -> bool {
+        matches!(
+            self,
+            MetricId::EarningsYield | MetricId::EquityRiskPremium | MetricId::DiscountRate
+        )
+    }
+}
+
+/// How a reading compares with history, per RISK-PREMIUMS-EXPLAINED.md.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Signal {
+    Favourable,
+    Neutral,
+    Warning,
+    Crisis,
+}
+
+/// The interpretation band a value falls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Band {
+    pub signal: Signal,
+    pub label: &'static str,
+}
+
+// Upper bounds (exclusive) in ascending order; the last band is open-ended
+type BandTable = &'static [(f64, Signal, &'static str)];
+
+const TREASURY_10Y_BANDS: BandTable = &[
+    (3.0, Signal::Neutral, "Low rates, cheap money"),
+    (4.0, Signal::Neutral, "Normal rates"),
+    (5.0, Signal::Neutral, "Higher rates, expensive borrowing"),
+    (f64::INFINITY, Signal::Warning, "Very high rates"),
+];
+const FED_FUNDS_BANDS: BandTable = &[
+    (1.0, Signal::Warning, "Emergency stimulus"),
+    (3.0, Signal::Neutral, "Accommodative"),
+    (5.0, Signal::Neutral, "Neutral to restrictive"),
+    (f64::INFINITY, Signal::Warning, "Very restrictive"),
+];
+const EQUITY_RISK_PREMIUM_BANDS: BandTable = &[
+    (1.0, Signal::Crisis, "Bubble territory"),
+    (2.0, Signal::Warning, "Stocks getting expensive"),
+    (3.0, Signal::Neutral, "Fair value"),
+    (4.0, Signal::Favourable, "Stocks moderately attractive"),
+    (f64::INFINITY, Signal::Favourable, "Stocks very attractive"),
+];
+const TERM_PREMIUM_BANDS: BandTable = &[
+    (-0.5, Signal::Crisis, "Deeply inverted curve"),
+    (0.0, Signal::Warning, "Inverted curve, recession warning"),
+    (0.5, Signal::Neutral, "Flattening, economy slowing"),
+    (2.0, Signal::Favourable, "Normal upward slope"),
+    (f64::INFINITY, Signal::Favourable, "Steep curve, expansion"),
+];
+const CREDIT_RISK_PREMIUM_BANDS: BandTable = &[
+    (1.5, Signal::Favourable, "Low credit stress"),
+    (2.5, Signal::Neutral, "Normal credit spread"),
+    (4.0, Signal::Warning, "Elevated stress"),
+    (f64::INFINITY, Signal::Crisis, "Crisis"),
+];
+const HIGH_YIELD_SPREAD_BANDS: BandTable = &[
+    (4.0, Signal::Favourable, "Very calm markets"),
+    (6.0, Signal::Neutral, "Normal conditions"),
+    (8.0, Signal::Warning, "Moderate stress"),
+    (10.0, Signal::Warning, "High stress"),
+    (f64::INFINITY, Signal::Crisis, "Crisis"),
+];
+const TED_SPREAD_BANDS: BandTable = &[
+    (0.5, Signal::Favourable, "Banks trust each other"),
+    (1.0, Signal::Neutral, "Moderate stress"),
+    (2.0, Signal::Warning, "High stress"),
+    (f64::INFINITY, Signal::Crisis, "Crisis"),
+];
+const REAL_YIELD_BANDS: BandTable = &[
+    (0.0, Signal::Warning, "Losing purchasing power"),
+    (1.0, Signal::Neutral, "Low real returns"),
+    (2.0, Signal::Neutral, "Moderate real returns"),
+    (f64::INFINITY, Signal::Favourable, "Strong real returns"),
+];
+const BREAKEVEN_INFLATION_BANDS: BandTable = &[
+    (1.5, Signal::Warning, "Deflation concerns"),
+    (2.5, Signal::Favourable, "Near the Fed's 2% target"),
+    (3.0, Signal::Neutral, "Slightly above target"),
+    (f64::INFINITY, Signal::Warning, "Inflation concerns"),
+];
+const DISCOUNT_RATE_BANDS: BandTable = &[
+    (7.0, Signal::Neutral, "Low, stocks can be expensive"),
+    (9.0, Signal::Neutral, "Normal discount rate"),
+    (11.0, Signal::Neutral, "Higher, stocks should be cheaper"),
+    (f64::INFINITY, Signal::Warning, "High discount rate"),
+];
+const VIX_BANDS: BandTable = &[
+    (12.0, Signal::Warning, "Extreme complacency"),
+    (20.0, Signal::Favourable, "Calm markets"),
+    (30.0, Signal::Neutral, "Moderate fear"),
+    (40.0, Signal::Warning, "High fear"),
+    (f64::INFINITY, Signal::Crisis, "Panic"),
+];
+const CORPORATE_BOND_YIELD_BANDS: BandTable = &[
+    (5.0, Signal::Neutral, "Low yields, easy borrowing"),
+    (7.0, Signal::Neutral, "Moderate yields"),
+    (f64::INFINITY, Signal::Warning, "High yields"),
+];
+
+impl MetricId {
+    fn bands(self) -> Option<BandTable> {
+        match self {
+            MetricId::Treasury10y => Some(TREASURY_10Y_BANDS),
+            MetricId::FedFunds => Some(FED_FUNDS_BANDS),
+            MetricId::EquityRiskPremium => Some(EQUITY_RISK_PREMIUM_BANDS),
+            MetricId::TermPremium => Some(TERM_PREMIUM_BANDS),
+            MetricId::CreditRiskPremium => Some(CREDIT_RISK_PREMIUM_BANDS),
+            MetricId::HighYieldSpread => Some(HIGH_YIELD_SPREAD_BANDS),
+            MetricId::LiquidityPremium => Some(TED_SPREAD_BANDS),
+            MetricId::RealYield10y => Some(REAL_YIELD_BANDS),
+            MetricId::BreakevenInflation => Some(BREAKEVEN_INFLATION_BANDS),
+            MetricId::DiscountRate => Some(DISCOUNT_RATE_BANDS),
+            MetricId::Vix => Some(VIX_BANDS),
+            MetricId::CorporateBondYield => Some(CORPORATE_BOND_YIELD_BANDS),
+            // Only meaningful next to other rates
+            MetricId::Treasury2y | MetricId::EarningsYield => None,
+        }
+    }
+
+    /// The band `value` falls in, for metrics that have them.
+    pub fn band(self, value: f64) -> Option<Band> {
+        let bands = self.bands()?;
+        bands
+            .iter()
+            .find(|(upper, _, _)| value < *upper)
+            .or(bands.last())
+            .map(|&(_, signal, label)| Band { signal, label })
+    }
+}
+
+/// A value a metric was computed from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricInput {
+    /// FRED series id, metric label or assumption name
+    pub name: String,
+    pub value: f64,
+    /// `None` for assumptions
+    pub date: Option<NaiveDate>,
+}
+
+impl MetricInput {
+    fn series(series: FredSeries, observation: Observation) -> Self {
+        Self {
+            name: series.id().to_string(),
+            value: observation.value,
+            date: Some(observation.date),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub id: MetricId,
+    /// `None` when an input is missing
+    pub value: Option<f64>,
+    pub formula: String,
+    pub inputs: Vec<MetricInput>,
+    /// Date of the oldest dated input
+    pub as_of: Option<NaiveDate>,
+}
+
+impl Metric {
+    fn new(
+        id: MetricId,
+        formula: impl Into<String>,
+        inputs: Vec<Option<MetricInput>>,
+        f: impl FnOnce(&[f64]) -> f64,
+    ) -> Self {
+        let complete = inputs.iter().all(Option::is_some);
+        let inputs: Vec<MetricInput> = inputs.into_iter().flatten().collect();
+        let values: Vec<f64> = inputs.iter().map(|input| input.value).collect();
+
+        Self {
+            id,
+            value: complete.then(|| f(&values)),
+            formula: formula.into(),
+            as_of: inputs
+                .iter()
+                .filter_map(|input| input.date)
+                .min()
+                .filter(|_| complete),
+            inputs,
+        }
+    }
+
+    /// The interpretation band of the value. A liquidity premium that fell
+    /// back to the VIX is read on the VIX scale.
+    pub fn band(&self) -> Option<Band> {
+        let value = self.value?;
+        let vix_fallback = self.id == MetricId::LiquidityPremium
+            && self
+                .inputs
+                .first()
+                .is_some_and(|input| input.name == FredSeries::Vix.id());
+        if vix_fallback {
+            MetricId::Vix.band(value * 10.0)
+        } else {
+            self.id.band(value)
+        }
+    }
+
+    /// This metric as the input of another.
+    fn as_input(&self) -> Option<MetricInput> {
+        Some(MetricInput {
+            name: self.id.label().to_string(),
+            value: self.value?,
+            date: self.as_of,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskPremiumReport {
+    /// In `MetricId::ALL` order
+    pub metrics: Vec<Metric>,
+    /// Date of the newest observation used
+    pub as_of: Option<NaiveDate>,
+    /// The earnings behind the earnings yield; `None` when the assumed P/E
+    /// was used
+    #[serde(default)]
+    pub earnings: Option<Earnings>,
+}
+
+impl RiskPremiumReport {
+    pub fn metric(&self, id: MetricId) -> Option<&Metric> {
+        self.metrics.iter().find(|metric| metric.id == id)
+    }
+
+    pub fn value(&self, id: MetricId) -> Option<f64> {
+        self.metric(id).and_then(|metric| metric.value)
+    }
+}
+
+/// `GET /api/risk-premiums`: the report with how current its data is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskPremiumsResponse {
+    pub report: RiskPremiumReport,
+    /// When the least recently fetched series came from FRED
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Some series could not be refreshed and are older cached copies
+    #[serde(default)]
+    pub stale: bool,
+    /// Series that could not be loaded at all
+    #[serde(default)]
+    pub missing: Vec<FredSeries>,
+}
+
+/// Query for `GET /api/risk-premiums/history`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricHistoryQuery {
+    pub metric: MetricId,
+    #[serde(default)]
+    pub observation_start: Option<NaiveDate>,
+    #[serde(default)]
+    pub observation_end: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricHistoryResponse {
+    pub metric: MetricId,
+    /// Oldest first
+    pub observations: Vec<Observation>,
+    /// When the least recently fetched series came from FRED
+    pub fetched_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub stale: bool,
+    #[serde(default)]
+    pub missing: Vec<FredSeries>,
+}
+
+/// Longest an observation is carried forward to a date its series has no
+/// value for: enough to bridge holidays that differ between series, short
+/// enough that a discontinued series (TEDRATE) drops out.
+pub const MAX_CARRY_DAYS: i64 = 7;
+
+/// Computes every metric from the latest observation of each series.
+pub fn compute(inputs: &MarketInputs) -> RiskPremiumReport {
+    let series = |series: FredSeries| {
+        inputs
+            .latest(series)
+            .map(|obs| MetricInput::series(series, obs))
+    };
+    let published = |id: MetricId, source: FredSeries| {
+        Metric::new(id, source.id(), vec![series(source)], |v| v[0])
+    };
+
+    let treasury_10y = published(MetricId::Treasury10y, FredSeries::Treasury10y);
+
+    let earnings_yield = match &inputs.earnings {
+        Some(earnings) => {
+            let eps = MetricInput {
+                name: format!("S&P 500 {} EPS", earnings.basis.label()),
+                value: earnings.eps,
+                date: Some(earnings.as_of),
+            };
+            Metric::new(
+                MetricId::EarningsYield,
+                "EPS / SP500 × 100",
+                vec![Some(eps), series(FredSeries::Sp500)],
+                |v| v[0] / v[1] * 100.0,
+            )
+        }
+        None => {
+            let pe = MetricInput {
+                name: "Assumed P/E".to_string(),
+                value: ASSUMED_PE,
+                date: None,
+            };
+            Metric::new(
+                MetricId::EarningsYield,
+                "100 / assumed P/E",
+                vec![Some(pe)],
+                |v| 100.0 / v[0],
+            )
+        }
+    };
+
+    let equity_risk_premium = Metric::new(
+        MetricId::EquityRiskPremium,
+        "Earnings yield - DGS10",
+        vec![earnings_yield.as_input(), series(FredSeries::Treasury10y)],
+        |v| v[0] - v[1],
+    );
+
+    let discount_rate = Metric::new(
+        MetricId::DiscountRate,
+        "DGS10 + equity risk premium",
+        vec![
+            series(FredSeries::Treasury10y),
+            equity_risk_premium.as_input(),
+        ],
+        |v| v[0] + v[1],
+    );
+
+    let term_premium = Metric::new(
+        MetricId::TermPremium,
+        "DGS10 - DGS2",
+        vec![
+            series(FredSeries::Treasury10y),
+            series(FredSeries::Treasury2y),
+        ],
+        |v| v[0] - v[1],
+    );
+
+    // TEDRATE stopped with LIBOR; VIX / 10 stands in for it
+    let liquidity_premium = match series(FredSeries::TedSpread) {
+        Some(ted) => Metric::new(
+            MetricId::LiquidityPremium,
+            "TEDRATE",
+            vec![Some(ted)],
+            |v| v[0],
+        ),
+        None => Metric::new(
+            MetricId::LiquidityPremium,
+            "VIXCLS / 10",
+            vec![series(FredSeries::Vix)],
+            |v| v[0] / 10.0,
+        ),
+    };
+
+    let metrics = vec![
+        treasury_10y,
+        published(MetricId::Treasury2y, FredSeries::Treasury2y),
+        published(MetricId::FedFunds, FredSeries::FedFunds),
+        earnings_yield,
+        equity_risk_premium,
+        term_premium,
+        published(MetricId::CreditRiskPremium, FredSeries::CorporateSpread),
+        published(MetricId::HighYieldSpread, FredSeries::HighYieldSpread),
+        liquidity_premium,
+        published(MetricId::RealYield10y, FredSeries::RealYield10y),
+        published(
+            MetricId::BreakevenInflation,
+            FredSeries::BreakevenInflation10y,
+        ),
+        discount_rate,
+        published(MetricId::Vix, FredSeries::Vix),
+        published(MetricId::CorporateBondYield, FredSeries::CorporateYield),
+    ];
+
+    let as_of = metrics
+        .iter()
+        .flat_map(|metric| &metric.inputs)
+        .filter_map(|input| input.date)
+        .max();
+
+    RiskPremiumReport {
+        metrics,
+        as_of,
+        earnings: inputs.earnings.clone(),
+    }
+}
+
+/// The metric on every date one of its series has an observation, oldest
+/// first. Each date is computed like `compute` from the latest observation
+/// of each series at that date and the earnings `earnings` had published by
+/// then; `inputs.earnings` is not used. Without a source, earnings-based
+/// metrics use the assumed P/E throughout; with one, dates before its first
+/// figure are left out.
+pub fn history(
+    metric: MetricId,
+    inputs: &MarketInputs,
+    earnings: Option<&dyn EarningsSource>,
+) -> Vec<Observation> {
+    let series: Vec<(FredSeries, Vec<Observation>)> = metric
+        .series()
+        .iter()
+        .filter_map(|&series| {
+            let mut observations = inputs.series.get(&series)?.clone();
+            observations.sort_by_key(|obs| obs.date);
+            Some((series, observations))
+        })
+        .collect();
+    let dates: BTreeSet<NaiveDate> = series
+        .iter()
+        .flat_map(|(_, observations)| observations.iter().map(|obs| obs.date))
+        .collect();
+
+    // Index of the first observation after the current date, per series
+    let mut cursors = vec![0; series.len()];
+    dates
+        .into_iter()
+        .filter_map(|date| {
+            let mut day = MarketInputs {
+                series: BTreeMap::new(),
+                earnings: earnings.and_then(|source| source.earnings_at(date)),
+            };
+            if earnings.is_some() && metric.uses_earnings() && day.earnings.is_none() {
+                return None;
+            }
+            for ((id, observations), cursor) in series.iter().zip(cursors.iter_mut()) {
+                while *cursor < observations.len() && observations[*cursor].date <= date {
+                    *cursor += 1;
+                }
+                let carried = cursor
+                    .checked_sub(1)
+                    .map(|i| observations[i])
+                    .filter(|obs| (date - obs.date).num_days() <= MAX_CARRY_DAYS);
+                if let Some(obs) = carried {
+                    day.series.insert(*id, vec![obs]);
+                }
+            }
+            compute(&day)
+                .value(metric)
+                .map(|value| Observation::new(date, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::earnings::{EarningsBasis, EarningsRow, EarningsTable};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn obs(d: &str, value: f64) -> Vec<Observation> {
+        vec![Observation::new(date(d), value)]
+    }
+
+    fn full_inputs() -> MarketInputs {
+        MarketInputs::default()
+            .with_series(FredSeries::Treasury10y, obs("2024-03-05", 4.0))
+            .with_series(FredSeries::Treasury2y, obs("2024-03-05", 4.5))
+            .with_series(FredSeries::RealYield10y, obs("2024-03-05", 1.8))
+            .with_series(FredSeries::BreakevenInflation10y, obs("2024-03-05", 2.2))
+            .with_series(FredSeries::CorporateSpread, obs("2024-03-04", 0.9))
+            .with_series(FredSeries::CorporateYield, obs("2024-03-04", 5.3))
+            .with_series(FredSeries::HighYieldSpread, obs("2024-03-04", 3.1))
+            .with_series(FredSeries::Vix, obs("2024-03-05", 14.0))
+            .with_series(FredSeries::FedFunds, obs("2024-02-01", 5.33))
+            .with_series(FredSeries::Sp500, obs("2024-03-05", 5000.0))
+    }
+
+    fn earnings(d: &str, eps: f64) -> Earnings {
+        Earnings {
+            as_of: date(d),
+            eps,
+            basis: EarningsBasis::Trailing,
+            source: "Maintained CSV".to_string(),
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("metric has a value");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn computes_every_metric_in_page_order() {
+        let report = compute(&full_inputs());
+        let ids: Vec<MetricId> = report.metrics.iter().map(|m| m.id).collect();
+        assert_eq!(ids, MetricId::ALL);
+        assert!(report.metrics.iter().all(|m| m.value.is_some()));
+        assert_eq!(report.as_of, Some(date("2024-03-05")));
+    }
+
+    #[test]
+    fn published_series_pass_through() {
+        let report = compute(&full_inputs());
+        assert_close(report.value(MetricId::Treasury10y), 4.0);
+        assert_close(report.value(MetricId::Treasury2y), 4.5);
+        assert_close(report.value(MetricId::FedFunds), 5.33);
+        assert_close(report.value(MetricId::CreditRiskPremium), 0.9);
+        assert_close(report.value(MetricId::HighYieldSpread), 3.1);
+        assert_close(report.value(MetricId::RealYield10y), 1.8);
+        assert_close(report.value(MetricId::BreakevenInflation), 2.2);
+        assert_close(report.value(MetricId::Vix), 14.0);
+        assert_close(report.value(MetricId::CorporateBondYield), 5.3);
+    }
+
+    #[test]
+    fn term_premium_is_the_curve_slope() {
+        let report = compute(&full_inputs());
+        let metric = report.metric(MetricId::TermPremium).unwrap();
+        assert_close(metric.value, -0.5);
+        assert_eq!(metric.formula, "DGS10 - DGS2");
+        let names: Vec<&str> = metric.inputs.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["DGS10", "DGS2"]);
+    }
+
+    #[test]
+    fn equity_premium_uses_the_assumed_pe_without_earnings() {
+        let report = compute(&full_inputs());
+        let earnings_yield = 100.0 / ASSUMED_PE;
+        assert_close(report.value(MetricId::EarningsYield), earnings_yield);
+        assert_close(
+            report.value(MetricId::EquityRiskPremium),
+            earnings_yield - 4.0,
+        );
+        assert_close(report.value(MetricId::DiscountRate), earnings_yield);
+
+        let metric = report.metric(MetricId::EarningsYield).unwrap();
+        assert_eq!(metric.inputs[0].date, None);
+        assert_eq!(metric.as_of, None);
+        // Dated by the Treasury yield alone
+        assert_eq!(
+            report.metric(MetricId::EquityRiskPremium).unwrap().as_of,
+            Some(date("2024-03-05"))
+        );
+    }
+
+    #[test]
+    fn equity_premium_uses_supplied_earnings() {
+        let inputs = full_inputs().with_earnings(earnings("2023-12-31", 220.0));
+        let report = compute(&inputs);
+
+        // 220 / 5000 = 4.4%
+        assert_close(report.value(MetricId::EarningsYield), 4.4);
+        assert_close(report.value(MetricId::EquityRiskPremium), 0.4);
+        assert_close(report.value(MetricId::DiscountRate), 4.4);
+
+        let metric = report.metric(MetricId::EquityRiskPremium).unwrap();
+        assert_eq!(metric.as_of, Some(date("2023-12-31")));
+        assert_eq!(metric.inputs[0].name, "S&P 500 Earnings Yield");
+
+        let metric = report.metric(MetricId::EarningsYield).unwrap();
+        assert_eq!(metric.inputs[0].name, "S&P 500 trailing EPS");
+        assert_eq!(report.earnings.as_ref().unwrap().source, "Maintained CSV");
+    }
+
+    #[test]
+    fn liquidity_premium_prefers_ted_spread() {
+        let report = compute(&full_inputs());
+        let metric = report.metric(MetricId::LiquidityPremium).unwrap();
+        assert_close(metric.value, 1.4);
+        assert_eq!(metric.formula, "VIXCLS / 10");
+
+        let inputs = full_inputs().with_series(FredSeries::TedSpread, obs("2022-01-21", 0.09));
+        let report = compute(&inputs);
+        let metric = report.metric(MetricId::LiquidityPremium).unwrap();
+        assert_close(metric.value, 0.09);
+        assert_eq!(metric.formula, "TEDRATE");
+        assert_eq!(metric.as_of, Some(date("2022-01-21")));
+    }
+
+    #[test]
+    fn latest_observation_is_used() {
+        let inputs = full_inputs().with_series(
+            FredSeries::Treasury10y,
+            vec![
+                Observation::new(date("2024-03-05"), 4.1),
+                Observation::new(date("2024-03-04"), 4.0),
+            ],
+        );
+        let report = compute(&inputs);
+        assert_close(report.value(MetricId::Treasury10y), 4.1);
+        assert_close(report.value(MetricId::TermPremium), 4.1 - 4.5);
+    }
+
+    #[test]
+    fn missing_inputs_leave_the_metric_empty() {
+        let mut inputs = full_inputs();
+        inputs.series.remove(&FredSeries::Treasury2y);
+        inputs.series.remove(&FredSeries::Vix);
+        let report = compute(&inputs);
+
+        let term = report.metric(MetricId::TermPremium).unwrap();
+        assert_eq!(term.value, None);
+        assert_eq!(term.as_of, None);
+        // What was available is still reported
+        assert_eq!(term.inputs.len(), 1);
+
+        assert_eq!(report.value(MetricId::Vix), None);
+        assert_eq!(report.value(MetricId::LiquidityPremium), None);
+        assert!(report.value(MetricId::Treasury10y).is_some());
+    }
+
+    #[test]
+    fn missing_treasury_empties_derived_metrics() {
+        let mut inputs = full_inputs();
+        inputs.series.remove(&FredSeries::Treasury10y);
+        let report = compute(&inputs);
+
+        assert!(report.value(MetricId::EarningsYield).is_some());
+        assert_eq!(report.value(MetricId::EquityRiskPremium), None);
+        assert_eq!(report.value(MetricId::DiscountRate), None);
+    }
+
+    #[test]
+    fn empty_inputs_compute_nothing() {
+        let report = compute(&MarketInputs::default());
+        assert_eq!(report.metrics.len(), MetricId::ALL.len());
+        assert_eq!(report.as_of, None);
+        let with_values: Vec<MetricId> = report
+            .metrics
+            .iter()
+            .filter(|m| m.value.is_some())
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(with_values, [MetricId::EarningsYield]);
+    }
+
+    #[test]
+    fn inputs_round_trip_through_json() {
+        let inputs = full_inputs().with_earnings(earnings("2023-12-31", 220.0));
+        let json = serde_json::to_value(&inputs).unwrap();
+        assert_eq!(json["series"]["DGS10"][0]["value"], 4.0);
+        let back: MarketInputs = serde_json::from_value(json).unwrap();
+        assert_eq!(back, inputs);
+        assert_eq!(compute(&back), compute(&inputs));
+    }
+
+    #[test]
+    fn report_serializes_metric_ids_in_snake_case() {
+        let report = compute(&full_inputs());
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["metrics"][4]["id"], "equity_risk_premium");
+        assert_eq!(json["as_of"], "2024-03-05");
+    }
+
+    #[test]
+    fn bands_follow_the_reference_table() {
+        let signal = |id: MetricId, value: f64| id.band(value).unwrap().signal;
+
+        assert_eq!(signal(MetricId::EquityRiskPremium, 4.5), Signal::Favourable);
+        assert_eq!(signal(MetricId::EquityRiskPremium, 2.5), Signal::Neutral);
+        assert_eq!(signal(MetricId::EquityRiskPremium, 1.5), Signal::Warning);
+        assert_eq!(signal(MetricId::EquityRiskPremium, 0.5), Signal::Crisis);
+
+        assert_eq!(signal(MetricId::TermPremium, 1.0), Signal::Favourable);
+        assert_eq!(signal(MetricId::TermPremium, -0.2), Signal::Warning);
+        assert_eq!(signal(MetricId::TermPremium, -0.8), Signal::Crisis);
+
+        assert_eq!(signal(MetricId::HighYieldSpread, 3.0), Signal::Favourable);
+        assert_eq!(signal(MetricId::HighYieldSpread, 12.0), Signal::Crisis);
+        assert_eq!(signal(MetricId::Vix, 15.0), Signal::Favourable);
+        assert_eq!(signal(MetricId::Vix, 45.0), Signal::Crisis);
+        assert_eq!(
+            signal(MetricId::BreakevenInflation, 2.0),
+            Signal::Favourable
+        );
+        assert_eq!(signal(MetricId::BreakevenInflation, 3.4), Signal::Warning);
+    }
+
+    #[test]
+    fn band_bounds_belong_to_the_upper_band() {
+        let band = |value: f64| MetricId::Vix.band(value).unwrap().label;
+        assert_eq!(band(11.99), "Extreme complacency");
+        assert_eq!(band(12.0), "Calm markets");
+        assert_eq!(band(40.0), "Panic");
+    }
+
+    #[test]
+    fn unbanded_metrics_have_no_band() {
+        assert_eq!(MetricId::Treasury2y.band(4.0), None);
+        assert_eq!(MetricId::EarningsYield.band(5.0), None);
+    }
+
+    #[test]
+    fn liquidity_from_vix_is_read_on_the_vix_scale() {
+        let report = compute(&full_inputs());
+        let metric = report.metric(MetricId::LiquidityPremium).unwrap();
+        // VIX 14 is calm, although 1.4 would be high stress as a TED spread
+        assert_eq!(metric.band().unwrap().label, "Calm markets");
+
+        let inputs = full_inputs().with_series(FredSeries::TedSpread, obs("2022-01-21", 1.4));
+        let report = compute(&inputs);
+        let metric = report.metric(MetricId::LiquidityPremium).unwrap();
+        assert_eq!(metric.band().unwrap().signal, Signal::Warning);
+    }
+
+    #[test]
+    fn metrics_without_values_have_no_band() {
+        let report = compute(&MarketInputs::default());
+        assert_eq!(report.metric(MetricId::Vix).unwrap().band(), None);
+    }
+
+    #[test]
+    fn metric_ids_match_serde_names() {
+        for id in MetricId::ALL {
+            assert_eq!(
+                serde_json::to_value(id).unwrap(),
+                serde_json::json!(id.id())
+            );
+        }
+    }
+
+    #[test]
+    fn every_metric_has_a_label_and_description() {
+        for id in MetricId::ALL {
+            assert!(!id.label().is_empty());
+            assert!(!id.description().is_empty());
+        }
+        assert_eq!(MetricId::Vix.unit(), Unit::Index);
+        assert_eq!(MetricId::TermPremium.unit(), Unit::Percent);
+    }
+
+    #[test]
+    fn every_metric_is_computed_from_its_series() {
+        let all = full_inputs();
+        for id in MetricId::ALL {
+            let mut inputs = MarketInputs::default();
+            for &series in id.series() {
+                if let Some(observations) = all.series.get(&series) {
+                    inputs.series.insert(series, observations.clone());
+                }
+            }
+            assert!(compute(&inputs).value(id).is_some(), "{id:?}");
+        }
+    }
+
+    fn daily(values: &[(&str, f64)]) -> Vec<Observation> {
+        values
+            .iter()
+            .map(|&(d, value)| Observation::new(date(d), value))
+            .collect()
+    }
+
+    #[test]
+    fn history_aligns_series_by_date() {
+        let inputs = MarketInputs::default()
+            .with_series(
+                FredSeries::Treasury10y,
+                daily(&[
+                    ("2024-03-05", 4.1),
+                    ("2024-03-01", 4.0),
+                    ("2024-03-04", 4.2),
+                ]),
+            )
+            .with_series(
+                FredSeries::Treasury2y,
+                daily(&[("2024-03-01", 4.5), ("2024-03-05", 4.0)]),
+            );
+
+        let history = history(MetricId::TermPremium, &inputs, None);
+        let dates: Vec<NaiveDate> = history.iter().map(|obs| obs.date).collect();
+        assert_eq!(
+            dates,
+            [date("2024-03-01"), date("2024-03-04"), date("2024-03-05")]
+        );
+        assert_close(Some(history[0].value), -0.5);
+        // DGS2 has no value on the 4th; the 1st carries forward
+        assert_close(Some(history[1].value), -0.3);
+        assert_close(Some(history[2].value), 0.1);
+    }
+
+    #[test]
+    fn history_does_not_carry_discontinued_series() {
+        let inputs = MarketInputs::default()
+            .with_series(FredSeries::TedSpread, daily(&[("2022-01-21", 0.2)]))
+            .with_series(
+                FredSeries::Vix,
+                daily(&[
+                    ("2022-01-21", 29.0),
+                    ("2022-01-28", 27.0),
+                    ("2022-01-31", 25.0),
+                ]),
+            );
+
+        let history = history(MetricId::LiquidityPremium, &inputs, None);
+        let values: Vec<f64> = history.iter().map(|obs| obs.value).collect();
+        assert_eq!(values, [0.2, 0.2, 2.5]);
+    }
+
+    #[test]
+    fn history_skips_dates_without_every_input() {
+        let inputs = MarketInputs::default()
+            .with_series(
+                FredSeries::Treasury10y,
+                daily(&[("2024-01-02", 4.0), ("2024-03-01", 4.2)]),
+            )
+            .with_series(FredSeries::Treasury2y, daily(&[("2024-03-01", 4.6)]));
+
+        let history = history(MetricId::TermPremium, &inputs, None);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].date, date("2024-03-01"));
+        assert!(super::history(MetricId::Vix, &inputs, None).is_empty());
+    }
+
+    #[test]
+    fn history_uses_earnings_known_at_each_date() {
+        let inputs = MarketInputs::default().with_series(
+            FredSeries::Sp500,
+            daily(&[
+                ("2023-12-29", 4000.0),
+                ("2024-01-02", 5000.0),
+                ("2024-04-01", 5000.0),
+            ]),
+        );
+        let table = EarningsTable::new(
+            "Maintained CSV",
+            EarningsBasis::Trailing,
+            vec![
+                EarningsRow {
+                    date: date("2024-01-01"),
+                    trailing_eps: Some(200.0),
+                    forward_eps: None,
+                },
+                EarningsRow {
+                    date: date("2024-03-31"),
+                    trailing_eps: Some(250.0),
+                    forward_eps: None,
+                },
+            ],
+        );
+
+        // Nothing was published before 2024-01-01, so the 29th is left out
+        let history = history(MetricId::EarningsYield, &inputs, Some(&table));
+        let values: Vec<f64> = history.iter().map(|obs| obs.value).collect();
+        assert_eq!(history[0].date, date("2024-01-02"));
+        assert_eq!(values, [4.0, 5.0]);
+
+        let assumed = super::history(MetricId::EarningsYield, &inputs, None);
+        assert_eq!(assumed.len(), 3);
+        assert_close(Some(assumed[0].value), 100.0 / ASSUMED_PE);
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Access level of an account. Each role includes everything below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    ReadOnly,
+    Analyst,
+    Admin,
+}
+
+impl Role {
+    /// Whether this role may perform an action that requires `required`.
+    pub fn includes(self, required: Role) -> bool {
+        self >= required
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Role::ReadOnly => "Read-only",
+            Role::Analyst => "Analyst",
+            Role::Admin => "Admin",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn roles_serialize_as_snake_case() {
+        assert_eq!(
+            serde_json::to_value(Role::ReadOnly).unwrap(),
+            json!("read_only")
+        );
+        assert_eq!(
+            serde_json::to_value(Role::Analyst).unwrap(),
+            json!("analyst")
+        );
+        assert_eq!(serde_json::to_value(Role::Admin).unwrap(), json!("admin"));
+        assert_eq!(
+            serde_json::from_value::<Role>(json!("read_only")).unwrap(),
+            Role::ReadOnly
+        );
+    }
+
+    #[test]
+    fn higher_roles_include_lower_ones() {
+        assert!(Role::Admin.includes(Role::Analyst));
+        assert!(Role::Analyst.includes(Role::ReadOnly));
+        assert!(Role::Analyst.includes(Role::Analyst));
+        assert!(!Role::Analyst.includes(Role::Admin));
+        assert!(!Role::ReadOnly.includes(Role::Analyst));
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// Second login step, sent with the token from the first `LoginResponse`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub two_factor_token: String,
+    /// A 6-digit authenticator code or one of the recovery codes
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+    /// The URI rendered as an SVG QR code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr_svg: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmTwoFactorRequest {
+    pub code: String,
+}
+
+/// Shown once after enrollment; only hashes are kept afterwards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryCodes {
+    pub codes: Vec<String>,
+}
+
+/// Users turning off their own 2FA must prove they still hold it; admins
+/// resetting someone else's do not.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisableTwoFactorRequest {
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn two_factor_login_request_round_trip() {
+        let request = TwoFactorLoginRequest {
+            two_factor_token: "abc".to_string(),
+            code: "123456".to_string(),
+        };
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value,
+            json!({ "two_factor_token": "abc", "code": "123456" })
+        );
+        assert_eq!(
+            serde_json::from_value::<TwoFactorLoginRequest>(value).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn enrollment_round_trip() {
+        let enrollment = TotpEnrollment {
+            secret: "JBSWY3DPEHPK3PXP".to_string(),
+            otpauth_uri: "otpauth://totp/x".to_string(),
+            qr_svg: None,
+        };
+
+        let value = serde_json::to_value(&enrollment).unwrap();
+        assert_eq!(
+            value,
+            json!({ "secret": "JBSWY3DPEHPK3PXP", "otpauth_uri": "otpauth://totp/x" })
+        );
+        assert_eq!(
+            serde_json::from_value::<TotpEnrollment>(value).unwrap(),
+            enrollment
+        );
+    }
+
+    #[test]
+    fn confirm_and_recovery_codes_round_trip() {
+        let confirm = ConfirmTwoFactorRequest {
+            code: "654321".to_string(),
+        };
+        let value = serde_json::to_value(&confirm).unwrap();
+        assert_eq!(value, json!({ "code": "654321" }));
+        assert_eq!(
+            serde_json::from_value::<ConfirmTwoFactorRequest>(value).unwrap(),
+            confirm
+        );
+
+        let codes = RecoveryCodes {
+            codes: vec!["abcde-fghjk".to_string()],
+        };
+        let value = serde_json::to_value(&codes).unwrap();
+        assert_eq!(value, json!({ "codes": ["abcde-fghjk"] }));
+        assert_eq!(
+            serde_json::from_value::<RecoveryCodes>(value).unwrap(),
+            codes
+        );
+    }
+
+    #[test]
+    fn disable_request_code_is_optional() {
+        let request = DisableTwoFactorRequest {
+            username: "analyst".to_string(),
+            code: None,
+        };
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value, json!({ "username": "analyst" }));
+        assert_eq!(
+            serde_json::from_value::<DisableTwoFactorRequest>(value).unwrap(),
+            request
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::roles::Role;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserSummary {
+    pub username: String,
+    pub role: Role,
+    pub disabled: bool,
+    #[serde(default)]
+    pub two_factor_enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub password_changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub password: String,
+    #[serde(default = "default_new_user_role")]
+    pub role: Role,
+}
+
+// New accounts get the least privilege unless a role is asked for
+fn default_new_user_role() -> Role {
+    Role::ReadOnly
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetUserRoleRequest {
+    pub username: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetUserDisabledRequest {
+    pub username: String,
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub username: String,
+    pub new_password: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn user_summary_round_trip() {
+        let created = Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let summary = UserSummary {
+            username: "analyst".to_string(),
+            role: Role::Analyst,
+            disabled: true,
+            two_factor_enabled: true,
+            created_at: created,
+            password_changed_at: created,
+        };
+
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "username": "analyst",
+                "role": "analyst",
+                "disabled": true,
+                "two_factor_enabled": true,
+                "created_at": "2025-01-02T03:04:05Z",
+                "password_changed_at": "2025-01-02T03:04:05Z"
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<UserSummary>(value).unwrap(),
+            summary
+        );
+    }
+
+    #[test]
+    fn user_management_requests_round_trip() {
+        let create = CreateUserRequest {
+            username: "analyst".to_string(),
+            password: "s3cret".to_string(),
+            role: Role::Analyst,
+        };
+        let value = serde_json::to_value(&create).unwrap();
+        assert_eq!(
+            value,
+            json!({ "username": "analyst", "password": "s3cret", "role": "analyst" })
+        );
+        assert_eq!(
+            serde_json::from_value::<CreateUserRequest>(value).unwrap(),
+            create
+        );
+
+        let without_role = json!({ "username": "viewer", "password": "s3cret" });
+        let parsed = serde_json::from_value::<CreateUserRequest>(without_role).unwrap();
+        assert_eq!(parsed.role, Role::ReadOnly);
+
+        let set_role = SetUserRoleRequest {
+            username: "analyst".to_string(),
+            role: Role::Admin,
+        };
+        let value = serde_json::to_value(&set_role).unwrap();
+        assert_eq!(value, json!({ "username": "analyst", "role": "admin" }));
+        assert_eq!(
+            serde_json::from_value::<SetUserRoleRequest>(value).unwrap(),
+            set_role
+        );
+
+        let disable = SetUserDisabledRequest {
+            username: "analyst".to_string(),
+            disabled: true,
+        };
+        let value = serde_json::to_value(&disable).unwrap();
+        assert_eq!(value, json!({ "username": "analyst", "disabled": true }));
+        assert_eq!(
+            serde_json::from_value::<SetUserDisabledRequest>(value).unwrap(),
+            disable
+        );
+
+        let reset = ResetPasswordRequest {
+            username: "analyst".to_string(),
+            new_password: "n3w".to_string(),
+        };
+        let value = serde_json::to_value(&reset).unwrap();
+        assert_eq!(
+            value,
+            json!({ "username": "analyst", "new_password": "n3w" })
+        );
+        assert_eq!(
+            serde_json::from_value::<ResetPasswordRequest>(value).unwrap(),
+            reset
+        );
+    }
+}
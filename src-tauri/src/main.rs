@@ -1,101 +1,483 @@
 use tauri::Manager;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Mutex;
+use chrono::Utc;
+use portal_auth::totp;
+use portal_auth::{store_error, AuditLog, Authenticator, LoginStep, NewPassword, SessionLimits, UserStore};
+use portal_market::{AlertMonitor, AlertStore, EarningsStore, FredCache, FredClient, FredService};
+use portal_types::{
+    AcknowledgeAlertsRequest, AlertRule, AlertsResponse, ApiError, AuditEntry, AuditEvent, AuditOutcome, AuditQuery, ConfirmTwoFactorRequest, CreateUserRequest, DisableTwoFactorRequest, EarningsBasis, EarningsSource, EarningsStatus,
+    ImportEarningsRequest, LoginRequest, LoginResponse,
+    RecoveryCodes, ResetPasswordRequest, Role, SetUserDisabledRequest, SetUserRoleRequest, TotpEnrollment,
+    SaveAlertRulesRequest, TwoFactorLoginRequest, UserInfo, UserSummary,
+};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_notification::NotificationExt;
 
-#[derive(Debug, Deserialize)]
-struct LoginRequest {
-    username: String,
-    password: String,
+const FRED_API_URL: &str = "https://api.stlouisfed.org/fred";
+const ALERT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// FRED observations cached in the app data directory, the S&P 500 earnings
+// and the alert rules checked against them, opened during setup
+struct MarketState {
+    fred: FredService,
+    earnings: EarningsStore,
+    alerts: AlertMonitor,
 }
 
-#[derive(Debug, Serialize)]
-struct LoginResponse {
-    success: bool,
-    message: String,
+// Recorded with the window the change came from, like logins
+fn admin_entry(actor: &str, client: &str, event: AuditEvent, detail: String) -> AuditEntry {
+    AuditEntry {
+        client: Some(client.to_string()),
+        detail: Some(detail),
+        ..AuditEntry::new(actor, event, AuditOutcome::Success)
+    }
 }
 
-#[derive(Debug, Serialize)]
-struct UserInfo {
-    username: String,
-    logged_in: bool,
+// Each window that has logged in is a client of its own. Commands that hash
+// passwords run off the main thread so the window stays responsive.
+#[tauri::command(async)]
+fn login(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    request: LoginRequest,
+) -> Result<LoginResponse, ApiError> {
+    match auth.login(&request.username, &request.password, window.label(), Utc::now())? {
+        LoginStep::Session(record) => Ok(LoginResponse {
+            session_token: Some(record.token),
+            ..LoginResponse::new("Login successful")
+        }),
+        LoginStep::TwoFactor(challenge) => Ok(LoginResponse {
+            two_factor_token: Some(challenge),
+            ..LoginResponse::new("Enter your authentication code")
+        }),
+    }
 }
 
-// Global state for user session
-struct AppState {
-    logged_in_user: Mutex<Option<String>>,
+#[tauri::command]
+fn login_two_factor(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    request: TwoFactorLoginRequest,
+) -> Result<LoginResponse, ApiError> {
+    let record = auth.login_two_factor(&request.two_factor_token, &request.code, window.label(), Utc::now())?;
+    Ok(LoginResponse {
+        session_token: Some(record.token),
+        ..LoginResponse::new("Login successful")
+    })
 }
 
-// Valid users (same as your backend)
-fn get_valid_users() -> HashMap<String, String> {
-    let mut users = HashMap::new();
-    users.insert("manetas & stevens associates".to_string(), "123".to_string());
-    users.insert("admin".to_string(), "admin123".to_string());
-    users.insert("user1".to_string(), "password1".to_string());
-    users.insert("user2".to_string(), "password2".to_string());
-    users
+#[tauri::command]
+fn logout(window: tauri::Window, auth: tauri::State<Authenticator>, token: String) -> LoginResponse {
+    auth.logout(&token, window.label());
+    LoginResponse::new("Logged out successfully")
 }
 
+// Polled by the dashboard, so it must not extend the idle timeout itself
 #[tauri::command]
-fn login(state: tauri::State<AppState>, request: LoginRequest) -> LoginResponse {
-    let valid_users = get_valid_users();
-    
-    if let Some(stored_password) = valid_users.get(&request.username) {
-        if stored_password == &request.password {
-            // Successful login
-            let mut logged_in_user = state.logged_in_user.lock().unwrap();
-            *logged_in_user = Some(request.username.clone());
-            
-            return LoginResponse {
-                success: true,
-                message: "Login successful".to_string(),
-            };
-        }
-    }
-    
-    // Failed login
-    LoginResponse {
-        success: false,
-        message: "Invalid username or password".to_string(),
+fn check_auth(auth: tauri::State<Authenticator>, token: Option<String>) -> UserInfo {
+    match token.and_then(|token| auth.session_user(&token, false)) {
+        Some(user) => auth.user_info(&user),
+        None => UserInfo::logged_out(),
     }
 }
 
 #[tauri::command]
-fn logout(state: tauri::State<AppState>) -> LoginResponse {
-    let mut logged_in_user = state.logged_in_user.lock().unwrap();
-    *logged_in_user = None;
-    
-    LoginResponse {
-        success: true,
-        message: "Logged out successfully".to_string(),
-    }
+fn refresh_session(auth: tauri::State<Authenticator>, token: String) -> Result<UserInfo, ApiError> {
+    let user = auth.require_role(&token, Role::ReadOnly)?;
+    Ok(auth.user_info(&user))
 }
 
 #[tauri::command]
-fn check_auth(state: tauri::State<AppState>) -> UserInfo {
-    let logged_in_user = state.logged_in_user.lock().unwrap();
-    
-    if let Some(username) = logged_in_user.as_ref() {
-        UserInfo {
-            username: username.clone(),
-            logged_in: true,
-        }
+fn list_users(
+    auth: tauri::State<Authenticator>,
+    token: String,
+) -> Result<Vec<UserSummary>, ApiError> {
+    auth.require_role(&token, Role::Admin)?;
+    Ok(auth.users.lock().unwrap().list())
+}
+
+#[tauri::command(async)]
+fn create_user(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    token: String,
+    request: CreateUserRequest,
+) -> Result<UserSummary, ApiError> {
+    let actor = auth.require_role(&token, Role::Admin)?;
+    let password = NewPassword::hash(&request.password).map_err(store_error)?;
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .create_user_hashed(&request.username, password, request.role)
+        .map_err(store_error)?;
+
+    auth.audit(admin_entry(
+        &actor.session.username,
+        window.label(),
+        AuditEvent::UserCreated,
+        format!("{} as {}", summary.username, summary.role.label()),
+    ));
+    Ok(summary)
+}
+
+#[tauri::command]
+fn set_user_disabled(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    token: String,
+    request: SetUserDisabledRequest,
+) -> Result<UserSummary, ApiError> {
+    let actor = auth.require_role(&token, Role::Admin)?;
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .set_disabled(&request.username, request.disabled)
+        .map_err(store_error)?;
+
+    let event = if summary.disabled {
+        auth.sessions.lock().unwrap().revoke_user(&summary.username);
+        AuditEvent::UserDisabled
     } else {
-        UserInfo {
-            username: "".to_string(),
-            logged_in: false,
+        AuditEvent::UserEnabled
+    };
+    auth.audit(admin_entry(&actor.session.username, window.label(), event, summary.username.clone()));
+    Ok(summary)
+}
+
+#[tauri::command]
+fn set_user_role(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    token: String,
+    request: SetUserRoleRequest,
+) -> Result<UserSummary, ApiError> {
+    let actor = auth.require_role(&token, Role::Admin)?;
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .set_role(&request.username, request.role)
+        .map_err(store_error)?;
+
+    auth.audit(admin_entry(
+        &actor.session.username,
+        window.label(),
+        AuditEvent::RoleChanged,
+        format!("{} to {}", summary.username, summary.role.label()),
+    ));
+    Ok(summary)
+}
+
+#[tauri::command(async)]
+fn reset_password(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    token: String,
+    request: ResetPasswordRequest,
+) -> Result<UserSummary, ApiError> {
+    let actor = auth.require_role(&token, Role::Admin)?;
+    let password = NewPassword::hash(&request.new_password).map_err(store_error)?;
+    let summary = auth
+        .users
+        .lock()
+        .unwrap()
+        .reset_password_hashed(&request.username, password)
+        .map_err(store_error)?;
+
+    // Sessions opened with the old password should not outlive it
+    auth.sessions.lock().unwrap().revoke_user(&summary.username);
+    auth.audit(admin_entry(&actor.session.username, window.label(), AuditEvent::PasswordReset, summary.username.clone()));
+    Ok(summary)
+}
+
+#[tauri::command]
+fn begin_two_factor_enrollment(
+    auth: tauri::State<Authenticator>,
+    token: String,
+) -> Result<TotpEnrollment, ApiError> {
+    let user = auth.require_role(&token, Role::ReadOnly)?;
+    let secret = auth
+        .users
+        .lock()
+        .unwrap()
+        .begin_totp_enrollment(&user.session.username)
+        .map_err(store_error)?;
+
+    Ok(totp::enrollment(&user.session.username, &secret))
+}
+
+#[tauri::command]
+fn confirm_two_factor(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    token: String,
+    request: ConfirmTwoFactorRequest,
+) -> Result<RecoveryCodes, ApiError> {
+    let user = auth.require_role(&token, Role::ReadOnly)?;
+    let codes = auth
+        .users
+        .lock()
+        .unwrap()
+        .confirm_totp(&user.session.username, &request.code, Utc::now())
+        .map_err(store_error)?;
+
+    auth.audit(AuditEntry {
+        client: Some(window.label().to_string()),
+        ..AuditEntry::new(&user.session.username, AuditEvent::TwoFactorEnabled, AuditOutcome::Success)
+    });
+    Ok(RecoveryCodes { codes })
+}
+
+// Users need a current code to turn off their own second factor; admins can
+// turn it off for anyone who has lost their device
+#[tauri::command]
+fn disable_two_factor(
+    window: tauri::Window,
+    auth: tauri::State<Authenticator>,
+    token: String,
+    request: DisableTwoFactorRequest,
+) -> Result<UserSummary, ApiError> {
+    let actor = auth.require_role(&token, Role::ReadOnly)?;
+    auth.disable_two_factor(&actor, &request.username, request.code.as_deref(), window.label(), Utc::now())
+}
+
+#[tauri::command]
+fn query_audit_log(
+    auth: tauri::State<Authenticator>,
+    token: String,
+    query: AuditQuery,
+) -> Result<Vec<AuditEntry>, ApiError> {
+    auth.require_role(&token, Role::Admin)?;
+    auth.audit_log.query(&query).map_err(|e| {
+        log::error!("Could not read audit log: {}", e);
+        ApiError::internal("Could not read audit log")
+    })
+}
+
+#[tauri::command]
+fn list_alerts(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+) -> Result<AlertsResponse, ApiError> {
+    auth.require_role(&token, Role::ReadOnly)?;
+    Ok(market.alerts.store.lock().unwrap().alerts())
+}
+
+#[tauri::command]
+fn acknowledge_alerts(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+    request: AcknowledgeAlertsRequest,
+) -> Result<AlertsResponse, ApiError> {
+    auth.require_role(&token, Role::ReadOnly)?;
+    let mut store = market.alerts.store.lock().unwrap();
+    store.acknowledge(&request.ids).map_err(alerts_error)?;
+    Ok(store.alerts())
+}
+
+#[tauri::command]
+fn alert_rules(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+) -> Result<Vec<AlertRule>, ApiError> {
+    auth.require_role(&token, Role::ReadOnly)?;
+    Ok(market.alerts.store.lock().unwrap().rules().to_vec())
+}
+
+#[tauri::command]
+fn save_alert_rules(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+    request: SaveAlertRulesRequest,
+) -> Result<Vec<AlertRule>, ApiError> {
+    auth.require_role(&token, Role::Analyst)?;
+    let mut store = market.alerts.store.lock().unwrap();
+    Ok(store.set_rules(request.rules)?.to_vec())
+}
+
+#[tauri::command]
+async fn check_alerts(
+    app: tauri::AppHandle,
+    auth: tauri::State<'_, Authenticator>,
+    token: String,
+) -> Result<AlertsResponse, ApiError> {
+    auth.require_role(&token, Role::Analyst)?;
+    check_and_notify(&app).await;
+    let market = app.state::<MarketState>();
+    let response = market.alerts.store.lock().unwrap().alerts();
+    Ok(response)
+}
+
+#[tauri::command]
+fn earnings_status(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+) -> Result<EarningsStatus, ApiError> {
+    auth.require_role(&token, Role::ReadOnly)?;
+    Ok(market.earnings.status())
+}
+
+#[tauri::command]
+fn import_earnings(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+    request: ImportEarningsRequest,
+) -> Result<EarningsStatus, ApiError> {
+    let actor = auth.require_role(&token, Role::Analyst)?;
+    market.earnings.import(&request, actor.username(), Utc::now())?;
+    Ok(market.earnings.status())
+}
+
+#[tauri::command]
+fn clear_earnings_import(
+    auth: tauri::State<Authenticator>,
+    market: tauri::State<MarketState>,
+    token: String,
+) -> Result<EarningsStatus, ApiError> {
+    auth.require_role(&token, Role::Analyst)?;
+    market.earnings.clear_import().map_err(|e| {
+        log::error!("Could not remove earnings import: {}", e);
+        ApiError::internal("Could not remove the imported file")
+    })?;
+    Ok(market.earnings.status())
+}
+
+fn alerts_error(error: std::io::Error) -> ApiError {
+    log::error!("Could not write alerts: {}", error);
+    ApiError::internal("Could not save the alerts")
+}
+
+// Checks the rules with the current earnings source and shows a system
+// notification for each new alert.
+async fn check_and_notify(app: &tauri::AppHandle) {
+    let market = app.state::<MarketState>();
+    let earnings = market.earnings.source();
+    let earnings = earnings.as_ref().map(|table| table as &(dyn EarningsSource + Sync));
+    let raised = match market.alerts.check(&market.fred, earnings, Utc::now()).await {
+        Ok(raised) => raised,
+        Err(e) => {
+            log::error!("Could not write alerts: {}", e);
+            return;
+        }
+    };
+
+    for alert in raised {
+        let shown = app
+            .notification()
+            .builder()
+            .title(&alert.rule_name)
+            .body(format!("{} ({:.2} on {})", alert.message, alert.value, alert.date))
+            .show();
+        if let Err(e) = shown {
+            log::warn!("Could not show notification: {}", e);
         }
     }
 }
 
+// Opens the FRED cache, earnings and alert rules in the app data directory.
+// Without FRED_API_KEY only cached observations are used. The maintained
+// earnings CSV is EARNINGS_FILE, or sp500-earnings.csv next to the other data.
+fn open_market_state(app: &tauri::App) -> Result<MarketState, Box<dyn std::error::Error>> {
+    let data_dir = app.path().app_data_dir()?;
+    let client = FredClient::new(FRED_API_URL, std::env::var("FRED_API_KEY").ok());
+    let earnings_file = std::env::var("EARNINGS_FILE")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| data_dir.join("sp500-earnings.csv"));
+    let earnings_basis = std::env::var("EARNINGS_BASIS")
+        .ok()
+        .and_then(|v| EarningsBasis::from_label(&v))
+        .unwrap_or_default();
+
+    Ok(MarketState {
+        fred: FredService::new(client, FredCache::open(data_dir.join("fred-cache.json"))),
+        earnings: EarningsStore::open(earnings_file, data_dir.join("earnings-import.json"), earnings_basis),
+        alerts: AlertMonitor::new(AlertStore::open(data_dir.join("alerts.json"))?),
+    })
+}
+
+// Opens the user store, seeding an admin account on first run.
+// A generated password is shown once in a dialog and never written to disk.
+fn open_user_store(app: &tauri::App) -> Result<UserStore, Box<dyn std::error::Error>> {
+    let data_dir = app.path().app_data_dir()?;
+    let mut store = UserStore::open(data_dir.join("users.json"))?;
+
+    // Earlier versions left the generated password here in plain text
+    match std::fs::remove_file(data_dir.join("initial-password.txt")) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    let username = std::env::var("LOGIN_USERNAME").unwrap_or_else(|_| "admin".to_string());
+    let password = std::env::var("LOGIN_PASSWORD").ok();
+    if let Some(generated) = store.bootstrap(&username, password)? {
+        log::warn!("Created initial user '{}' with a generated password", username);
+        app.dialog()
+            .message(format!(
+                "Sign in as '{}' with the password:\n\n{}\n\nIt is shown only this once. \
+                 Change it after logging in.",
+                username, generated
+            ))
+            .title("Initial admin account")
+            .show(|_| {});
+    }
+
+    Ok(store)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // The shared crates report through `log`, e.g. audit writes that fail during login
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+
     tauri::Builder::default()
-        .manage(AppState {
-            logged_in_user: Mutex::new(None),
-        })
-        .invoke_handler(tauri::generate_handler![login, logout, check_auth])
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            login,
+            login_two_factor,
+            logout,
+            check_auth,
+            refresh_session,
+            list_users,
+            create_user,
+            set_user_disabled,
+            set_user_role,
+            reset_password,
+            begin_two_factor_enrollment,
+            confirm_two_factor,
+            disable_two_factor,
+            query_audit_log,
+            list_alerts,
+            acknowledge_alerts,
+            alert_rules,
+            save_alert_rules,
+            check_alerts,
+            earnings_status,
+            import_earnings,
+            clear_earnings_import
+        ])
         .setup(|app| {
+            let store = open_user_store(app)?;
+            let audit_log = AuditLog::new(app.path().app_data_dir()?.join("audit.jsonl"));
+            app.manage(Authenticator::new(store, SessionLimits::default(), audit_log));
+            app.manage(open_market_state(app)?);
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(ALERT_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    check_and_notify(&handle).await;
+                }
+            });
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -109,4 +491,4 @@ pub fn run() {
 
 fn main() {
     run();
-} 
\ No newline at end of file
+}